@@ -0,0 +1,502 @@
+/*
+
+Procedural macros for RustUI
+
+The `view!` macro is a declarative alternative to the `VStack!`/`HStack!` macros
+which validates widget ids and builder methods at compile time.
+
+It is invoked through `RustUI::view!`, which passes its `$crate` path along
+so the generated code works when the library is renamed.
+
+*/
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenTree};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{braced, parenthesized, parse_macro_input, token, Expr, ExprLit, Ident, Lit, LitStr, Token};
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// ========================== Known Builders ========================== //
+
+/// Views which may be declared as `Name { children.. }`
+const VIEWS: &[&str] = &["VStack", "HStack", "Overlay"];
+
+/// Builder methods shared by all views (see the `View` trait)
+const VIEW_METHODS: &[&str] = &["alignment", "fixed_width", "fixed_height", "fixed_size", "padding"];
+
+/// Builder methods of the library's widgets.
+/// Widgets not listed here (such as user-defined widgets) are not validated.
+// NOTE: Keep this in sync with the widget builder methods
+const WIDGET_METHODS: &[(&str, &[&str])] = &[
+    ("Button", &[
+        "with_text", "with_on_click", "with_width", "with_height", "with_dimensions",
+        "with_base_color", "with_hover_color", "with_click_color", "with_message",
+        "with_transition", "with_size_transition", "with_corner_radius", "with_skin", "with_icon",
+    ]),
+    ("Text", &[
+        "with_point_size", "center", "with_text_update", "with_color", "with_rgb", "with_rgba", "auto_resize",
+        "with_wrap_width", "with_max_lines", "with_text_align",
+    ]),
+    ("TextBox", &[
+        "with_default_text", "with_on_text_changed", "with_on_text_submit",
+        "with_on_text_changed_message", "with_on_text_submit_message",
+    ]),
+    ("CheckBox", &[
+        "with_text", "with_on_check", "check", "with_on_check_message",
+    ]),
+    ("ScrollBar", &[
+        "with_orientation", "with_length", "with_thickness", "with_on_value_changed",
+        "with_on_value_changed_message",
+    ]),
+    ("Image", &[
+        "with_on_click", "with_hover_border", "with_hover_border_width", "with_hover_shade",
+        "with_hover_color", "with_click_color", "with_message", "with_transition",
+        "with_fit", "with_alignment", "with_nine_slice", "with_color_key", "with_tint", "with_opacity",
+    ]),
+    ("RichText", &[
+        "with_wrap_width", "with_color", "with_point_size",
+    ]),
+    ("AnimatedImage", &[
+        "with_size", "with_playing", "paused", "with_loop", "with_on_click", "with_message",
+    ]),
+    ("Canvas", &[
+        "with_on_draw", "with_background", "with_on_mouse_down", "with_on_mouse_up",
+        "with_on_mouse_drag", "with_on_mouse_move",
+    ]),
+];
+
+fn widget_methods(widget: &str) -> Option<&'static [&'static str]> {
+    WIDGET_METHODS.iter()
+        .find(|(name, _)| *name == widget)
+        .map(|(_, methods)| *methods)
+}
+
+// ========================== Syntax Tree ========================== //
+
+/// `crate_path; Root { .. }` as passed by `RustUI::view!`
+struct ViewInput {
+    /// Path to the RustUI crate (`$crate`)
+    crate_path: proc_macro2::TokenStream,
+    root: ViewBlock,
+}
+
+/// A builder method call such as `.padding(10, 10, 5, 0)`
+struct MethodCall {
+    name: Ident,
+    args: Punctuated<Expr, Token![,]>,
+}
+
+/// A view declared as `VStack { children.. }` followed by builder methods
+struct ViewBlock {
+    name: Ident,
+    children: Punctuated<ViewNode, Token![,]>,
+    methods: Vec<MethodCall>,
+}
+
+/// An item within a view block
+enum ViewNode {
+    View(ViewBlock),
+    /// Any expression implementing `IntoViewComponent` (usually a widget builder chain)
+    Item(Expr),
+}
+
+impl Parse for ViewBlock {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+
+        let content;
+        braced!(content in input);
+        let children = content.parse_terminated(ViewNode::parse, Token![,])?;
+
+        let mut methods = Vec::new();
+        while input.peek(Token![.]) {
+            input.parse::<Token![.]>()?;
+            let method_name: Ident = input.parse()?;
+
+            let args;
+            parenthesized!(args in input);
+
+            methods.push(MethodCall {
+                name: method_name,
+                args: args.parse_terminated(Expr::parse, Token![,])?,
+            });
+        }
+
+        Ok(ViewBlock {
+            name,
+            children,
+            methods,
+        })
+    }
+}
+
+impl Parse for ViewInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut crate_path = proc_macro2::TokenStream::new();
+        while !input.peek(Token![;]) {
+            crate_path.extend(Some(input.parse::<TokenTree>()?));
+        }
+        input.parse::<Token![;]>()?;
+
+        Ok(ViewInput {
+            crate_path,
+            root: input.parse()?,
+        })
+    }
+}
+
+impl Parse for ViewNode {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Ident) && input.peek2(token::Brace) {
+            Ok(ViewNode::View(input.parse()?))
+        } else {
+            Ok(ViewNode::Item(input.parse()?))
+        }
+    }
+}
+
+// ========================== Validation ========================== //
+
+/// Tracks ids and errors across an entire `view!` invocation
+struct Validator {
+    /// id -> where it was first used
+    ids: HashMap<String, Span>,
+    errors: Vec<syn::Error>,
+
+    /// Distinguishes auto-generated ids between `view!` call sites
+    call_site_hash: u64,
+}
+
+/// Numbers auto-generated ids, so identical invocations still receive distinct ids
+static AUTO_ID_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+impl Validator {
+    fn new(call_site_hash: u64) -> Self {
+        Validator {
+            ids: HashMap::new(),
+            errors: Vec::new(),
+            call_site_hash,
+        }
+    }
+
+    fn error(&mut self, span: Span, message: String) {
+        self.errors.push(syn::Error::new(span, message));
+    }
+
+    fn register_id(&mut self, id: &LitStr) {
+        let value = id.value();
+
+        // Empty ids belong to sub-widgets and are assigned at runtime
+        if value.is_empty() {
+            return;
+        }
+
+        if let Some(first_use) = self.ids.get(&value).cloned() {
+            self.error(id.span(), format!("duplicate widget id `{}`", value));
+            self.error(first_use, format!("`{}` is first used here", value));
+        } else {
+            self.ids.insert(value, id.span());
+        }
+    }
+
+    fn auto_id(&mut self, widget: &str, span: Span) -> LitStr {
+        let count = AUTO_ID_COUNT.fetch_add(1, Ordering::Relaxed);
+        let id = format!("__auto_{}_{:x}_{}", widget, self.call_site_hash, count);
+        LitStr::new(&id, span)
+    }
+
+    fn check_methods(&mut self, owner: &str, allowed: &[&str], methods: &[&Ident]) {
+        for method in methods {
+            let name = method.to_string();
+            if !allowed.contains(&name.as_str()) {
+                self.error(method.span(), format!(
+                    "no builder method `{}` for `{}`; expected one of: {}",
+                    name, owner, allowed.join(", ")
+                ));
+            }
+        }
+    }
+
+    fn validate_view(&mut self, view: &mut ViewBlock) {
+        let name = view.name.to_string();
+        if !VIEWS.contains(&name.as_str()) {
+            self.error(view.name.span(), format!(
+                "`{}` cannot be declared as a view block; expected one of: {}",
+                name, VIEWS.join(", ")
+            ));
+        }
+
+        let methods: Vec<&Ident> = view.methods.iter().map(|method| &method.name).collect();
+        self.check_methods(&name, VIEW_METHODS, &methods);
+
+        for child in view.children.iter_mut() {
+            match child {
+                ViewNode::View(subview) => self.validate_view(subview),
+                ViewNode::Item(item) => self.validate_widget(item),
+            }
+        }
+    }
+
+    /// Validates builder chains of the form `Widget::new(id, ..).method(..)..`
+    fn validate_widget(&mut self, item: &mut Expr) {
+        // Walk the chain down to the constructor
+        let mut methods = Vec::new();
+        let mut current = item;
+        while let Expr::MethodCall(call) = current {
+            methods.push(call.method.clone());
+            current = &mut *call.receiver;
+        }
+
+        let call = match current {
+            Expr::Call(call) => call,
+            // Not a builder chain (nested macro, variable, etc.)
+            _ => return,
+        };
+
+        let (widget, constructor) = match &*call.func {
+            Expr::Path(path) => {
+                let segments = &path.path.segments;
+                if segments.len() < 2 {
+                    return;
+                }
+                (segments[segments.len() - 2].ident.clone(), segments[segments.len() - 1].ident.clone())
+            }
+            _ => return,
+        };
+
+        let widget_name = widget.to_string();
+
+        // The first argument of every widget constructor is its id
+        if constructor == "new" {
+            if let Some(first_arg) = call.args.first_mut() {
+                match first_arg {
+                    Expr::Lit(ExprLit { lit: Lit::Str(id), .. }) => {
+                        let id = id.clone();
+                        self.register_id(&id);
+                    }
+                    Expr::Infer(infer) => {
+                        let id = self.auto_id(&widget_name, infer.underscore_token.span);
+                        *first_arg = Expr::Lit(ExprLit { attrs: Vec::new(), lit: Lit::Str(id) });
+                    }
+                    // Ids computed at runtime cannot be checked here
+                    _ => {}
+                }
+            }
+        }
+
+        if let Some(allowed) = widget_methods(&widget_name) {
+            methods.reverse();
+            let methods: Vec<&Ident> = methods.iter().collect();
+            self.check_methods(&widget_name, allowed, &methods);
+        }
+    }
+}
+
+// ========================== Expansion ========================== //
+
+fn expand_view(view: &ViewBlock, crate_path: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let name = &view.name;
+    // Hygienic, so user expressions mentioning their own `components` are unaffected
+    let components = Ident::new("components", Span::mixed_site());
+
+    let children = view.children.iter().map(|child| {
+        let child = match child {
+            ViewNode::View(subview) => expand_view(subview, crate_path),
+            ViewNode::Item(item) => quote!(#item),
+        };
+
+        quote! {
+            #components.push(#crate_path::view_components::IntoViewComponent::as_component(#child));
+        }
+    });
+
+    let methods = view.methods.iter().map(|method| {
+        let method_name = &method.name;
+        let args = &method.args;
+        quote!(.#method_name(#args))
+    });
+
+    quote! {
+        {
+            #[allow(unused_imports)]
+            use #crate_path::view_components::views::View as _;
+
+            let mut #components = Vec::new();
+            #(#children)*
+
+            #crate_path::view_components::views::#name::new(#components)
+                #(#methods)*
+        }
+    }
+}
+
+/// Validates & expands a `view!` invocation
+/// - `call_site`: Location of the invocation, used to seed generated ids
+fn expand(input: ViewInput, call_site: &str) -> proc_macro2::TokenStream {
+    let ViewInput { crate_path, mut root } = input;
+
+    let mut hasher = DefaultHasher::new();
+    call_site.hash(&mut hasher);
+
+    let mut validator = Validator::new(hasher.finish());
+    validator.validate_view(&mut root);
+
+    let mut errors = validator.errors.into_iter();
+    if let Some(mut error) = errors.next() {
+        for other in errors {
+            error.combine(other);
+        }
+        // Wrapped in a block so multiple errors are valid in expression position
+        let errors = error.to_compile_error();
+        return quote!({ #errors unreachable!() });
+    }
+
+    expand_view(&root, &crate_path)
+}
+
+/// Implementation of `RustUI::view!` (see its documentation)
+/// - Expects the path to RustUI followed by `;` before the root view
+#[proc_macro]
+pub fn view(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as ViewInput);
+
+    // The root view's name is written by the user (unlike tokens from `RustUI::view!`)
+    let location = input.root.name.span().unwrap();
+    let call_site = format!("{}:{}:{}", location.file(), location.line(), location.column());
+
+    expand(input, &call_site).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use std::path::Path;
+
+    fn expand_str(source: &str) -> String {
+        let input: ViewInput = syn::parse_str(source).expect("Failed to parse view");
+        expand(input, "test.rs:1:1").to_string()
+    }
+
+    /// Messages of `compile_error!` invocations within the expansion
+    fn errors(source: &str) -> Vec<String> {
+        let expansion = expand_str(source);
+        expansion.split("compile_error ! {").skip(1)
+            .map(|error| error.split('"').nth(1).unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn expands_with_crate_path() {
+        let expansion = expand_str("::renamed_ui; VStack { Button::new(\"a\").with_text(\"A\") }.padding(1, 2, 3, 4)");
+        assert!(expansion.contains(":: renamed_ui :: view_components :: views :: VStack :: new"));
+        assert!(!expansion.contains("RustUI"));
+    }
+
+    #[test]
+    fn duplicate_ids_are_errors() {
+        let errors = errors("$crate; VStack { Button::new(\"a\"), HStack { Text::new(\"a\", \"\") }, Text::new(\"\", \"\"), Text::new(\"\", \"\") }");
+        assert_eq!(errors, vec!["duplicate widget id `a`", "`a` is first used here"]);
+    }
+
+    #[test]
+    fn unknown_methods_are_errors() {
+        let errors = errors("$crate; VStack { Button::new(_).with_txt(\"A\"), Custom::new(\"c\").anything() }.centered()");
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("no builder method `centered` for `VStack`"));
+        assert!(errors[1].starts_with("no builder method `with_txt` for `Button`; expected one of: with_text,"));
+    }
+
+    #[test]
+    fn unknown_views_are_errors() {
+        let errors = errors("$crate; VStack { Grid { } }");
+        assert_eq!(errors, vec!["`Grid` cannot be declared as a view block; expected one of: VStack, HStack, Overlay"]);
+    }
+
+    #[test]
+    fn auto_ids_are_unique() {
+        let expansion = expand_str("$crate; VStack { Button::new(_), Button::new(_) }");
+        let ids: Vec<&str> = expansion.split('"').filter(|part| part.starts_with("__auto_Button_")).collect();
+        assert_eq!(ids.len(), 2);
+        assert_ne!(ids[0], ids[1]);
+    }
+
+    /// Builder methods (`self` -> `Self`) of each inherent impl in `source`, by type name
+    fn builder_methods(source: &str, methods: &mut HashMap<String, BTreeSet<String>>) {
+        let file = syn::parse_file(source).expect("Failed to parse source");
+
+        for item in file.items {
+            let item = match item {
+                syn::Item::Impl(item) if item.trait_.is_none() => item,
+                _ => continue,
+            };
+            let type_name = match &*item.self_ty {
+                syn::Type::Path(path) => path.path.segments.last().unwrap().ident.to_string(),
+                _ => continue,
+            };
+
+            for impl_item in item.items {
+                if let syn::ImplItem::Fn(method) = impl_item {
+                    let takes_self = match method.sig.inputs.first() {
+                        Some(syn::FnArg::Receiver(receiver)) => receiver.reference.is_none(),
+                        _ => false,
+                    };
+                    let returns_self = match &method.sig.output {
+                        syn::ReturnType::Type(_, output) => quote!(#output).to_string() == "Self",
+                        _ => false,
+                    };
+
+                    if takes_self && returns_self && matches!(method.vis, syn::Visibility::Public(_)) {
+                        methods.entry(type_name.clone()).or_default().insert(method.sig.ident.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn widget_methods_match_builders() {
+        let widgets_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../src/view_components/widgets");
+        let mut methods = HashMap::new();
+
+        for entry in std::fs::read_dir(widgets_dir).unwrap() {
+            let source = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            builder_methods(&source, &mut methods);
+        }
+
+        for (widget, listed) in WIDGET_METHODS {
+            let listed: BTreeSet<String> = listed.iter().map(|method| method.to_string()).collect();
+            assert_eq!(methods.get(*widget), Some(&listed), "builder methods of `{}`", widget);
+        }
+    }
+
+    #[test]
+    fn view_methods_match_trait() {
+        let source = std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("../src/view_components/views/mod.rs")).unwrap();
+        let file = syn::parse_file(&source).unwrap();
+
+        let view_trait = file.items.iter().find_map(|item| match item {
+            syn::Item::Trait(item) if item.ident == "View" => Some(item),
+            _ => None,
+        }).expect("Missing `View` trait");
+
+        let methods: BTreeSet<String> = view_trait.items.iter().filter_map(|item| match item {
+            syn::TraitItem::Fn(method) => match (method.sig.inputs.first(), &method.sig.output) {
+                (Some(syn::FnArg::Receiver(receiver)), syn::ReturnType::Type(_, output))
+                if receiver.reference.is_none() && quote!(#output).to_string() == "Self" => Some(method.sig.ident.to_string()),
+                _ => None,
+            },
+            _ => None,
+        }).collect();
+
+        assert_eq!(methods, VIEW_METHODS.iter().map(|method| method.to_string()).collect());
+    }
+}
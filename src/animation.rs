@@ -0,0 +1,640 @@
+/*
+
+Property animation & view transitions
+
+Animations are driven by the window loop's frame delta time (in seconds):
+- `Animated` values tween towards a target (e.g.: a button's hover color or size)
+- `TreeTransitions` fades widgets in & out and slides moved widgets
+  when the view tree is regenerated
+
+*/
+
+extern crate sdl2;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Texture, TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+
+use std::collections::HashMap;
+
+use crate::backend::system::window::Window;
+use crate::images;
+use crate::backend::system::state::GenerateView;
+use crate::view_components::ViewComponent;
+use crate::view_components::views::View;
+use crate::view_components::widgets::{Widget, WidgetState};
+
+// ========================== Easing Enum ========================== //
+
+/// Easing curves, mapping linear progress to eased progress
+/// ## Curves
+/// - `Linear` - Constant speed
+/// - `EaseIn` - Starts slow (cubic)
+/// - `EaseOut` - Ends slow (cubic)
+/// - `EaseInOut` - Starts & ends slow (cubic)
+#[derive(Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Eased progress for linear progress `t` within `0.0..=1.0`
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+// ========================== Lerp Trait ========================== //
+
+/// Linear interpolation between two values (`t` within `0.0..=1.0`)
+pub trait Lerp {
+    fn lerp(&self, target: &Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, target: &Self, t: f32) -> Self {
+        self + (target - self) * t
+    }
+}
+
+impl Lerp for i32 {
+    fn lerp(&self, target: &Self, t: f32) -> Self {
+        (*self as f32).lerp(&(*target as f32), t).round() as i32
+    }
+}
+
+impl Lerp for u32 {
+    fn lerp(&self, target: &Self, t: f32) -> Self {
+        (*self as f32).lerp(&(*target as f32), t).round().max(0.0) as u32
+    }
+}
+
+impl Lerp for u8 {
+    fn lerp(&self, target: &Self, t: f32) -> Self {
+        (*self as f32).lerp(&(*target as f32), t).round().clamp(0.0, 255.0) as u8
+    }
+}
+
+impl Lerp for Color {
+    fn lerp(&self, target: &Self, t: f32) -> Self {
+        Color::RGBA(
+            self.r.lerp(&target.r, t),
+            self.g.lerp(&target.g, t),
+            self.b.lerp(&target.b, t),
+            self.a.lerp(&target.a, t),
+        )
+    }
+}
+
+/// Tweens each element (e.g.: a `(width, height)` size)
+impl<A: Lerp, B: Lerp> Lerp for (A, B) {
+    fn lerp(&self, target: &Self, t: f32) -> Self {
+        (self.0.lerp(&target.0, t), self.1.lerp(&target.1, t))
+    }
+}
+
+/// Tweens both position & size
+impl Lerp for Rect {
+    fn lerp(&self, target: &Self, t: f32) -> Self {
+        Rect::new(
+            self.x().lerp(&target.x(), t),
+            self.y().lerp(&target.y(), t),
+            self.width().lerp(&target.width(), t),
+            self.height().lerp(&target.height(), t),
+        )
+    }
+}
+
+// ========================== Animated Struct ========================== //
+
+/// A value which tweens towards its target over time
+/// - The first target snaps immediately, so new widgets do not play a transition
+///   (widgets of regenerated views continue theirs via `resume`)
+/// - A duration of `0.0` (default) disables tweening
+#[derive(Clone)]
+pub struct Animated<V> {
+    start: V,
+    target: V,
+    /// Seconds since the current tween began
+    elapsed: f32,
+    /// Length of a tween in seconds
+    pub duration: f32,
+    pub easing: Easing,
+    has_target: bool,
+}
+
+impl<V: Lerp + Copy + PartialEq> Animated<V> {
+    pub fn new(initial: V) -> Self {
+        Animated {
+            start: initial,
+            target: initial,
+            elapsed: 0.0,
+            duration: 0.0,
+            easing: Easing::Linear,
+            has_target: false,
+        }
+    }
+
+    /// Tween over `duration` seconds using `easing`
+    pub fn with_transition(mut self, duration: f32, easing: Easing) -> Self {
+        self.duration = duration;
+        self.easing = easing;
+        self
+    }
+
+    /// Begin tweening from the current value towards `target`
+    pub fn set_target(&mut self, target: V) {
+        if !self.has_target {
+            self.has_target = true;
+            self.jump_to(target);
+        } else if target != self.target {
+            self.start = self.value();
+            self.target = target;
+            self.elapsed = 0.0;
+        }
+    }
+
+    /// Assign a value without tweening
+    pub fn jump_to(&mut self, value: V) {
+        self.start = value;
+        self.target = value;
+        self.elapsed = self.duration;
+    }
+
+    /// Advance the tween by `delta` seconds
+    pub fn advance(&mut self, delta: f32) {
+        self.elapsed = (self.elapsed + delta).min(self.duration);
+    }
+
+    /// The current (tweened) value
+    pub fn value(&self) -> V {
+        if self.is_finished() {
+            self.target
+        } else {
+            self.start.lerp(&self.target, self.easing.apply(self.elapsed / self.duration))
+        }
+    }
+
+    pub fn target(&self) -> V {
+        self.target
+    }
+
+    /// Whether a target has been assigned (see `set_target`)
+    pub fn has_target(&self) -> bool {
+        self.has_target
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.duration <= 0.0 || self.elapsed >= self.duration
+    }
+
+    /// Continue the tween of `previous` (e.g.: from the matching widget of the previous view),
+    /// keeping this value's duration & easing
+    pub fn resume(&mut self, previous: &Animated<V>) {
+        let (duration, easing) = (self.duration, self.easing);
+
+        *self = previous.clone();
+        self.duration = duration;
+        self.easing = easing;
+        self.elapsed = self.elapsed.min(duration);
+    }
+}
+
+// ========================== TreeTransitions Struct ========================== //
+
+/// Widget sliding from its previous position
+struct Move {
+    id: &'static str,
+    /// Offset from the widget's layout position when the move began
+    offset: (i32, i32),
+    /// Offset currently applied to the widget
+    applied: (i32, i32),
+    progress: Animated<f32>,
+}
+
+/// Animates the differences between consecutive view trees (matched by widget id)
+/// - New widgets fade in
+/// - Removed widgets fade out in their last position (they no longer receive events)
+/// - Moved widgets slide from their previous position
+pub struct TreeTransitions<T> {
+    duration: f32,
+    easing: Easing,
+    entering: Vec<(&'static str, Animated<f32>)>,
+    leaving: Vec<(Box<dyn Widget<T>>, Animated<f32>)>,
+    moving: Vec<Move>,
+
+    /// Offscreen texture which fading widgets are drawn to (created when first needed)
+    layer: Option<Texture>,
+    // Keeps the renderer alive until the layer is destroyed (see `images::destroy_texture`)
+    texture_creator: Option<TextureCreator<WindowContext>>,
+    /// Stands in for the window's canvas while it renders to the layer (see `render_faded`)
+    placeholder: Option<WindowCanvas>,
+}
+
+impl<T> TreeTransitions<T> {
+    /// - `duration`: Length of each transition in seconds
+    pub fn new(duration: f32, easing: Easing) -> Self {
+        TreeTransitions {
+            duration,
+            easing,
+            entering: Vec::new(),
+            leaving: Vec::new(),
+            moving: Vec::new(),
+            layer: None,
+            texture_creator: None,
+            placeholder: None,
+        }
+    }
+
+    fn progress(&self) -> Animated<f32> {
+        let mut progress = Animated::new(0.0).with_transition(self.duration, self.easing);
+        progress.set_target(0.0);
+        progress.set_target(1.0);
+        progress
+    }
+
+    /// Begin transitions from `previous` to `next`
+    /// - `next` must be initialized & aligned (widget ids & positions are final)
+    pub fn begin(&mut self, previous: &mut dyn View<T>, next: &mut dyn View<T>) {
+        let mut previous_rects = HashMap::new();
+        for widget in previous.child_widgets_mut() {
+            previous_rects.insert(widget.id(), widget.rect());
+        }
+
+        let mut next_ids = Vec::new();
+        for widget in next.child_widgets_mut() {
+            let id = widget.id();
+            next_ids.push(id);
+
+            match previous_rects.get(id) {
+                Some(previous_rect) => {
+                    let offset = (previous_rect.x() - widget.rect().x(), previous_rect.y() - widget.rect().y());
+                    if offset != (0, 0) {
+                        // Start from the previous position
+                        widget.translate(offset.0, offset.1);
+                        self.moving.retain(|existing| existing.id != id);
+                        self.moving.push(Move {
+                            id,
+                            offset,
+                            applied: offset,
+                            progress: self.progress(),
+                        });
+                    }
+                }
+                None => {
+                    self.entering.push((id, self.progress()));
+                }
+            }
+        }
+
+        // Moves & fades of widgets which no longer exist
+        self.moving.retain(|existing| next_ids.contains(&existing.id));
+        self.entering.retain(|(id, _)| next_ids.contains(id));
+
+        // Only widgets which were visible (e.g.: not on another tab) fade out
+        let removed = |widget: &dyn Widget<T>| {
+            previous_rects.contains_key(widget.id()) && !next_ids.contains(&widget.id())
+        };
+
+        let progress = self.progress();
+        for widget in take_widgets(previous, &removed) {
+            self.leaving.push((widget, progress.clone()));
+        }
+    }
+
+    /// Advance all transitions by `delta` seconds
+    pub fn advance(&mut self, delta: f32, view: &mut dyn View<T>) {
+        for (_, progress) in &mut self.entering {
+            progress.advance(delta);
+        }
+        for (_, progress) in &mut self.leaving {
+            progress.advance(delta);
+        }
+
+        if !self.moving.is_empty() {
+            let mut widgets: HashMap<&'static str, &mut Box<dyn Widget<T>>> = view.child_widgets_mut()
+                .into_iter()
+                .map(|widget| (widget.id(), widget))
+                .collect();
+
+            for movement in &mut self.moving {
+                movement.progress.advance(delta);
+
+                let remaining = 1.0 - movement.progress.value();
+                let offset = (
+                    (movement.offset.0 as f32 * remaining).round() as i32,
+                    (movement.offset.1 as f32 * remaining).round() as i32,
+                );
+
+                if let Some(widget) = widgets.get_mut(movement.id) {
+                    widget.translate(offset.0 - movement.applied.0, offset.1 - movement.applied.1);
+                }
+                movement.applied = offset;
+            }
+        }
+
+        self.entering.retain(|(_, progress)| !progress.is_finished());
+        self.leaving.retain(|(_, progress)| !progress.is_finished());
+        self.moving.retain(|movement| !movement.progress.is_finished());
+    }
+
+    /// Whether any transition is in progress
+    pub fn is_active(&self) -> bool {
+        !(self.entering.is_empty() && self.leaving.is_empty() && self.moving.is_empty())
+    }
+
+    /// Render a widget of the current view (faded in if it is entering)
+    pub fn render_widget(&mut self, window: &mut Window<T>, widget: &dyn Widget<T>, widget_state: WidgetState)
+    where T: GenerateView<T> {
+        let opacity = self.entering.iter()
+            .find(|(id, _)| *id == widget.id())
+            .map_or(1.0, |(_, progress)| progress.value());
+
+        self.render_faded(window, widget, widget_state, opacity);
+    }
+
+    /// Render widgets which are fading out
+    pub fn render_leaving(&mut self, window: &mut Window<T>)
+    where T: GenerateView<T> {
+        let leaving = std::mem::take(&mut self.leaving);
+
+        for (widget, progress) in &leaving {
+            self.render_faded(window, &**widget, WidgetState::Base, 1.0 - progress.value());
+        }
+
+        self.leaving = leaving;
+    }
+
+    /// Render `widget` with the given opacity (`0.0..=1.0`)
+    /// - Translucent widgets are drawn to an offscreen layer, which is then blended onto the window
+    fn render_faded(&mut self, window: &mut Window<T>, widget: &dyn Widget<T>, widget_state: WidgetState, opacity: f32)
+    where T: GenerateView<T> {
+        if opacity >= 1.0 {
+            widget.render(window, widget_state);
+            return;
+        }
+        if opacity <= 0.0 {
+            return;
+        }
+
+        let (width, height) = window.canvas.output_size().expect("Failed to obtain canvas size");
+        let texture_creator = self.texture_creator.get_or_insert_with(|| window.canvas.texture_creator());
+
+        let resized = self.layer.as_ref().is_none_or(|layer| {
+            let query = layer.query();
+            (query.width, query.height) != (width, height)
+        });
+        if resized {
+            if let Some(layer) = self.layer.take() {
+                images::destroy_texture(texture_creator, layer);
+            }
+            let mut layer = texture_creator.create_texture_target(PixelFormatEnum::RGBA8888, width, height)
+                .expect("Failed to create transition layer");
+            layer.set_blend_mode(BlendMode::Blend);
+            self.layer = Some(layer);
+        }
+        let layer = self.layer.as_mut().unwrap();
+
+        // Widgets render through the window, while the canvas drawing to the layer is borrowed from it.
+        // The canvas is therefore swapped out for a placeholder & swapped back in while the layer is the target.
+        let placeholder = match self.placeholder.take() {
+            Some(placeholder) => placeholder,
+            None => placeholder_canvas(&window.canvas),
+        };
+        let mut canvas = std::mem::replace(&mut window.canvas, placeholder);
+
+        canvas.with_texture_canvas(layer, |layer_canvas| {
+            std::mem::swap(layer_canvas, &mut window.canvas);
+            window.canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+            window.canvas.clear();
+            widget.render(window, widget_state);
+            std::mem::swap(layer_canvas, &mut window.canvas);
+        }).expect("Failed to render transition layer");
+
+        self.placeholder = Some(std::mem::replace(&mut window.canvas, canvas));
+
+        layer.set_alpha_mod((opacity * 255.0).round() as u8);
+        window.canvas.copy(layer, None, None).expect("Failed to copy transition layer");
+    }
+}
+
+impl<T> Drop for TreeTransitions<T> {
+    fn drop(&mut self) {
+        if let (Some(layer), Some(texture_creator)) = (self.layer.take(), &self.texture_creator) {
+            images::destroy_texture(texture_creator, layer);
+        }
+    }
+}
+
+/// A hidden 1x1 canvas (software rendered, so no further GPU context is created)
+fn placeholder_canvas(canvas: &WindowCanvas) -> WindowCanvas {
+    canvas.window().subsystem()
+        .window("", 1, 1)
+        .hidden()
+        .build()
+        .expect("Failed to create placeholder window")
+        .into_canvas()
+        .software()
+        .build()
+        .expect("Failed to create placeholder canvas")
+}
+
+/// Removes the widgets matching `predicate` from `view` (recursively)
+fn take_widgets<T>(view: &mut dyn View<T>, predicate: &dyn Fn(&dyn Widget<T>) -> bool) -> Vec<Box<dyn Widget<T>>> {
+    let mut taken = Vec::new();
+
+    for component in view.components_mut() {
+        let matches = match component {
+            ViewComponent::Widget(widget) => predicate(&**widget),
+            ViewComponent::View(subview) => {
+                taken.append(&mut take_widgets(&mut **subview, predicate));
+                false
+            }
+            _ => false,
+        };
+
+        if matches {
+            if let ViewComponent::Widget(widget) = std::mem::replace(component, ViewComponent::Empty) {
+                taken.push(widget);
+            }
+        }
+    }
+
+    taken
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EASINGS: [Easing; 4] = [Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut];
+
+    #[test]
+    fn easing_endpoints_are_fixed() {
+        for easing in EASINGS.iter() {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+            // Progress is clamped
+            assert_eq!(easing.apply(-1.0), 0.0);
+            assert_eq!(easing.apply(2.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn easing_curves() {
+        assert_eq!(Easing::Linear.apply(0.25), 0.25);
+        assert_eq!(Easing::EaseIn.apply(0.5), 0.125);
+        assert_eq!(Easing::EaseOut.apply(0.5), 0.875);
+        assert_eq!(Easing::EaseInOut.apply(0.25), 0.0625);
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+        assert_eq!(Easing::EaseInOut.apply(0.75), 0.9375);
+    }
+
+    #[test]
+    fn easing_is_monotonic() {
+        for easing in EASINGS.iter() {
+            let values: Vec<f32> = (0..=100).map(|step| easing.apply(step as f32 / 100.0)).collect();
+            assert!(values.windows(2).all(|pair| pair[0] <= pair[1]));
+        }
+    }
+
+    #[test]
+    fn lerp_values() {
+        assert_eq!(0.0f32.lerp(&10.0, 0.25), 2.5);
+        assert_eq!(10u32.lerp(&0, 0.5), 5);
+        assert_eq!(200u8.lerp(&255, 2.0), 255);
+        assert_eq!((10u32, 20u32).lerp(&(20, 40), 0.5), (15, 30));
+        assert_eq!(Color::RGBA(0, 0, 0, 255).lerp(&Color::RGBA(100, 200, 50, 255), 0.5), Color::RGBA(50, 100, 25, 255));
+        assert_eq!(Rect::new(0, 0, 10, 10).lerp(&Rect::new(10, -10, 30, 50), 0.5), Rect::new(5, -5, 20, 30));
+    }
+
+    #[test]
+    fn animated_first_target_snaps() {
+        let mut value = Animated::new(0.0).with_transition(1.0, Easing::Linear);
+        assert!(!value.has_target());
+
+        value.set_target(5.0);
+        assert!(value.has_target());
+        assert!(value.is_finished());
+        assert_eq!(value.value(), 5.0);
+    }
+
+    #[test]
+    fn animated_tweens_from_current_value() {
+        let mut value = Animated::new(0.0).with_transition(1.0, Easing::Linear);
+        value.set_target(0.0);
+        value.set_target(10.0);
+
+        value.advance(0.5);
+        assert_eq!(value.value(), 5.0);
+
+        // Retargeting starts from the displayed value
+        value.set_target(0.0);
+        assert_eq!(value.value(), 5.0);
+        value.advance(0.5);
+        assert_eq!(value.value(), 2.5);
+        value.advance(10.0);
+        assert!(value.is_finished());
+        assert_eq!(value.value(), 0.0);
+    }
+
+    #[test]
+    fn animated_without_duration_jumps() {
+        let mut value = Animated::new(0u32);
+        value.set_target(1);
+        value.set_target(10);
+        assert_eq!(value.value(), 10);
+    }
+
+    #[test]
+    fn resumed_tweens_keep_progress_and_new_timing() {
+        let mut previous = Animated::new(0.0).with_transition(1.0, Easing::Linear);
+        previous.set_target(0.0);
+        previous.set_target(10.0);
+        previous.advance(0.25);
+
+        let mut next = Animated::new(0.0).with_transition(0.5, Easing::Linear);
+        next.resume(&previous);
+        assert_eq!(next.target(), 10.0);
+        assert_eq!(next.value(), 5.0);
+        next.advance(0.25);
+        assert!(next.is_finished());
+    }
+
+    mod transitions {
+        use super::*;
+        use sdl2::ttf;
+        use crate::view_components::IntoViewComponent;
+        use crate::view_components::views::VStack;
+        use crate::view_components::widgets::Button;
+
+        fn view(ids: &[&'static str]) -> VStack<()> {
+            let buttons = ids.iter().map(|id| Button::new(id).with_dimensions(100, 40).as_component()).collect();
+            let mut view = VStack::new(buttons);
+            view.init(&ttf::init().unwrap());
+            view.align();
+            view
+        }
+
+        fn positions(view: &mut dyn View<()>) -> Vec<(&'static str, i32)> {
+            view.child_widgets_mut().into_iter().map(|widget| (widget.id(), widget.rect().y())).collect()
+        }
+
+        #[test]
+        fn added_widgets_enter_and_moved_widgets_slide() {
+            let mut previous = view(&["a", "b"]);
+            let mut next = view(&["new", "a", "b"]);
+            let settled = positions(&mut next);
+
+            let mut transitions = TreeTransitions::new(1.0, Easing::Linear);
+            transitions.begin(&mut previous, &mut next);
+            assert!(transitions.is_active());
+            assert_eq!(transitions.entering.len(), 1);
+            assert_eq!(transitions.moving.len(), 2);
+
+            // Moved widgets start from their previous positions
+            assert_eq!(positions(&mut next)[1..], positions(&mut previous)[..]);
+
+            transitions.advance(0.5, &mut next);
+            let halfway = positions(&mut next);
+            assert!(halfway[1].1 > positions(&mut previous)[0].1 && halfway[1].1 < settled[1].1);
+
+            transitions.advance(0.5, &mut next);
+            assert!(!transitions.is_active());
+            assert_eq!(positions(&mut next), settled);
+        }
+
+        #[test]
+        fn removed_widgets_leave_the_view() {
+            let mut previous = view(&["a", "b"]);
+            let mut next = view(&["b"]);
+
+            let mut transitions = TreeTransitions::new(1.0, Easing::Linear);
+            transitions.begin(&mut previous, &mut next);
+
+            let leaving: Vec<&str> = transitions.leaving.iter().map(|(widget, _)| widget.id()).collect();
+            assert_eq!(leaving, ["a"]);
+            let remaining: Vec<&str> = positions(&mut previous).iter().map(|(id, _)| *id).collect();
+            assert_eq!(remaining, ["b"]);
+
+            transitions.advance(1.0, &mut next);
+            assert!(!transitions.is_active());
+        }
+    }
+}
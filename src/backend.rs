@@ -20,6 +20,7 @@ pub mod system {
     pub mod state {
         use crate::view_components::views::View;
         use sdl2::mouse::Cursor;
+        use std::cell::Cell;
 
         // TODO: Flesh this out and utilize appropriately. Or move event handling to Widget
         /// Holds application state relating to window events
@@ -62,6 +63,108 @@ pub mod system {
 
             }
         }
+
+        /// Optional message-based architecture (alternative to `Fn(&mut T)` callbacks)
+        /// - Widgets emit `Message`s via builders such as `Button::with_message`
+        /// - All state transitions occur within `update`, making them easy to test, log & replay
+        pub trait Update {
+            /// User-defined message type (typically an enum)
+            type Message: Clone + 'static;
+
+            /// Apply a message to the state
+            fn update(&mut self, message: Self::Message);
+        }
+
+        /// Explicit change notification (alternative to `Clone + PartialEq` diffing)
+        /// - Used by `Window::start_tracked`, which regenerates the view only when `take_changed` returns `true`
+        /// - Typically implemented by combining `DirtyFlag`s and/or `Observable` fields
+        pub trait TrackChanges {
+            /// Returns whether the state changed since the last call, clearing the change
+            fn take_changed(&mut self) -> bool;
+        }
+
+        /// A manually set change marker
+        /// - Call `mark` from callbacks after mutating state
+        #[derive(Default)]
+        pub struct DirtyFlag {
+            dirty: Cell<bool>,
+        }
+
+        impl DirtyFlag {
+            pub fn new() -> Self {
+                DirtyFlag {
+                    dirty: Cell::new(false),
+                }
+            }
+
+            /// Flag the state as changed
+            pub fn mark(&self) {
+                self.dirty.set(true);
+            }
+
+            pub fn is_dirty(&self) -> bool {
+                self.dirty.get()
+            }
+
+            /// Returns whether the flag was set, clearing it
+            pub fn take(&self) -> bool {
+                self.dirty.replace(false)
+            }
+        }
+
+        /// A state field which records when it is modified
+        /// - Reads go through `Deref`, writes through `set` or `modify`
+        #[derive(Default)]
+        pub struct Observable<V> {
+            value: V,
+            changed: DirtyFlag,
+        }
+
+        impl<V> Observable<V> {
+            pub fn new(value: V) -> Self {
+                Observable {
+                    value,
+                    changed: DirtyFlag::new(),
+                }
+            }
+
+            pub fn get(&self) -> &V {
+                &self.value
+            }
+
+            /// Replace the value, marking the field as changed
+            pub fn set(&mut self, value: V) {
+                self.value = value;
+                self.changed.mark();
+            }
+
+            /// Mutate the value in place, marking the field as changed
+            pub fn modify<F: FnOnce(&mut V)>(&mut self, modify_fn: F) {
+                modify_fn(&mut self.value);
+                self.changed.mark();
+            }
+
+            /// Returns whether the field changed since the last call, clearing the change
+            pub fn take_changed(&self) -> bool {
+                self.changed.take()
+            }
+        }
+
+        impl<V> std::ops::Deref for Observable<V> {
+            type Target = V;
+
+            fn deref(&self) -> &V {
+                &self.value
+            }
+        }
+
+        /// Apply a sequence of messages in order (such as a recorded session)
+        pub fn replay<S, I>(state: &mut S, messages: I)
+        where S: Update, I: IntoIterator<Item = S::Message> {
+            for message in messages {
+                state.update(message);
+            }
+        }
     } // end mod state
 
     /// This module handles application windows and related events:
@@ -70,15 +173,25 @@ pub mod system {
     /// - Event Handling (within the window)
     /// - Application State (both backend and user-level)
     pub mod window {
-        use sdl2::pixels::Color;
         use sdl2::event::Event;
         use sdl2::keyboard::Keycode;
         use sdl2::mouse::MouseButton;
         use sdl2::rect::Point;
-        use crate::view_components::widgets::WidgetState;
-        use crate::font::{FontParams, Fonts};
-        use crate::images;
-        use super::state::{ApplicationState, GenerateView};
+        use sdl2::pixels::Color;
+        use crate::view_components::widgets::{Widget, WidgetState};
+        use crate::view_components::views::{View, WidgetClips};
+        use crate::view_components::ids::{self, IdScheme, DuplicateIds};
+        use crate::view_components::reconcile;
+        use crate::view_components::theme::{self, Theme, ThemeWatcher};
+        use crate::font::GlyphAtlas;
+        use crate::images::{self, ImageCache, ImageEffects};
+        use crate::draw::ShapeCache;
+        use crate::scale;
+        use crate::tasks;
+        use crate::timers::{self, TimerHandle};
+        use crate::animation::{Easing, TreeTransitions};
+        use std::time::{Duration, Instant};
+        use super::state::{ApplicationState, GenerateView, TrackChanges};
         
         // Expected lifetime ('a) -> the initializing function containing the .start() call
         // Generic type (T) -> The user-defined application state struct for use with callbacks
@@ -87,15 +200,33 @@ pub mod system {
             pub ttf_context: sdl2::ttf::Sdl2TtfContext,
             video_subsystem: sdl2::VideoSubsystem,           
             pub canvas: sdl2::render::WindowCanvas,
+            /// Cached glyphs used to draw text
+            pub glyph_atlas: GlyphAtlas,
+            /// Decoded & uploaded images shared by widgets (see `ImageCache::preload`)
+            pub image_cache: ImageCache,
+            /// Rasterized shapes (such as rounded rects) shared by widgets
+            pub shape_cache: ShapeCache,
             event_pump: sdl2::EventPump,
+            /// Drawable pixels per window coordinate (above 1 where the OS scales windows, such as macOS)
+            pixel_ratio: f32,
 
             //TODO: Is this the best way to handle state? Shouldn't it be shared across multiple windows, etc?
             pub window_state: ApplicationState<'a, T>,
+
+            /// How ids are generated for widgets created without one
+            id_scheme: IdScheme,
+            /// Duplicate ids found in the current view (reported once per change)
+            duplicate_ids: Option<DuplicateIds>,
+
+            /// Duration (seconds) & easing of view tree transitions, if enabled
+            view_transitions: Option<(f32, Easing)>,
+            /// Reloads the theme when its style file changes (see `set_theme_file`)
+            theme_watcher: Option<ThemeWatcher>,
         }
 
         // TODO: Create a builder similar to widget declaration
         //       include things like .scale, .resizable, .accelerated, .background_color, etc.
-        impl<'a, T: GenerateView<T> + Clone + PartialEq> Window<'a, T> {
+        impl<'a, T: GenerateView<T>> Window<'a, T> {
             pub fn init(window_title: &str, state: &'a mut T) -> Self {
                 let sdl_context = sdl2::init().map_err(|e| e.to_string()).unwrap();
                 let video_subsystem = sdl_context.video().map_err(|e| e.to_string()).unwrap();
@@ -104,6 +235,7 @@ pub mod system {
                 let mut default_window = video_subsystem
                                      .window(window_title, 800, 600)
                                      .position_centered()
+                                     .allow_highdpi()
                                      .build()
                                      .expect("Failed to create window");
 
@@ -121,27 +253,123 @@ pub mod system {
                                                 .event_pump()
                                                 .expect("Failed to obtain event pump");
 
+                let glyph_atlas = GlyphAtlas::new(&default_window_canvas);
+                let image_cache = ImageCache::new(&default_window_canvas);
+                let shape_cache = ShapeCache::new(&default_window_canvas);
+
+                // Detect the UI scale factor (see `set_scale_factor`)
+                let window_width = default_window_canvas.window().size().0.max(1);
+                let drawable_width = default_window_canvas.output_size().map(|(width, _)| width).unwrap_or(window_width);
+                let pixel_ratio = drawable_width as f32 / window_width as f32;
+
+                if pixel_ratio > 1.0 {
+                    scale::set_factor(pixel_ratio);
+                } else {
+                    let display_dpi = default_window_canvas.window().display_index()
+                        .and_then(|display| video_subsystem.display_dpi(display));
+                    if let Ok((_, horizontal_dpi, _)) = display_dpi {
+                        scale::set_factor(scale::factor_from_dpi(horizontal_dpi));
+                    }
+                }
+
                 Window {
                     sdl_context: sdl_context,
                     ttf_context: ttf_context,
                     video_subsystem: video_subsystem,
                     // window: default_window,
                     canvas: default_window_canvas,
+                    glyph_atlas,
+                    image_cache,
+                    shape_cache,
                     event_pump: default_window_event_pump,
+                    pixel_ratio,
                     window_state: ApplicationState::new(state),
+                    id_scheme: IdScheme::default(),
+                    duplicate_ids: None,
+                    view_transitions: None,
+                    theme_watcher: None,
                 }
             }
 
+            /// Choose how ids are generated for widgets created without one
+            /// - See `IdScheme` for options
+            pub fn set_id_scheme(&mut self, scheme: IdScheme) {
+                self.id_scheme = scheme;
+            }
+
+            /// Set the default appearance of widgets & views (see `Theme::dark` & `Theme::light`)
+            /// - Builder methods such as `with_base_color` override the theme
+            /// - Call before `start` (applies to views generated afterwards)
+            pub fn set_theme(&mut self, theme: Theme) {
+                theme::set_current(theme);
+            }
+
+            /// Load the theme from a TOML style file (see `Theme::from_file`)
+            /// - The file is reloaded automatically whenever it is modified
+            pub fn set_theme_file<P: AsRef<std::path::Path>>(&mut self, path: P) -> Result<(), String> {
+                self.set_theme(Theme::from_file(&path)?);
+                self.theme_watcher = Some(ThemeWatcher::new(path));
+                Ok(())
+            }
+
+            /// Animate changes to the view tree over `duration` seconds
+            /// - Added widgets fade in, removed widgets fade out & moved widgets slide into place
+            /// - Widgets are matched by id, so unnamed widgets may be treated as replaced when siblings change
+            pub fn set_view_transitions(&mut self, duration: f32, easing: Easing) {
+                self.view_transitions = Some((duration, easing));
+            }
+
+            /// Call `callback` once after `delay`
+            /// - Returns a handle which may be used to cancel the callback
+            /// - Use `timers::after` to schedule from callbacks
+            pub fn after<F: 'static + FnMut(&mut T)>(&mut self, delay: Duration, callback: F) -> TimerHandle
+            where T: 'static {
+                timers::after(delay, callback)
+            }
+
+            /// Call `callback` repeatedly, every `interval` (at least `timers::MIN_INTERVAL`)
+            /// - Returns a handle which may be used to cancel the callback
+            /// - Use `timers::every` to schedule from callbacks
+            pub fn every<F: 'static + FnMut(&mut T)>(&mut self, interval: Duration, callback: F) -> TimerHandle
+            where T: 'static {
+                timers::every(interval, callback)
+            }
+
             /// Set the window icon to the specified image resource
             pub fn set_icon(&mut self, resource_path: &str) {
-                // FIXME: This is temporary. See TODO below
+                self.set_icon_with_effects(resource_path, &ImageEffects::default());
+            }
+
+            /// Set the window icon, making pixels of `color_key` transparent (such as a bitmap's background)
+            pub fn set_icon_with_color_key(&mut self, resource_path: &str, color_key: Color) {
+                self.set_icon_with_effects(resource_path, &ImageEffects { color_key: Some(color_key) });
+            }
+
+            fn set_icon_with_effects(&mut self, resource_path: &str, effects: &ImageEffects) {
                 let path = std::path::Path::new(resource_path);
-                let surface = images::load_image(path).unwrap();
+                // Icons are converted to RGBA8888 so that transparency is kept
+                let surface = images::load_image(path)
+                    .and_then(|surface| effects.apply(surface))
+                    .expect("Failed to load icon");
 
                 let window = self.canvas.window_mut();
                 window.set_icon(surface);
             }
 
+            /// Scale sizes, spacing & font sizes of views generated afterwards (see `scale`)
+            /// - Detected from the display's DPI by default
+            /// - Call before `start`
+            pub fn set_scale_factor(&mut self, factor: f32) {
+                scale::set_factor(factor);
+                // Glyphs are rasterized at the scaled point size
+                self.glyph_atlas.clear();
+            }
+
+            /// Physical pixels per logical pixel
+            pub fn scale_factor(&self) -> f32 {
+                scale::factor()
+            }
+
             /// Used for scaling to device independent resolutions
             /// - Accepts tuple: `(width, height)`
             // TODO: See this: https://gamedev.stackexchange.com/questions/119414/resolution-scaling
@@ -149,18 +377,67 @@ pub mod system {
                 self.canvas.set_logical_size(dimensions.0, dimensions.1).expect("Failed to set logical size");
             }
 
+            /// Initializes a newly generated view & validates its widget ids
+            fn prepare_view(&mut self, view: &mut Box<dyn View<T>>) {
+                view.init(&self.ttf_context);
+                self.assign_ids(view);
+
+                // FIXME: This is only needed because only the parent
+                //        view should call this explicitly
+                view.align();
+            }
+
+            /// Generates missing widget ids & reports duplicates
+            fn assign_ids(&mut self, view: &mut Box<dyn View<T>>) {
+                let duplicates = ids::validate_ids(&mut **view, self.id_scheme).err();
+                // Only report when the problem changes (views are regenerated often)
+                if duplicates != self.duplicate_ids {
+                    if let Some(error) = &duplicates {
+                        eprintln!("Warning: {}", error);
+                    }
+                    self.duplicate_ids = duplicates;
+                }
+            }
+
             /// Resizes the application window to the specified pixel values
             /// - Usage: `resize_window((width, height));`
             fn resize_window(&mut self, dimensions: (u32, u32)) {
-                self.canvas.window_mut().set_size(dimensions.0, dimensions.1).expect("Failed to resize");
+                // Views are sized in drawable pixels
+                let width = (dimensions.0 as f32 / self.pixel_ratio).round() as u32;
+                let height = (dimensions.1 as f32 / self.pixel_ratio).round() as u32;
+
+                self.canvas.window_mut().set_size(width, height).expect("Failed to resize");
+            }
+
+            /// Begin UI window main loop
+            /// - The view is regenerated whenever the state differs from its previous clone
+            /// - See `start_tracked` for large states which are expensive to clone or compare
+            pub fn start(self) where T: Clone + PartialEq + 'static {
+                let mut last_user_state = self.window_state.user_state.clone();
+
+                self.run(move |user_state| {
+                    if *user_state != last_user_state {
+                        last_user_state = user_state.clone();
+                        true
+                    } else {
+                        false
+                    }
+                });
+            }
+
+            /// Begin UI window main loop
+            /// - The view is regenerated only when the state reports a change (see `TrackChanges`)
+            /// - Does not require `T: Clone + PartialEq`
+            pub fn start_tracked(self) where T: TrackChanges + 'static {
+                self.run(|user_state| user_state.take_changed());
             }
 
             // TODO: Allow multiple windows to run at once on multiple threads
             // TODO: How to handle window size changes from the user?
             // FIXME: Implementing HashMap will remove *all* for-loops
             // pub fn start<V: View<T> + Sized>(mut self, mut view: V) {
-            /// Begin UI window main loop
-            pub fn start(mut self) {
+            /// Main loop. `state_changed` is polled each frame to decide whether to regenerate the view.
+            fn run<F: FnMut(&mut T) -> bool>(mut self, mut state_changed: F) where T: 'static {
                 /* TODO: Use this pattern to implement cursors for widgets
                     Note that cursor is reset when dropped (when exits scope)
                 
@@ -173,16 +450,13 @@ pub mod system {
 
                 /* Initialize here */
 
-                // Used to detect state changes, triggering view generation
-                let mut last_user_state = self.window_state.user_state.clone();
                 // Stores the root view
                 let mut view = self.window_state.user_state.generate_view();
 
                 // Initialize the window/widget layout
-                view.init(&self.ttf_context);
-                // FIXME: This is only needed because only the parent
-                //        view should call this explicitly
-                view.align();
+                self.prepare_view(&mut view);
+                // Areas that nested widgets are clipped to (such as `SplitView` panes)
+                let mut clips = view.widget_clips();
 
                 // Used to determine whether to resize window
                 let mut last_window_size = view.view_size();
@@ -191,15 +465,54 @@ pub mod system {
                 // Set initial window size (will override the default of 800x600)
                 self.resize_window(last_window_size);
 
+                // Animates view changes (see `set_view_transitions`)
+                let mut transitions = self.view_transitions.map(|(duration, easing)| TreeTransitions::new(duration, easing));
+                // Used to calculate frame delta time for animations
+                let mut last_frame = Instant::now();
+
                 'window_loop: loop {
+                    let now = Instant::now();
+                    let delta = now.duration_since(last_frame).as_secs_f32();
+                    last_frame = now;
+
+                    // Apply results of background tasks (see `tasks::Task`)
+                    tasks::deliver(self.window_state.user_state);
+                    // Run scheduled callbacks (see `after` & `every`)
+                    timers::run_due(self.window_state.user_state);
+
+                    // Reload the style file if it changed
+                    let mut theme_changed = false;
+                    if let Some(reloaded) = self.theme_watcher.as_mut().and_then(|watcher| watcher.poll()) {
+                        match reloaded {
+                            Ok(theme) => {
+                                theme::set_current(theme);
+                                theme_changed = true;
+                            }
+                            // Keep the previous theme until the file is fixed
+                            Err(error) => eprintln!("Warning: {}", error),
+                        }
+                    }
+
                     // Only update the view tree if state was modified
-                    if *self.window_state.user_state != last_user_state {
-                        last_user_state = self.window_state.user_state.clone();
+                    // Note: `|` ensures state changes are always tracked
+                    if state_changed(self.window_state.user_state) | theme_changed {
+                        // Generate the new view, reusing measurements of unchanged text
+                        let mut next_view = self.window_state.user_state.generate_view();
+                        reconcile::reconcile(&mut *view, &mut *next_view);
+
+                        if reconcile::patch(&mut *view, &mut *next_view, &self.ttf_context) {
+                            // Same layout -> the current view now holds the new widgets
+                            self.assign_ids(&mut view);
+                        } else {
+                            self.prepare_view(&mut next_view);
+
+                            if let Some(transitions) = &mut transitions {
+                                transitions.begin(&mut *view, &mut *next_view);
+                            }
 
-                        // Generate the new view
-                        view = self.window_state.user_state.generate_view();
-                        view.init(&self.ttf_context);
-                        view.align();
+                            view = next_view;
+                        }
+                        clips = view.widget_clips();
 
                         // View's size has changed -> adjust
                         if view.view_size() != last_window_size {
@@ -208,10 +521,28 @@ pub mod system {
                         }
                     }
 
-                    self.canvas.set_draw_color(Color::RGB(50, 50, 100));
+                    self.canvas.set_draw_color(theme::with_current(|theme| theme.palette.background));
                     self.canvas.clear();
 
-                    'event_pump: for event in self.event_pump.poll_iter() {
+                    for event in self.event_pump.poll_iter() {
+                        let event = to_drawable_coordinates(event, self.pixel_ratio);
+
+                        // Mouse events are delivered before hover & click states change,
+                        // so the clicked widget also receives the release
+                        let mouse_location = match event {
+                            Event::MouseMotion { x, y, .. } |
+                            Event::MouseButtonDown { x, y, .. } |
+                            Event::MouseButtonUp { x, y, .. } => Some(Point::new(x, y)),
+                            _ => None,
+                        };
+                        if let Some(location) = mouse_location {
+                            for widget in view.child_widgets_mut() {
+                                if is_hit(&**widget, &clips, location) || self.window_state.clicking == Some(widget.id()) {
+                                    widget.on_mouse_event(self.window_state.user_state, &event);
+                                }
+                            }
+                        }
+
                         match event {
                             Event::Quit {..} |
                             Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
@@ -230,7 +561,7 @@ pub mod system {
                                 self.window_state.hovering = None;
 
                                 for widget in view.child_widgets_mut() {
-                                    if widget.rect().contains_point(event_location) {
+                                    if is_hit(&**widget, &clips, event_location) {
                                         if let Some(clicking_id) = self.window_state.clicking {
                                             if clicking_id == widget.id() {
                                                 break; // Actually clicking a widget
@@ -255,7 +586,7 @@ pub mod system {
                                 self.window_state.clicking = None;
 
                                 for widget in view.child_widgets_mut() {
-                                    if widget.rect().contains_point(event_location) {
+                                    if is_hit(&**widget, &clips, event_location) {
                                         if let Some(hover_id) = self.window_state.hovering {
                                             // Cannot click a widget without hovering over it
                                             if hover_id == widget.id() {
@@ -291,7 +622,7 @@ pub mod system {
                                 if let Some(active_id) = self.window_state.clicking { // If there is an active widget
                                     // TODO: Replace the for loop with hash table lookup (should be part of the view)
                                     for widget in view.child_widgets_mut() { // Look at each widget
-                                        if widget.rect().contains_point(event_location) { // If the mouse was released on any widget
+                                        if is_hit(&**widget, &clips, event_location) { // If the mouse was released on any widget
                                             if active_id == widget.id() { // Trigger the callback if that widget was active
                                                 widget.on_click(self.window_state.user_state);
                                             }
@@ -310,6 +641,24 @@ pub mod system {
                             }
                         }
 
+                        // Keyboard shortcuts are delivered to every widget
+                        match event {
+                            Event::KeyDown { .. } | Event::KeyUp { .. } => {
+                                for widget in view.child_widgets_mut() {
+                                    let widget_state = if self.window_state.focused == Some(widget.id()) {
+                                        WidgetState::Focused
+                                    } else if self.window_state.hovering == Some(widget.id()) {
+                                        WidgetState::Hovering
+                                    } else {
+                                        WidgetState::Base
+                                    };
+
+                                    widget.on_key_event(self.window_state.user_state, &event, &widget_state);
+                                }
+                            }
+                            _ => {}
+                        }
+
                         // TODO: Consider combining update & render
                         //  Call update first, then render
 
@@ -336,8 +685,18 @@ pub mod system {
                     // TODO: Create 'Render' trait and get all renderables, not just widgets
                     
                     
+                    if let Some(transitions) = &mut transitions {
+                        transitions.advance(delta, &mut *view);
+                    }
+
                     // Render each widget
                     for widget in view.child_widgets_mut() {
+                        let clip = clips.get(widget.id()).copied();
+                        // Clipped entirely (such as a collapsed pane)
+                        if clip == Some(None) {
+                            continue;
+                        }
+
                         let mut widget_state = WidgetState::Base;
 
                         if let Some(active_id) = self.window_state.clicking {
@@ -358,7 +717,18 @@ pub mod system {
                             }
                         }
 
-                        widget.render(&mut self, widget_state);
+                        widget.animate(delta, &widget_state);
+                        self.canvas.set_clip_rect(clip.flatten());
+                        match &mut transitions {
+                            // Entering widgets fade in
+                            Some(transitions) => transitions.render_widget(&mut self, &**widget, widget_state),
+                            None => widget.render(&mut self, widget_state),
+                        }
+                        self.canvas.set_clip_rect(None);
+                    }
+
+                    if let Some(transitions) = &mut transitions {
+                        transitions.render_leaving(&mut self);
                     }
 
                     for comp in view.child_comps() {
@@ -369,9 +739,158 @@ pub mod system {
 
                     // FIXME: Replace this with delta time for use in animations & frame rate limiting
                     // Hard-limit to 60fps to avoid excessive rendering (lowers GPU usage considerably)
-                    std::thread::sleep(std::time::Duration::new(0, 1_000_000_000u32 / 60));
+                    // Wake early if a timer is due before the next frame
+                    let mut frame_time = Duration::new(0, 1_000_000_000u32 / 60);
+                    if let Some(deadline) = timers::next_deadline() {
+                        frame_time = frame_time.min(deadline.saturating_duration_since(Instant::now()));
+                    }
+                    std::thread::sleep(frame_time);
                 } // end event loop
-            } // end start() method
+            } // end run() method
         } // end impl window
+
+        /// Whether `point` is on the visible part of `widget` (see `View::widget_clips`)
+        fn is_hit<T>(widget: &dyn Widget<T>, clips: &WidgetClips, point: Point) -> bool {
+            let clip = clips.get(widget.id()).copied();
+
+            widget.rect().contains_point(point) && match clip {
+                Some(Some(area)) => area.contains_point(point),
+                Some(None) => false,
+                None => true,
+            }
+        }
+
+        /// Convert mouse event coordinates from window coordinates to drawable pixels
+        fn to_drawable_coordinates(mut event: Event, pixel_ratio: f32) -> Event {
+            if pixel_ratio == 1.0 {
+                return event;
+            }
+
+            let scaled = |value: &mut i32| *value = (*value as f32 * pixel_ratio).round() as i32;
+            match &mut event {
+                Event::MouseMotion { x, y, xrel, yrel, .. } => {
+                    scaled(x);
+                    scaled(y);
+                    scaled(xrel);
+                    scaled(yrel);
+                }
+                Event::MouseButtonDown { x, y, .. } |
+                Event::MouseButtonUp { x, y, .. } => {
+                    scaled(x);
+                    scaled(y);
+                }
+                _ => {}
+            }
+
+            event
+        }
     } // end mod window
 } // end mod system
+
+#[cfg(test)]
+mod tests {
+    use super::system::state::{Update, replay, TrackChanges, DirtyFlag, Observable};
+    use crate::view_components::widgets::{Widget, Button, CheckBox};
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Message {
+        Increment,
+        Checked(bool),
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        count: i32,
+        checked: bool,
+        log: Vec<Message>,
+    }
+
+    impl Update for Counter {
+        type Message = Message;
+
+        fn update(&mut self, message: Message) {
+            match message {
+                Message::Increment => self.count += 1,
+                Message::Checked(checked) => self.checked = checked,
+            }
+            self.log.push(message);
+        }
+    }
+
+    /// Tracked state combining a manual flag & an observable field
+    #[derive(Default)]
+    struct Tracked {
+        dirty: DirtyFlag,
+        name: Observable<String>,
+    }
+
+    impl TrackChanges for Tracked {
+        fn take_changed(&mut self) -> bool {
+            // Both must be cleared
+            self.dirty.take() | self.name.take_changed()
+        }
+    }
+
+    #[test]
+    fn dirty_flag_is_cleared_when_taken() {
+        let flag = DirtyFlag::new();
+        assert!(!flag.is_dirty());
+
+        flag.mark();
+        flag.mark();
+        assert!(flag.is_dirty());
+        assert!(flag.take());
+        assert!(!flag.take());
+    }
+
+    #[test]
+    fn observable_records_writes() {
+        let mut name = Observable::new(String::from("a"));
+        assert!(!name.take_changed());
+
+        name.set(String::from("b"));
+        assert_eq!(name.get(), "b");
+        assert!(name.take_changed());
+
+        name.modify(|name| name.push('c'));
+        // Reads go through `Deref` and do not mark changes
+        assert_eq!(name.len(), 2);
+        assert!(name.take_changed());
+        assert!(!name.take_changed());
+    }
+
+    #[test]
+    fn tracked_state_reports_each_change_once() {
+        let mut state = Tracked::default();
+        assert!(!state.take_changed());
+
+        state.dirty.mark();
+        state.name.set(String::from("name"));
+        assert!(state.take_changed());
+        assert!(!state.take_changed());
+    }
+
+    #[test]
+    fn replay_applies_messages_in_order() {
+        let mut state = Counter::default();
+        replay(&mut state, vec![Message::Increment, Message::Checked(true), Message::Increment]);
+
+        assert_eq!(state.count, 2);
+        assert!(state.checked);
+        assert_eq!(state.log, vec![Message::Increment, Message::Checked(true), Message::Increment]);
+    }
+
+    #[test]
+    fn widgets_send_messages() {
+        let mut state = Counter::default();
+
+        let mut button = Button::new("button").with_message(Message::Increment);
+        button.on_click(&mut state);
+        button.on_click(&mut state);
+
+        let mut checkbox = CheckBox::new("checkbox", false).with_on_check_message(Message::Checked);
+        checkbox.on_click(&mut state);
+
+        assert_eq!(state.log, vec![Message::Increment, Message::Increment, Message::Checked(true)]);
+    }
+}
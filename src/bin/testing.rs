@@ -42,7 +42,6 @@ impl State {
 
 impl GenerateView<State> for State {
     fn generate_view(&self) -> Box<dyn View<State>> {
-        // TODO: Need a way to handle loops/if statements for view generation (within macros)
         let mut view = VStack!(
             // TODO: Test different images & formats
             Image::new("TestImage", "./res/logo/temp_logo_low_quality.bmp", (100, 100))
@@ -108,6 +107,10 @@ impl GenerateView<State> for State {
                     state.is_locked = is_checked;
                 }),
 
+            // Items can be conditional
+            if self.is_locked => Text::new("LockedText", "The counter is locked")
+                .with_color(colors::LIGHT_GRAY),
+
             Button::new("ResetCounter")
                 .with_on_click(|state: &mut State| {
                     if !state.is_locked {
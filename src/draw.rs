@@ -0,0 +1,520 @@
+/*
+
+Primitive shape rendering (without SDL2_gfx)
+
+Shapes are described by signed distance functions (negative inside, positive outside).
+Each pixel's coverage is derived from its distance to the shape's edge, giving
+anti-aliased edges.
+
+Shapes are rasterized once into a texture (see `ShapeCache`), which is drawn
+wherever & as often as the same shape is used.
+
+*/
+
+extern crate sdl2;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{BlendMode, Texture, TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+
+use std::collections::HashMap;
+
+use crate::images;
+
+/// Number of cached shapes after which unused shapes are released
+const MAX_SHAPES: usize = 256;
+
+// ========================== Shape Enum ========================== //
+
+/// Geometry of a shape relative to the top-left corner of its bounds,
+/// so that the same shape drawn at another position shares its texture
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+enum Shape {
+    RoundedRect { radius: u32 },
+    RoundedBorder { radius: u32, thickness: u32 },
+    Ellipse { radius_x: u32, radius_y: u32 },
+    Ring { radius: u32, thickness: u32 },
+    Polyline { points: Vec<(i32, i32)>, thickness: u32 },
+    Polygon { points: Vec<(i32, i32)> },
+}
+
+impl Shape {
+    /// Signed distance from the shape's edge at (`x`, `y`), relative to bounds of `size`
+    fn distance(&self, size: (u32, u32), x: f32, y: f32) -> f32 {
+        let bounds = Rect::new(0, 0, size.0, size.1);
+
+        match self {
+            Shape::RoundedRect { radius } => rounded_rect_distance(bounds, clamp_radius(bounds, *radius), x, y),
+            Shape::RoundedBorder { radius, thickness } => {
+                let distance = rounded_rect_distance(bounds, clamp_radius(bounds, *radius), x, y);
+                // Inside the outer edge & outside the inner edge
+                distance.max(-(distance + *thickness as f32))
+            }
+            Shape::Ellipse { radius_x, radius_y } => {
+                let center = Point::new(*radius_x as i32 + 1, *radius_y as i32 + 1);
+                ellipse_distance(center, *radius_x as f32, *radius_y as f32, x, y)
+            }
+            Shape::Ring { radius, thickness } => {
+                let center = Point::new(*radius as i32 + 1, *radius as i32 + 1);
+                let distance = ellipse_distance(center, *radius as f32, *radius as f32, x, y);
+                distance.max(-(distance + *thickness as f32))
+            }
+            Shape::Polyline { points, thickness } => {
+                let distance = points.windows(2)
+                    .map(|segment| segment_distance(point(segment[0]), point(segment[1]), x, y))
+                    .fold(f32::MAX, f32::min);
+                distance - *thickness as f32 / 2.0
+            }
+            Shape::Polygon { points } => {
+                let points: Vec<Point> = points.iter().map(|&position| point(position)).collect();
+                polygon_distance(&points, x, y)
+            }
+        }
+    }
+}
+
+fn point((x, y): (i32, i32)) -> Point {
+    Point::new(x, y)
+}
+
+/// `points` relative to `origin`
+fn local_points(points: &[Point], origin: Point) -> Vec<(i32, i32)> {
+    points.iter().map(|point| (point.x() - origin.x(), point.y() - origin.y())).collect()
+}
+
+// ========================== ShapeCache Struct ========================== //
+
+/// Cache key: a shape's geometry, size & color
+type ShapeKey = (Shape, (u32, u32), Color);
+
+struct CachedShape {
+    texture: Texture,
+    /// Whether the shape was drawn since the cache was last trimmed
+    used: bool,
+}
+
+/// Draws anti-aliased shapes from textures rasterized once per (shape, size, color)
+/// - Shapes which are no longer drawn are released once `MAX_SHAPES` are cached
+pub struct ShapeCache {
+    // Keeps the renderer alive until the textures are destroyed (see `images::destroy_texture`)
+    texture_creator: TextureCreator<WindowContext>,
+    shapes: HashMap<ShapeKey, CachedShape>,
+}
+
+impl ShapeCache {
+    pub fn new(canvas: &WindowCanvas) -> Self {
+        ShapeCache {
+            texture_creator: canvas.texture_creator(),
+            shapes: HashMap::new(),
+        }
+    }
+
+    /// Fill a rectangle with rounded corners
+    /// - `radius` is clamped to half of the smaller dimension
+    pub fn fill_rounded_rect(&mut self, canvas: &mut WindowCanvas, rect: Rect, radius: u32, color: Color) {
+        let radius = clamp_radius(rect, radius) as u32;
+        self.draw(canvas, Shape::RoundedRect { radius }, rect, color);
+    }
+
+    /// Outline a rectangle with rounded corners. The border is drawn inside `rect`.
+    pub fn draw_rounded_border(&mut self, canvas: &mut WindowCanvas, rect: Rect, radius: u32, thickness: u32, color: Color) {
+        let radius = clamp_radius(rect, radius) as u32;
+        self.draw(canvas, Shape::RoundedBorder { radius, thickness }, rect, color);
+    }
+
+    /// Fill a circle
+    pub fn fill_circle(&mut self, canvas: &mut WindowCanvas, center: Point, radius: u32, color: Color) {
+        self.fill_ellipse(canvas, center, radius, radius, color);
+    }
+
+    /// Outline a circle. The border is drawn inside `radius`.
+    pub fn draw_circle(&mut self, canvas: &mut WindowCanvas, center: Point, radius: u32, thickness: u32, color: Color) {
+        self.draw(canvas, Shape::Ring { radius, thickness }, ellipse_bounds(center, radius, radius), color);
+    }
+
+    /// Fill an ellipse with horizontal radius `radius_x` & vertical radius `radius_y`
+    pub fn fill_ellipse(&mut self, canvas: &mut WindowCanvas, center: Point, radius_x: u32, radius_y: u32, color: Color) {
+        self.draw(canvas, Shape::Ellipse { radius_x, radius_y }, ellipse_bounds(center, radius_x, radius_y), color);
+    }
+
+    /// Draw a line of any thickness with rounded ends
+    pub fn draw_line(&mut self, canvas: &mut WindowCanvas, from: Point, to: Point, thickness: u32, color: Color) {
+        self.draw_polyline(canvas, &[from, to], thickness, color);
+    }
+
+    /// Draw connected line segments (such as a checkmark) with rounded joins & ends
+    pub fn draw_polyline(&mut self, canvas: &mut WindowCanvas, points: &[Point], thickness: u32, color: Color) {
+        if points.len() < 2 {
+            return;
+        }
+
+        let margin = (thickness as f32 / 2.0).ceil() as i32 + 1;
+        if let Some(bounds) = points_bounds(points, margin) {
+            let points = local_points(points, bounds.top_left());
+            self.draw(canvas, Shape::Polyline { points, thickness }, bounds, color);
+        }
+    }
+
+    /// Fill a polygon (even-odd rule)
+    pub fn fill_polygon(&mut self, canvas: &mut WindowCanvas, points: &[Point], color: Color) {
+        if points.len() < 3 {
+            return;
+        }
+
+        if let Some(bounds) = points_bounds(points, 1) {
+            let points = local_points(points, bounds.top_left());
+            self.draw(canvas, Shape::Polygon { points }, bounds, color);
+        }
+    }
+
+    /// Release all shapes (they are rasterized again when next drawn)
+    pub fn clear(&mut self) {
+        for (_, shape) in self.shapes.drain() {
+            images::destroy_texture(&self.texture_creator, shape.texture);
+        }
+    }
+
+    /// Draw `shape` within `bounds`, rasterizing it if it is not cached
+    fn draw(&mut self, canvas: &mut WindowCanvas, shape: Shape, bounds: Rect, color: Color) {
+        let key = (shape, bounds.size(), color);
+
+        if !self.shapes.contains_key(&key) {
+            if self.shapes.len() >= MAX_SHAPES {
+                self.trim();
+            }
+
+            match self.rasterize(&key) {
+                Ok(texture) => { self.shapes.insert(key.clone(), CachedShape { texture, used: false }); }
+                Err(_) => {
+                    // Such as shapes larger than the renderer's maximum texture size
+                    let (shape, size, _) = key;
+                    let origin = bounds.top_left();
+                    fill_shape(canvas, bounds, color, |x, y| {
+                        shape.distance(size, x - origin.x() as f32, y - origin.y() as f32)
+                    });
+                    return;
+                }
+            }
+        }
+
+        let cached = self.shapes.get_mut(&key).unwrap();
+        cached.used = true;
+        canvas.copy(&cached.texture, None, bounds).expect("Failed to copy shape texture");
+    }
+
+    fn rasterize(&self, (shape, size, color): &ShapeKey) -> Result<Texture, String> {
+        let pixels = coverage_pixels(*size, *color, |x, y| shape.distance(*size, x, y));
+
+        let mut texture = self.texture_creator
+            .create_texture_static(PixelFormatEnum::RGBA32, size.0, size.1)
+            .map_err(|error| error.to_string())?;
+        if let Err(error) = texture.update(None, &pixels, size.0 as usize * 4) {
+            images::destroy_texture(&self.texture_creator, texture);
+            return Err(error.to_string());
+        }
+        texture.set_blend_mode(BlendMode::Blend);
+
+        Ok(texture)
+    }
+
+    /// Release the shapes which were not drawn since the last trim
+    fn trim(&mut self) {
+        let unused: Vec<ShapeKey> = self.shapes.iter()
+            .filter(|(_, shape)| !shape.used)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in unused {
+            if let Some(shape) = self.shapes.remove(&key) {
+                images::destroy_texture(&self.texture_creator, shape.texture);
+            }
+        }
+        for shape in self.shapes.values_mut() {
+            shape.used = false;
+        }
+    }
+}
+
+impl Drop for ShapeCache {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+// ========================== Rasterization ========================== //
+
+/// Portion of a pixel covered by a shape, from the distance between the pixel's center & the shape's edge
+fn coverage(distance: f32) -> f32 {
+    (0.5 - distance).clamp(0.0, 1.0)
+}
+
+/// RGBA pixels (row by row) of `color` with each pixel's alpha scaled by its coverage
+/// - `distance(x, y)` is evaluated at pixel centers
+fn coverage_pixels<F: Fn(f32, f32) -> f32>((width, height): (u32, u32), color: Color, distance: F) -> Vec<u8> {
+    let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+
+    for y in 0..height {
+        for x in 0..width {
+            let coverage = coverage(distance(x as f32 + 0.5, y as f32 + 0.5));
+            let alpha = (color.a as f32 * coverage).round() as u8;
+            pixels.extend_from_slice(&[color.r, color.g, color.b, alpha]);
+        }
+    }
+
+    pixels
+}
+
+/// Fill every pixel within `bounds` according to its distance from the shape's edge
+/// - Used for shapes which cannot be cached (fully covered runs are drawn as single lines)
+fn fill_shape<F: Fn(f32, f32) -> f32>(canvas: &mut WindowCanvas, bounds: Rect, color: Color, distance: F) {
+    let previous_blend_mode = canvas.blend_mode();
+    canvas.set_blend_mode(BlendMode::Blend);
+
+    for y in bounds.top()..bounds.bottom() {
+        // Start of the current fully covered run
+        let mut run_start = None;
+
+        for x in bounds.left()..bounds.right() {
+            let coverage = coverage(distance(x as f32 + 0.5, y as f32 + 0.5));
+
+            if coverage >= 1.0 {
+                if run_start.is_none() {
+                    run_start = Some(x);
+                }
+                continue;
+            }
+
+            if let Some(start) = run_start.take() {
+                draw_run(canvas, start, x, y, color);
+            }
+
+            if coverage > 0.0 {
+                let mut blended = color;
+                blended.a = (color.a as f32 * coverage).round() as u8;
+                canvas.set_draw_color(blended);
+                canvas.draw_point(Point::new(x, y)).unwrap();
+            }
+        }
+
+        if let Some(start) = run_start {
+            draw_run(canvas, start, bounds.right(), y, color);
+        }
+    }
+
+    canvas.set_blend_mode(previous_blend_mode);
+}
+
+/// Draw the fully covered pixels from `start` up to (excluding) `end`
+fn draw_run(canvas: &mut WindowCanvas, start: i32, end: i32, y: i32, color: Color) {
+    canvas.set_draw_color(color);
+    canvas.fill_rect(Rect::new(start, y, (end - start) as u32, 1)).unwrap();
+}
+
+// ========================== Distance Functions ========================== //
+
+fn clamp_radius(rect: Rect, radius: u32) -> f32 {
+    radius.min(rect.width() / 2).min(rect.height() / 2) as f32
+}
+
+fn rounded_rect_distance(rect: Rect, radius: f32, x: f32, y: f32) -> f32 {
+    let half_width = rect.width() as f32 / 2.0;
+    let half_height = rect.height() as f32 / 2.0;
+
+    // Position relative to the center, folded into the first quadrant
+    let px = (x - (rect.x() as f32 + half_width)).abs();
+    let py = (y - (rect.y() as f32 + half_height)).abs();
+
+    // Distance from the inner (unrounded) rectangle
+    let qx = px - (half_width - radius);
+    let qy = py - (half_height - radius);
+
+    let outside = (qx.max(0.0).powi(2) + qy.max(0.0).powi(2)).sqrt();
+    let inside = qx.max(qy).min(0.0);
+
+    outside + inside - radius
+}
+
+/// Approximate distance (exact for circles)
+fn ellipse_distance(center: Point, radius_x: f32, radius_y: f32, x: f32, y: f32) -> f32 {
+    if radius_x <= 0.0 || radius_y <= 0.0 {
+        return f32::MAX;
+    }
+
+    let dx = (x - center.x() as f32) / radius_x;
+    let dy = (y - center.y() as f32) / radius_y;
+
+    ((dx * dx + dy * dy).sqrt() - 1.0) * radius_x.min(radius_y)
+}
+
+fn ellipse_bounds(center: Point, radius_x: u32, radius_y: u32) -> Rect {
+    Rect::new(
+        center.x() - radius_x as i32 - 1,
+        center.y() - radius_y as i32 - 1,
+        radius_x * 2 + 2,
+        radius_y * 2 + 2,
+    )
+}
+
+/// Distance from the line segment `a`-`b`
+fn segment_distance(a: Point, b: Point, x: f32, y: f32) -> f32 {
+    let (ax, ay) = (a.x() as f32, a.y() as f32);
+    let (abx, aby) = (b.x() as f32 - ax, b.y() as f32 - ay);
+    let (apx, apy) = (x - ax, y - ay);
+
+    let length_squared = abx * abx + aby * aby;
+    let t = if length_squared > 0.0 {
+        ((apx * abx + apy * aby) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    ((apx - abx * t).powi(2) + (apy - aby * t).powi(2)).sqrt()
+}
+
+fn polygon_distance(points: &[Point], x: f32, y: f32) -> f32 {
+    let mut distance = f32::MAX;
+    let mut inside = false;
+
+    for index in 0..points.len() {
+        let a = points[index];
+        let b = points[(index + 1) % points.len()];
+        distance = distance.min(segment_distance(a, b, x, y));
+
+        // Even-odd crossing test
+        let (ay, by) = (a.y() as f32, b.y() as f32);
+        if (ay > y) != (by > y) {
+            let crossing_x = a.x() as f32 + (y - ay) / (by - ay) * (b.x() - a.x()) as f32;
+            if x < crossing_x {
+                inside = !inside;
+            }
+        }
+    }
+
+    if inside { -distance } else { distance }
+}
+
+/// Bounding rect of `points`, expanded by `margin` on every side
+fn points_bounds(points: &[Point], margin: i32) -> Option<Rect> {
+    let left = points.iter().map(|point| point.x()).min()? - margin;
+    let right = points.iter().map(|point| point.x()).max()? + margin;
+    let top = points.iter().map(|point| point.y()).min()? - margin;
+    let bottom = points.iter().map(|point| point.y()).max()? + margin;
+
+    Some(Rect::new(left, top, (right - left) as u32, (bottom - top) as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors;
+
+    /// Alpha of the pixel at (x, y)
+    fn alpha(pixels: &[u8], width: u32, x: u32, y: u32) -> u8 {
+        pixels[((y * width + x) * 4 + 3) as usize]
+    }
+
+    #[test]
+    fn coverage_is_clamped() {
+        assert_eq!(coverage(-3.0), 1.0);
+        assert_eq!(coverage(0.0), 0.5);
+        assert_eq!(coverage(3.0), 0.0);
+    }
+
+    #[test]
+    fn rounded_rect_pixels() {
+        let color = Color::RGBA(10, 20, 30, 200);
+        let shape = Shape::RoundedRect { radius: 8 };
+        let pixels = coverage_pixels((40, 20), color, |x, y| shape.distance((40, 20), x, y));
+        assert_eq!(pixels.len(), 40 * 20 * 4);
+
+        // Corners are cut off, the edges & center are covered
+        assert_eq!(alpha(&pixels, 40, 0, 0), 0);
+        assert_eq!(alpha(&pixels, 40, 39, 19), 0);
+        assert_eq!(alpha(&pixels, 40, 20, 0), 200);
+        assert_eq!(alpha(&pixels, 40, 20, 10), 200);
+        assert_eq!(&pixels[..3], &[10, 20, 30]);
+
+        // Partially covered pixels along the rounded corner
+        let corner: Vec<u8> = (0..8).map(|index| alpha(&pixels, 40, index, 7 - index)).collect();
+        assert!(corner.iter().any(|&alpha| alpha > 0 && alpha < 200));
+    }
+
+    #[test]
+    fn rounded_border_is_hollow() {
+        let shape = Shape::RoundedBorder { radius: 0, thickness: 2 };
+        let pixels = coverage_pixels((10, 10), colors::WHITE, |x, y| shape.distance((10, 10), x, y));
+
+        assert_eq!(alpha(&pixels, 10, 0, 5), 255);
+        assert_eq!(alpha(&pixels, 10, 1, 5), 255);
+        assert_eq!(alpha(&pixels, 10, 2, 5), 0);
+        assert_eq!(alpha(&pixels, 10, 5, 5), 0);
+    }
+
+    #[test]
+    fn circles_are_symmetric() {
+        let shape = Shape::Ellipse { radius_x: 6, radius_y: 6 };
+        let size = ellipse_bounds(Point::new(0, 0), 6, 6).size();
+        let pixels = coverage_pixels(size, colors::WHITE, |x, y| shape.distance(size, x, y));
+
+        for y in 0..size.1 {
+            for x in 0..size.0 {
+                assert_eq!(alpha(&pixels, size.0, x, y), alpha(&pixels, size.0, size.0 - 1 - x, y));
+                assert_eq!(alpha(&pixels, size.0, x, y), alpha(&pixels, size.0, y, x));
+            }
+        }
+        assert_eq!(alpha(&pixels, size.0, 0, 0), 0);
+        assert_eq!(alpha(&pixels, size.0, 7, 7), 255);
+
+        // Degenerate ellipses are empty
+        assert_eq!(ellipse_distance(Point::new(0, 0), 0.0, 4.0, 0.0, 0.0), f32::MAX);
+    }
+
+    #[test]
+    fn shapes_are_independent_of_position() {
+        let near = [Point::new(0, 0), Point::new(10, 5)];
+        let far = [Point::new(300, 200), Point::new(310, 205)];
+
+        let bounds = (points_bounds(&near, 2).unwrap(), points_bounds(&far, 2).unwrap());
+        assert_eq!(bounds.0.size(), bounds.1.size());
+        assert_eq!(local_points(&near, bounds.0.top_left()), local_points(&far, bounds.1.top_left()));
+        assert_eq!(local_points(&far, bounds.1.top_left()), [(2, 2), (12, 7)]);
+    }
+
+    #[test]
+    fn segment_distances() {
+        let (a, b) = (Point::new(0, 0), Point::new(10, 0));
+        assert_eq!(segment_distance(a, b, 5.0, 3.0), 3.0);
+        // Beyond the ends, distances are measured from the end points
+        assert_eq!(segment_distance(a, b, -3.0, 4.0), 5.0);
+        assert_eq!(segment_distance(a, b, 13.0, 0.0), 3.0);
+        // Zero length segments are points
+        assert_eq!(segment_distance(a, a, 3.0, 4.0), 5.0);
+
+        let line = Shape::Polyline { points: vec![(0, 5), (10, 5)], thickness: 4 };
+        assert_eq!(line.distance((10, 10), 5.0, 5.0), -2.0);
+        assert_eq!(line.distance((10, 10), 5.0, 9.0), 2.0);
+    }
+
+    #[test]
+    fn polygons_use_the_even_odd_rule() {
+        let square = [Point::new(0, 0), Point::new(10, 0), Point::new(10, 10), Point::new(0, 10)];
+        assert_eq!(polygon_distance(&square, 5.0, 5.0), -5.0);
+        assert_eq!(polygon_distance(&square, 15.0, 5.0), 5.0);
+
+        // The center of a pentagram is enclosed twice, so it is outside
+        let star: Vec<Point> = (0..5)
+            .map(|index| {
+                let angle = std::f32::consts::PI * 2.0 * (index * 2) as f32 / 5.0;
+                Point::new((angle.sin() * 100.0) as i32, (-angle.cos() * 100.0) as i32)
+            })
+            .collect();
+        assert!(polygon_distance(&star, 0.0, 0.0) > 0.0);
+        assert!(polygon_distance(&star, 0.0, -80.0) < 0.0);
+    }
+
+    #[test]
+    fn radii_fit_their_rect() {
+        assert_eq!(clamp_radius(Rect::new(0, 0, 100, 20), 50), 10.0);
+        assert_eq!(clamp_radius(Rect::new(0, 0, 100, 20), 4), 4.0);
+    }
+}
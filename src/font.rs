@@ -2,23 +2,46 @@
 
 Handle font loading, storage, and sizing
 
+Fonts never fail to load: missing font files fall back to the embedded default font
+and characters missing from a font are drawn using fallback fonts (see `FontChain`).
+
+Text is drawn from cached glyph textures (see `GlyphAtlas`), so redrawing
+(frequently changing) text does not require loading fonts or rendering surfaces.
+
 TODO: Major refactoring to include this in widgets.rs & backend.rs
 
 */
 
 use sdl2::ttf;
 use sdl2::ttf::Sdl2TtfContext;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::render::{BlendMode, Texture, TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+use sdl2::rect::Rect;
+use sdl2::rwops::RWops;
+use sdl2::surface::Surface;
+
+use crate::view_components::ids;
+use crate::images;
+use crate::scale;
 
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 // use std::rc::Rc;
-// use std::path::Path;
 
 // ========================== Font Parameters ========================== //
 
+/// Path referring to the embedded default font
+pub const EMBEDDED_FONT: &str = "<embedded>";
+
+/// DejaVu Sans (see `res/font/DejaVuSans-LICENSE.txt`)
+static EMBEDDED_FONT_DATA: &[u8] = include_bytes!("../res/font/DejaVuSans.ttf");
+
 // Eq -> iff self.fields == other.fields
 #[derive(PartialEq, Eq, Hash, Copy, Clone)]
 pub struct FontParams {
-    /// Path to the font
+    /// Path to the font (or `EMBEDDED_FONT`)
     pub path: &'static str,
     /// Point size of the font
     pub point_size: u16,
@@ -32,12 +55,856 @@ impl FontParams {
         }
     }
 
+    /// The embedded font, which is always available
     pub fn default_font() -> Self {
         FontParams {
-            path: "./res/font/OpenSans-Regular.ttf",
+            path: EMBEDDED_FONT,
             point_size: 20,
         }
     }
+
+    /// An installed font found by family name (see `find_system_font`)
+    pub fn system(family: &str, point_size: u16) -> Option<Self> {
+        find_system_font(family).map(|path| FontParams::new(path, point_size))
+    }
+}
+
+/// Interpret `name` as a font file path or, if no such file exists, an installed font's family name
+/// - Unresolved names are kept as paths (which fall back to the embedded font when loaded)
+pub fn resolve_font(name: &str) -> &'static str {
+    if name != EMBEDDED_FONT && !Path::new(name).is_file() {
+        if let Some(path) = find_system_font(name) {
+            return path;
+        }
+    }
+
+    ids::intern(name.to_owned())
+}
+
+// ========================== Font Loading ========================== //
+
+/// Families used as fallbacks (if installed) unless `set_fallback_fonts` is called
+const DEFAULT_FALLBACK_FAMILIES: &[&str] = &[
+    "DejaVu Sans", "Noto Sans", "Noto Sans CJK", "Noto Sans Symbols", "Noto Sans Symbols2", "Noto Emoji", "Symbola",
+];
+
+thread_local! {
+    /// Font paths searched (in order) for characters missing from a font
+    static FALLBACK_FONTS: RefCell<Option<Vec<&'static str>>> = const { RefCell::new(None) };
+    /// Paths which failed to load (reported once)
+    static FAILED_FONTS: RefCell<HashSet<&'static str>> = RefCell::new(HashSet::new());
+}
+
+/// Assign the fonts searched (in order) for characters missing from a font
+/// - The embedded font is always searched last
+pub fn set_fallback_fonts(paths: Vec<&'static str>) {
+    FALLBACK_FONTS.with(|fallbacks| *fallbacks.borrow_mut() = Some(paths));
+}
+
+/// Fonts searched for characters missing from a font
+/// - Defaults to common installed fonts (see `DEFAULT_FALLBACK_FAMILIES`)
+pub fn fallback_fonts() -> Vec<&'static str> {
+    FALLBACK_FONTS.with(|fallbacks| {
+        fallbacks.borrow_mut()
+            .get_or_insert_with(|| {
+                DEFAULT_FALLBACK_FAMILIES.iter()
+                    .filter_map(|family| find_system_font(family))
+                    .collect()
+            })
+            .clone()
+    })
+}
+
+/// Load a single font file (or the embedded font)
+/// - `point_size` is logical & is scaled by the UI scale factor (see `scale`)
+fn load_face<'ttf>(ttf_context: &'ttf Sdl2TtfContext, path: &'static str, point_size: u16) -> Result<ttf::Font<'ttf, 'static>, String> {
+    let point_size = scale::point_size(point_size);
+
+    if path == EMBEDDED_FONT {
+        ttf_context.load_font_from_rwops(RWops::from_bytes(EMBEDDED_FONT_DATA)?, point_size)
+    } else {
+        ttf_context.load_font(path, point_size)
+    }
+}
+
+/// A fallback font, loaded when first needed
+enum Fallback<'ttf> {
+    Unloaded(&'static str),
+    Loaded(ttf::Font<'ttf, 'static>),
+    Failed,
+}
+
+/// A font followed by fallback fonts
+/// - Each character is drawn using the first font containing its glyph
+/// - Fallback fonts are only loaded once a character requires them
+pub struct FontChain<'ttf> {
+    ttf_context: &'ttf Sdl2TtfContext,
+    point_size: u16,
+    style: ttf::FontStyle,
+    primary: ttf::Font<'ttf, 'static>,
+    fallbacks: RefCell<Vec<Fallback<'ttf>>>,
+}
+
+impl<'ttf> FontChain<'ttf> {
+    /// Load a font & its fallbacks
+    /// - Fonts which fail to load are reported once & replaced by the embedded font
+    pub fn load(ttf_context: &'ttf Sdl2TtfContext, params: &FontParams) -> Self {
+        let primary = load_face(ttf_context, params.path, params.point_size).unwrap_or_else(|error| {
+            let first_failure = FAILED_FONTS.with(|failed| failed.borrow_mut().insert(params.path));
+            if first_failure {
+                eprintln!("Warning: Failed to load font '{}' ({}). Using the embedded font.", params.path, error);
+            }
+            load_face(ttf_context, EMBEDDED_FONT, params.point_size).expect("Failed to load embedded font")
+        });
+
+        let mut fallbacks: Vec<Fallback> = fallback_fonts().into_iter()
+            .filter(|path| *path != params.path)
+            .map(Fallback::Unloaded)
+            .collect();
+        if params.path != EMBEDDED_FONT {
+            fallbacks.push(Fallback::Unloaded(EMBEDDED_FONT));
+        }
+
+        FontChain {
+            ttf_context,
+            point_size: params.point_size,
+            style: ttf::FontStyle::NORMAL,
+            primary,
+            fallbacks: RefCell::new(fallbacks),
+        }
+    }
+
+    /// Apply styles (bold, italic, etc.) to every font of the chain
+    pub fn set_style(&mut self, style: ttf::FontStyle) {
+        self.style = style;
+        self.primary.set_style(style);
+
+        for fallback in self.fallbacks.get_mut() {
+            if let Fallback::Loaded(font) = fallback {
+                font.set_style(style);
+            }
+        }
+    }
+
+    /// The chain's first font
+    pub fn primary(&self) -> &ttf::Font<'ttf, 'static> {
+        &self.primary
+    }
+
+    pub fn height(&self) -> i32 {
+        self.primary.height()
+    }
+
+    pub fn ascent(&self) -> i32 {
+        self.primary.ascent()
+    }
+
+    pub fn recommended_line_spacing(&self) -> i32 {
+        self.primary.recommended_line_spacing()
+    }
+
+    /// Dimensions (width, height) of rendered text
+    pub fn size_of(&self, text: &str) -> Result<(u32, u32), String> {
+        let mut width = 0;
+        let mut height = self.primary.height().max(0) as u32;
+
+        for (index, segment) in self.segments(text) {
+            let (segment_width, segment_height) = self.with_font(index, |font| font.size_of(segment))
+                .map_err(|error| error.to_string())?;
+            width += segment_width;
+            height = height.max(segment_height);
+        }
+
+        Ok((width, height))
+    }
+
+    /// Render text (blended) to a new surface
+    pub fn render(&self, text: &str, color: Color) -> Result<Surface<'static>, String> {
+        let segments = self.segments(text);
+
+        // Most text is drawn entirely by a single font
+        if segments.len() == 1 {
+            let (index, segment) = segments[0];
+            return self.with_font(index, |font| font.render(segment).blended(color))
+                .map_err(|error| error.to_string());
+        }
+
+        // Render each segment & align their baselines
+        let mut rendered = Vec::new();
+        for (index, segment) in segments {
+            let mut surface = self.with_font(index, |font| font.render(segment).blended(color))
+                .map_err(|error| error.to_string())?;
+            surface.set_blend_mode(BlendMode::None)?;
+            let offset = self.primary.ascent() - self.with_font(index, |font| font.ascent());
+            rendered.push((surface, offset));
+        }
+
+        let width = rendered.iter().map(|(surface, _)| surface.width()).sum::<u32>();
+        let top = rendered.iter().map(|(_, offset)| *offset).min().unwrap_or(0).min(0);
+        let height = rendered.iter()
+            .map(|(surface, offset)| (offset - top) as u32 + surface.height())
+            .max()
+            .unwrap_or(0);
+
+        let mut combined = Surface::new(width.max(1), height.max(1), PixelFormatEnum::ARGB8888)?;
+        let mut x = 0;
+        for (surface, offset) in &rendered {
+            surface.blit(None, &mut combined, Rect::new(x, offset - top, surface.width(), surface.height()))?;
+            x += surface.width() as i32;
+        }
+
+        Ok(combined)
+    }
+
+    /// Rasterize a single character in white (tinted when drawn)
+    /// - Returns the glyph, its advance & the vertical offset aligning its baseline with the primary font
+    pub fn render_glyph(&self, character: char) -> Result<(Surface<'static>, i32, i32), String> {
+        let index = self.font_for(character);
+
+        self.with_font(index, |font| {
+            let surface = font.render_char(character)
+                .blended(Color::RGBA(255, 255, 255, 255))
+                .map_err(|error| error.to_string())?;
+            let advance = font.find_glyph_metrics(character)
+                .map(|metrics| metrics.advance)
+                .unwrap_or(surface.width() as i32);
+
+            Ok((surface, advance, self.primary.ascent() - font.ascent()))
+        })
+    }
+
+    /// Horizontal adjustment between two adjacent characters
+    pub fn kerning(&self, first: char, second: char) -> i32 {
+        let width = |text: &str| self.size_of(text).map(|(width, _)| width as i32).unwrap_or(0);
+        let pair: String = [first, second].iter().collect();
+
+        width(&pair) - width(&first.to_string()) - width(&second.to_string())
+    }
+
+    /// Split text into runs drawn by the same font (0 = primary, otherwise fallback index + 1)
+    fn segments<'t>(&self, text: &'t str) -> Vec<(usize, &'t str)> {
+        let mut segments: Vec<(usize, &'t str)> = Vec::new();
+        let mut start = 0;
+        let mut current = 0;
+
+        for (position, character) in text.char_indices() {
+            let index = self.font_for(character);
+            if index != current && position > start {
+                segments.push((current, &text[start..position]));
+                start = position;
+            }
+            current = index;
+        }
+
+        if start < text.len() || segments.is_empty() {
+            segments.push((current, &text[start..]));
+        }
+
+        segments
+    }
+
+    /// Index of the first font containing a glyph for `character`
+    fn font_for(&self, character: char) -> usize {
+        if character.is_whitespace() || self.primary.find_glyph(character).is_some() {
+            return 0;
+        }
+
+        let mut fallbacks = self.fallbacks.borrow_mut();
+        for (index, fallback) in fallbacks.iter_mut().enumerate() {
+            if let Fallback::Unloaded(path) = *fallback {
+                *fallback = match load_face(self.ttf_context, path, self.point_size) {
+                    Ok(mut font) => {
+                        font.set_style(self.style);
+                        Fallback::Loaded(font)
+                    }
+                    Err(_) => Fallback::Failed,
+                };
+            }
+
+            if let Fallback::Loaded(font) = fallback {
+                if font.find_glyph(character).is_some() {
+                    return index + 1;
+                }
+            }
+        }
+
+        // No font contains the glyph (drawn as the primary font's placeholder)
+        0
+    }
+
+    fn with_font<R, F: FnOnce(&ttf::Font) -> R>(&self, index: usize, function: F) -> R {
+        if index == 0 {
+            return function(&self.primary);
+        }
+
+        match &self.fallbacks.borrow()[index - 1] {
+            Fallback::Loaded(font) => function(font),
+            _ => function(&self.primary),
+        }
+    }
+}
+
+// ========================== Glyph Atlas ========================== //
+
+/// Minimum dimensions of each atlas texture (page)
+const ATLAS_PAGE_SIZE: u32 = 512;
+/// Space between glyphs within a page
+const GLYPH_PADDING: u32 = 1;
+
+/// A rasterized glyph's location within the atlas
+#[derive(Clone, Copy)]
+struct Glyph {
+    page: usize,
+    /// Region of the page (`None` for glyphs without pixels, such as some whitespace)
+    source: Option<Rect>,
+    advance: i32,
+    /// Vertical offset aligning the glyph's baseline with the primary font
+    offset_y: i32,
+}
+
+/// Cached glyphs & kerning of a single font, size & style
+#[derive(Default)]
+struct GlyphSet {
+    glyphs: HashMap<char, Glyph>,
+    kerning: HashMap<(char, char), i32>,
+    line_spacing: Option<i32>,
+}
+
+/// A texture containing glyphs
+struct AtlasPage {
+    texture: Texture,
+    shelves: Shelves,
+}
+
+/// Space of a square page, filled row (shelf) by row
+#[derive(Clone, Copy)]
+struct Shelves {
+    size: u32,
+    /// Position of the next glyph
+    cursor_x: u32,
+    cursor_y: u32,
+    /// Height of the tallest glyph in the current row
+    row_height: u32,
+}
+
+impl Shelves {
+    fn new(size: u32) -> Self {
+        Shelves { size, cursor_x: 0, cursor_y: 0, row_height: 0 }
+    }
+
+    /// Reserve space for a glyph, if it fits
+    fn allocate(&mut self, width: u32, height: u32) -> Option<Rect> {
+        if self.cursor_x + width > self.size {
+            // Begin a new row
+            self.cursor_x = 0;
+            self.cursor_y += self.row_height + GLYPH_PADDING;
+            self.row_height = 0;
+        }
+
+        if self.cursor_x + width > self.size || self.cursor_y + height > self.size {
+            return None;
+        }
+
+        let region = Rect::new(self.cursor_x as i32, self.cursor_y as i32, width, height);
+        self.cursor_x += width + GLYPH_PADDING;
+        self.row_height = self.row_height.max(height);
+        Some(region)
+    }
+}
+
+/// Text drawn by `GlyphAtlas::draw_styled`
+pub struct StyledText<'t> {
+    pub text: &'t str,
+    pub font: FontParams,
+    /// Bold, italic, etc.
+    pub style: ttf::FontStyle,
+    pub color: Color,
+}
+
+/// Draws text from glyphs rasterized once per (font, size, style) into shared textures
+/// - Strings are laid out glyph by glyph (with kerning) & drawn as textured quads
+/// - Fonts are only loaded when a string contains new glyphs or character pairs
+pub struct GlyphAtlas {
+    // Keeps the renderer alive until the pages are destroyed (see `images::destroy_texture`)
+    texture_creator: TextureCreator<WindowContext>,
+    pages: Vec<AtlasPage>,
+    sets: HashMap<(FontParams, i32), GlyphSet>,
+}
+
+impl GlyphAtlas {
+    pub fn new(canvas: &WindowCanvas) -> Self {
+        GlyphAtlas {
+            texture_creator: canvas.texture_creator(),
+            pages: Vec::new(),
+            sets: HashMap::new(),
+        }
+    }
+
+    /// Draw `text` with its top-left corner at `position`
+    /// - Returns the drawn dimensions (width, height)
+    pub fn draw_text(&mut self, canvas: &mut WindowCanvas, ttf_context: &Sdl2TtfContext,
+                     font: &FontParams, text: &str, color: Color, position: (i32, i32)) -> (u32, u32) {
+        let styled = StyledText { text, font: *font, style: ttf::FontStyle::NORMAL, color };
+        self.draw_styled(canvas, ttf_context, &styled, position)
+    }
+
+    /// Draw `text` rasterized at the point size which fits `target` (determined by the target's height)
+    /// - The text is centered within `target`, since whole point sizes rarely fit it exactly
+    pub fn draw_text_within(&mut self, canvas: &mut WindowCanvas, ttf_context: &Sdl2TtfContext,
+                            font: &FontParams, text: &str, color: Color, target: Rect) {
+        let (_, height) = self.size_text(ttf_context, font, ttf::FontStyle::NORMAL, text);
+        let fitted = FontParams::new(font.path, fitted_point_size(font.point_size, height, target.height()));
+
+        let (width, height) = self.size_text(ttf_context, &fitted, ttf::FontStyle::NORMAL, text);
+        let position = (
+            target.x() + (target.width() as i32 - width as i32) / 2,
+            target.y() + (target.height() as i32 - height as i32) / 2,
+        );
+        self.draw_text(canvas, ttf_context, &fitted, text, color, position);
+    }
+
+    /// Draw `text` using a font style (bold, italic, etc.)
+    /// - Underlines & strikethroughs are not part of glyphs & should be drawn separately
+    /// - Returns the drawn dimensions (width, height)
+    pub fn draw_styled(&mut self, canvas: &mut WindowCanvas, ttf_context: &Sdl2TtfContext,
+                       styled: &StyledText, position: (i32, i32)) -> (u32, u32) {
+        let (glyphs, height) = self.layout(ttf_context, &styled.font, styled.style, styled.text);
+        let color = styled.color;
+
+        // Tint every page used by the string
+        for page in &mut self.pages {
+            page.texture.set_color_mod(color.r, color.g, color.b);
+            page.texture.set_alpha_mod(color.a);
+        }
+
+        let mut width = 0;
+        for (glyph, x) in &glyphs {
+            if let Some(source) = glyph.source {
+                let target = Rect::new(position.0 + x, position.1 + glyph.offset_y, source.width(), source.height());
+                canvas.copy(&self.pages[glyph.page].texture, source, target).unwrap();
+            }
+            width = width.max(x + glyph.advance);
+        }
+
+        (width.max(0) as u32, height)
+    }
+
+    /// Dimensions (width, height) of `text` as drawn by the atlas
+    pub fn size_text(&mut self, ttf_context: &Sdl2TtfContext, font: &FontParams, style: ttf::FontStyle, text: &str) -> (u32, u32) {
+        let (glyphs, height) = self.layout(ttf_context, font, style, text);
+        let width = glyphs.iter().map(|(glyph, x)| x + glyph.advance).max().unwrap_or(0);
+
+        (width.max(0) as u32, height)
+    }
+
+    /// Distance between the tops of consecutive lines
+    pub fn line_spacing(&mut self, ttf_context: &Sdl2TtfContext, font: &FontParams) -> i32 {
+        let set = self.sets.entry((*font, ttf::FontStyle::NORMAL.bits())).or_default();
+
+        *set.line_spacing.get_or_insert_with(|| FontChain::load(ttf_context, font).recommended_line_spacing())
+    }
+
+    /// Release all glyphs (they are rasterized again when next drawn)
+    pub fn clear(&mut self) {
+        for page in self.pages.drain(..) {
+            images::destroy_texture(&self.texture_creator, page.texture);
+        }
+        self.sets.clear();
+        // Text measurements depend on the UI scale as well
+        clear_styled_metrics();
+    }
+
+    /// Position each glyph of `text`, rasterizing missing glyphs
+    /// - Returns (glyph, x) pairs & the line height
+    fn layout<'t>(&mut self, ttf_context: &'t Sdl2TtfContext, font: &FontParams, style: ttf::FontStyle, text: &str) -> (Vec<(Glyph, i32)>, u32) {
+        let key = (*font, style.bits());
+        let characters: Vec<char> = text.chars().collect();
+
+        // Fonts are only loaded when something is missing
+        let mut chain: Option<FontChain> = None;
+        let load = |chain: &mut Option<FontChain<'t>>| {
+            if chain.is_none() {
+                *chain = Some(load_styled(ttf_context, font, style));
+            }
+        };
+
+        // Rasterize new glyphs
+        for character in &characters {
+            if self.sets.get(&key).is_some_and(|set| set.glyphs.contains_key(character)) {
+                continue;
+            }
+
+            load(&mut chain);
+            let glyph = self.rasterize(chain.as_ref().unwrap(), *character);
+            self.sets.entry(key).or_default().glyphs.insert(*character, glyph);
+        }
+
+        // Measure new character pairs
+        for pair in characters.windows(2) {
+            let pair = (pair[0], pair[1]);
+            if self.sets.get(&key).is_some_and(|set| set.kerning.contains_key(&pair)) {
+                continue;
+            }
+
+            load(&mut chain);
+            let kerning = chain.as_ref().unwrap().kerning(pair.0, pair.1);
+            self.sets.entry(key).or_default().kerning.insert(pair, kerning);
+        }
+
+        let set = match self.sets.get(&key) {
+            Some(set) => set,
+            None => return (Vec::new(), 0),
+        };
+
+        let mut glyphs = Vec::with_capacity(characters.len());
+        let mut x = 0;
+        let mut height = 0;
+        for (index, character) in characters.iter().enumerate() {
+            if index > 0 {
+                x += set.kerning[&(characters[index - 1], *character)];
+            }
+
+            let glyph = set.glyphs[character];
+            if let Some(source) = glyph.source {
+                height = height.max(source.height() as i32 + glyph.offset_y.max(0));
+            }
+            glyphs.push((glyph, x));
+            x += glyph.advance;
+        }
+
+        (glyphs, height.max(0) as u32)
+    }
+
+    /// Rasterize a glyph & copy it into a page
+    fn rasterize(&mut self, chain: &FontChain, character: char) -> Glyph {
+        let (surface, advance, offset_y) = match chain.render_glyph(character) {
+            Ok(rendered) => rendered,
+            // Glyphs which cannot be rendered (such as control characters) take no space
+            Err(_) => return Glyph { page: 0, source: None, advance: 0, offset_y: 0 },
+        };
+
+        let (width, height) = (surface.width(), surface.height());
+        let mut glyph = Glyph { page: 0, source: None, advance, offset_y };
+        if width == 0 || height == 0 {
+            return glyph;
+        }
+
+        let surface = match to_argb8888(surface) {
+            Ok(surface) => surface,
+            Err(_) => return glyph,
+        };
+
+        // Place the glyph in the newest page, or a new page if full
+        let mut region = self.pages.last_mut().and_then(|page| page.shelves.allocate(width, height));
+        if region.is_none() {
+            if let Ok(page) = self.new_page(width.max(height)) {
+                self.pages.push(page);
+                region = self.pages.last_mut().unwrap().shelves.allocate(width, height);
+            }
+        }
+
+        if let Some(region) = region {
+            let page = self.pages.len() - 1;
+            let uploaded = surface.with_lock(|pixels| {
+                self.pages[page].texture.update(region, pixels, surface.pitch() as usize)
+            });
+
+            if uploaded.is_ok() {
+                glyph.page = page;
+                glyph.source = Some(region);
+            }
+        }
+
+        glyph
+    }
+
+    fn new_page(&self, minimum_size: u32) -> Result<AtlasPage, String> {
+        let size = ATLAS_PAGE_SIZE.max(minimum_size);
+        let mut texture = self.texture_creator
+            .create_texture_static(PixelFormatEnum::ARGB8888, size, size)
+            .map_err(|error| error.to_string())?;
+        texture.set_blend_mode(BlendMode::Blend);
+
+        // Texture contents are undefined until written
+        let transparent = vec![0u8; (size * size * 4) as usize];
+        texture.update(None, &transparent, (size * 4) as usize).map_err(|error| error.to_string())?;
+
+        Ok(AtlasPage { texture, shelves: Shelves::new(size) })
+    }
+}
+
+impl Drop for GlyphAtlas {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// Ensure a surface uses the atlas' pixel format
+fn to_argb8888(surface: Surface<'static>) -> Result<Surface<'static>, String> {
+    if surface.pixel_format_enum() == PixelFormatEnum::ARGB8888 {
+        return Ok(surface);
+    }
+
+    let mut converted = Surface::new(surface.width(), surface.height(), PixelFormatEnum::ARGB8888)?;
+    let mut surface = surface;
+    surface.set_blend_mode(BlendMode::None)?;
+    surface.blit(None, &mut converted, None)?;
+    Ok(converted)
+}
+
+// ========================== Styled Font Metrics ========================== //
+
+/// Strings measured per font & style before the measurements are discarded
+const MAX_MEASURED_WIDTHS: usize = 4096;
+
+thread_local! {
+    /// Metrics of styled fonts, keyed by (font, style)
+    static STYLED_METRICS: RefCell<HashMap<(FontParams, i32), StyledMetrics>> = RefCell::new(HashMap::new());
+}
+
+/// Cached measurements of a single font, size & style
+struct StyledMetrics {
+    height: i32,
+    ascent: i32,
+    line_spacing: i32,
+    widths: HashMap<String, u32>,
+}
+
+/// Measures text of a styled font (see `with_styled_metrics`)
+/// - The font is only loaded when a measurement is not cached
+pub struct StyledMeasure<'m, 'ttf> {
+    metrics: &'m mut StyledMetrics,
+    font: Option<FontChain<'ttf>>,
+    ttf_context: &'ttf Sdl2TtfContext,
+    params: FontParams,
+    style: ttf::FontStyle,
+}
+
+impl StyledMeasure<'_, '_> {
+    pub fn height(&self) -> i32 {
+        self.metrics.height
+    }
+
+    pub fn ascent(&self) -> i32 {
+        self.metrics.ascent
+    }
+
+    pub fn recommended_line_spacing(&self) -> i32 {
+        self.metrics.line_spacing
+    }
+
+    /// Width of rendered text
+    pub fn width(&mut self, text: &str) -> u32 {
+        if let Some(width) = self.metrics.widths.get(text) {
+            return *width;
+        }
+
+        let (ttf_context, params, style) = (self.ttf_context, self.params, self.style);
+        let font = self.font.get_or_insert_with(|| load_styled(ttf_context, &params, style));
+        let width = font.size_of(text).map(|(width, _)| width).unwrap_or(0);
+
+        if self.metrics.widths.len() >= MAX_MEASURED_WIDTHS {
+            self.metrics.widths.clear();
+        }
+        self.metrics.widths.insert(text.to_owned(), width);
+        width
+    }
+}
+
+/// Measure text of a font with `style` applied, using metrics cached across layouts
+/// - Fonts are only loaded for measurements which are not cached
+/// - `measure` must not call `with_styled_metrics` itself
+pub fn with_styled_metrics<R, F>(ttf_context: &Sdl2TtfContext, params: &FontParams, style: ttf::FontStyle, measure: F) -> R
+    where F: FnOnce(&mut StyledMeasure) -> R
+{
+    STYLED_METRICS.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let mut font = None;
+
+        let metrics = cache.entry((*params, style.bits())).or_insert_with(|| {
+            let loaded = load_styled(ttf_context, params, style);
+            let metrics = StyledMetrics {
+                height: loaded.height(),
+                ascent: loaded.ascent(),
+                line_spacing: loaded.recommended_line_spacing(),
+                widths: HashMap::new(),
+            };
+            font = Some(loaded);
+            metrics
+        });
+
+        measure(&mut StyledMeasure { metrics, font, ttf_context, params: *params, style })
+    })
+}
+
+/// Discard cached metrics (e.g. once the UI scale changes)
+pub fn clear_styled_metrics() {
+    STYLED_METRICS.with(|cache| cache.borrow_mut().clear());
+}
+
+fn load_styled<'ttf>(ttf_context: &'ttf Sdl2TtfContext, params: &FontParams, style: ttf::FontStyle) -> FontChain<'ttf> {
+    let mut font = FontChain::load(ttf_context, params);
+    font.set_style(style);
+    font
+}
+
+// ========================== System Fonts ========================== //
+
+thread_local! {
+    /// Font files within the system's font directories (found once)
+    static SYSTEM_FONT_FILES: RefCell<Option<Vec<PathBuf>>> = const { RefCell::new(None) };
+}
+
+/// Find an installed font by family name (such as `"DejaVu Sans"`)
+/// - Searches the standard font directories & those listed by fontconfig (see `font_directories`)
+/// - Matches file names, preferring regular styles (`DejaVuSans.ttf` over `DejaVuSans-Bold.ttf`)
+pub fn find_system_font(family: &str) -> Option<&'static str> {
+    let family = normalize_font_name(family);
+    if family.is_empty() {
+        return None;
+    }
+
+    SYSTEM_FONT_FILES.with(|files| {
+        let mut files = files.borrow_mut();
+        let files = files.get_or_insert_with(|| {
+            let mut found = Vec::new();
+            for directory in font_directories() {
+                collect_font_files(&directory, 0, &mut found);
+            }
+            found
+        });
+
+        files.iter()
+            .filter_map(|path| match_score(&family, path).map(|score| (score, path)))
+            .min_by_key(|(score, _)| *score)
+            .map(|(_, path)| ids::intern(path.to_string_lossy().into_owned()))
+    })
+}
+
+/// How well a font file matches a (normalized) family name
+/// - Lower scores are better matches & `None` means the file is another family
+fn match_score(family: &str, path: &Path) -> Option<usize> {
+    let stem = normalize_font_name(&path.file_stem()?.to_string_lossy());
+    let style = stem.strip_prefix(family)?;
+
+    match style {
+        "" | "regular" => Some(0),
+        "book" | "roman" | "medium" => Some(1),
+        _ => Some(2 + style.len()),
+    }
+}
+
+/// Directories containing installed fonts (Linux)
+/// - Standard locations & `<dir>` entries of fontconfig configuration files
+pub fn font_directories() -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let data_home = std::env::var_os("XDG_DATA_HOME").map(PathBuf::from)
+        .or_else(|| home.as_ref().map(|home| home.join(".local/share")));
+
+    let mut directories = vec![PathBuf::from("/usr/share/fonts"), PathBuf::from("/usr/local/share/fonts")];
+    if let Some(data_home) = &data_home {
+        directories.push(data_home.join("fonts"));
+    }
+    if let Some(home) = &home {
+        directories.push(home.join(".fonts"));
+    }
+
+    let mut configs = vec![PathBuf::from("/etc/fonts/fonts.conf")];
+    if let Ok(entries) = std::fs::read_dir("/etc/fonts/conf.d") {
+        configs.extend(entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()));
+    }
+    if let Some(home) = &home {
+        configs.push(home.join(".config/fontconfig/fonts.conf"));
+    }
+
+    for config in configs {
+        let contents = match std::fs::read_to_string(&config) {
+            Ok(contents) => contents,
+            Err(_) => continue,
+        };
+
+        for (attributes, directory) in config_dirs(&contents) {
+            let path = if attributes.contains("prefix=\"xdg\"") {
+                match &data_home {
+                    Some(data_home) => data_home.join(directory),
+                    None => continue,
+                }
+            } else if let Some(relative) = directory.strip_prefix("~/") {
+                match &home {
+                    Some(home) => home.join(relative),
+                    None => continue,
+                }
+            } else {
+                PathBuf::from(directory)
+            };
+
+            if !directories.contains(&path) {
+                directories.push(path);
+            }
+        }
+    }
+
+    directories
+}
+
+/// (attributes, directory) of each `<dir ...>directory</dir>` element
+fn config_dirs(contents: &str) -> Vec<(&str, &str)> {
+    let mut dirs = Vec::new();
+    let mut remaining = contents;
+
+    while let Some(start) = remaining.find("<dir") {
+        remaining = &remaining[start + 4..];
+
+        // Skip other elements such as `<dirname>`
+        let tag_end = match remaining.find('>') {
+            Some(tag_end) => tag_end,
+            None => break,
+        };
+        let attributes = &remaining[..tag_end];
+        if !(attributes.is_empty() || attributes.starts_with(' ')) || attributes.ends_with('/') {
+            continue;
+        }
+
+        remaining = &remaining[tag_end + 1..];
+        if let Some(end) = remaining.find("</dir>") {
+            dirs.push((attributes, remaining[..end].trim()));
+            remaining = &remaining[end..];
+        }
+    }
+
+    dirs
+}
+
+fn collect_font_files(directory: &Path, depth: usize, found: &mut Vec<PathBuf>) {
+    // Guard against symlink cycles
+    if depth > 8 {
+        return;
+    }
+
+    let entries = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_font_files(&path, depth + 1, found);
+        } else {
+            let extension = path.extension().map(|extension| extension.to_string_lossy().to_lowercase());
+            if let Some("ttf") | Some("otf") | Some("ttc") = extension.as_deref() {
+                found.push(path);
+            }
+        }
+    }
+}
+
+/// Lowercase letters & digits only (`"DejaVu Sans"` -> `"dejavusans"`)
+fn normalize_font_name(name: &str) -> String {
+    name.chars()
+        .filter(|character| character.is_alphanumeric())
+        .flat_map(|character| character.to_lowercase())
+        .collect()
 }
 
 // ========================== Font Backend ========================== //
@@ -48,7 +915,7 @@ pub struct Fonts<'ttf> {
     // ttf_context: &'ttf Sdl2TtfContext,
 
     /// Map of (FontParams -> Loaded Font)
-    font_map: HashMap<FontParams, ttf::Font<'ttf, 'static>>,
+    font_map: HashMap<FontParams, FontChain<'ttf>>,
 }
 
 impl<'ttf> Fonts<'ttf> {
@@ -61,22 +928,23 @@ impl<'ttf> Fonts<'ttf> {
 
     /// Obtain a reference to the desired font
     // TODO: How to handle missing font? Return default? Load and store that font?
-    pub fn get_font(&self, font: &FontParams) -> &ttf::Font {
-        self.font_map.get(&font).expect("No such font exists")
+    pub fn get_font(&self, font: &FontParams) -> &FontChain<'ttf> {
+        self.font_map.get(font).expect("No such font exists")
     }
 
     /// Load and store a font for future use
     pub fn load_font(&mut self, ttf_context: &'ttf Sdl2TtfContext, font_params: &FontParams) {
-        // TODO: assert that the font is not already loaded (saves some execution time)
-        let font = ttf_context.load_font(font_params.path, font_params.point_size).expect("Failed to load font");
+        if self.font_map.contains_key(font_params) {
+            return;
+        }
+
+        let font = FontChain::load(ttf_context, font_params);
         self.font_map.insert(*font_params, font);
     }
 
-    pub fn render_surface(&mut self, font: &FontParams, text: &str, color: sdl2::pixels::Color) -> Box<sdl2::surface::Surface> {
-        let surface = self.font_map.get(font)
-            .expect("No such font exists")
-            .render(text)
-            .blended(color)
+    pub fn render_surface(&mut self, font: &FontParams, text: &str, color: sdl2::pixels::Color) -> Box<sdl2::surface::Surface<'_>> {
+        let surface = self.get_font(font)
+            .render(text, color)
             .expect("Failed to render surface");
 
         Box::new(surface)
@@ -86,4 +954,279 @@ impl<'ttf> Fonts<'ttf> {
     pub fn size_surface(&self, font: &FontParams, text: &str) -> (u32, u32) {
         self.get_font(font).size_of(text).expect("Failed to query text size")
     }
-}
\ No newline at end of file
+
+    /// Obtain the dimensions (width, height) of `text` broken into lines according to `wrap`
+    pub fn size_text(&self, font: &FontParams, text: &str, wrap: &TextWrap) -> (u32, u32) {
+        if *wrap == TextWrap::default() && !text.contains('\n') {
+            return self.size_surface(font, text);
+        }
+
+        let loaded_font = self.get_font(font);
+        let lines = break_lines(text, wrap, |line| loaded_font.size_of(line).map(|(width, _)| width).unwrap_or(0));
+        text_block_size(loaded_font, &lines)
+    }
+}
+
+impl Default for Fonts<'_> {
+    fn default() -> Self {
+        Fonts::new()
+    }
+}
+
+// ========================== Text Layout ========================== //
+
+/// Ellipsis appended to truncated text
+const ELLIPSIS: &str = "…";
+
+/// Line breaking options for text
+/// - Explicit line breaks (`\n`) are always respected
+#[derive(PartialEq, Eq, Copy, Clone, Default)]
+pub struct TextWrap {
+    /// Break lines between words so that no line is wider than this (in pixels)
+    pub max_width: Option<u32>,
+    /// Truncate text beyond this many lines, ending the last line with an ellipsis
+    pub max_lines: Option<usize>,
+}
+
+/// A single line of laid out text
+#[derive(Clone)]
+pub struct TextLine {
+    pub text: String,
+    /// Width of the line in pixels
+    pub width: u32,
+    /// Whether the line ends a paragraph (explicit line break or end of text)
+    pub ends_paragraph: bool,
+}
+
+/// Break `text` into lines according to `wrap`
+/// - `measure` returns the width of a string in pixels
+/// - Words wider than `max_width` are broken between characters
+pub fn break_lines<F: FnMut(&str) -> u32>(text: &str, wrap: &TextWrap, mut measure: F) -> Vec<TextLine> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.split('\n') {
+        let max_width = match wrap.max_width {
+            Some(max_width) => max_width,
+            None => {
+                lines.push(TextLine { text: paragraph.to_owned(), width: measure(paragraph), ends_paragraph: true });
+                continue;
+            }
+        };
+
+        let mut current = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if current.is_empty() { word.to_owned() } else { format!("{} {}", current, word) };
+
+            if measure(&candidate) <= max_width {
+                current = candidate;
+                continue;
+            }
+
+            // The word does not fit on the current line -> begin a new one
+            if !current.is_empty() {
+                let width = measure(&current);
+                lines.push(TextLine { text: std::mem::take(&mut current), width, ends_paragraph: false });
+            }
+
+            // Break words which do not fit on a line of their own
+            let mut pieces = break_word(word, max_width, &mut measure);
+            current = pieces.pop().unwrap_or_default();
+            for piece in pieces {
+                let width = measure(&piece);
+                lines.push(TextLine { text: piece, width, ends_paragraph: false });
+            }
+        }
+
+        let width = measure(&current);
+        lines.push(TextLine { text: current, width, ends_paragraph: true });
+    }
+
+    if let Some(max_lines) = wrap.max_lines {
+        if lines.len() > max_lines && max_lines > 0 {
+            lines.truncate(max_lines);
+            let last = lines.last_mut().unwrap();
+
+            // Remove characters until the ellipsis fits
+            let mut truncated = last.text.trim_end().to_owned();
+            loop {
+                let candidate = format!("{}{}", truncated.trim_end(), ELLIPSIS);
+                let width = measure(&candidate);
+                if truncated.is_empty() || wrap.max_width.is_none_or(|max_width| width <= max_width) {
+                    *last = TextLine { text: candidate, width, ends_paragraph: true };
+                    break;
+                }
+                truncated.pop();
+            }
+        } else if max_lines == 0 {
+            lines.clear();
+        }
+    }
+
+    lines
+}
+
+/// Break `word` between characters into pieces no wider than `max_width`
+/// - Each piece has at least one character, even if that character is wider
+pub fn break_word<F: FnMut(&str) -> u32>(word: &str, max_width: u32, mut measure: F) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+
+    for character in word.chars() {
+        current.push(character);
+        if current.chars().count() > 1 && measure(&current) > max_width {
+            current.pop();
+            pieces.push(std::mem::replace(&mut current, character.to_string()));
+        }
+    }
+
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+
+    pieces
+}
+
+/// Dimensions (width, height) of laid out lines
+pub fn text_block_size(font: &FontChain, lines: &[TextLine]) -> (u32, u32) {
+    let width = lines.iter().map(|line| line.width).max().unwrap_or(0);
+    let height = if lines.is_empty() {
+        0
+    } else {
+        font.recommended_line_spacing() * (lines.len() as i32 - 1) + font.height()
+    };
+
+    (width, height.max(0) as u32)
+}
+
+/// Point size at which text `text_height` pixels tall (at `point_size`) becomes `target_height` pixels tall
+/// - Rounded down so that the text never overflows the target
+fn fitted_point_size(point_size: u16, text_height: u32, target_height: u32) -> u16 {
+    if text_height == 0 {
+        return point_size;
+    }
+
+    let scale = target_height as f32 / text_height as f32;
+    ((point_size as f32 * scale).floor() as u16).max(1)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every character is 10 pixels wide
+    fn measure(text: &str) -> u32 {
+        text.chars().count() as u32 * 10
+    }
+
+    fn wrapped(text: &str, max_width: Option<u32>, max_lines: Option<usize>) -> Vec<String> {
+        let wrap = TextWrap { max_width, max_lines };
+        break_lines(text, &wrap, measure).into_iter().map(|line| line.text).collect()
+    }
+
+    #[test]
+    fn break_lines_keeps_explicit_line_breaks() {
+        let lines = break_lines("a b\nc", &TextWrap::default(), measure);
+
+        assert_eq!(lines.iter().map(|line| line.text.as_str()).collect::<Vec<_>>(), vec!["a b", "c"]);
+        assert_eq!(lines.iter().map(|line| line.width).collect::<Vec<_>>(), vec![30, 10]);
+        assert!(lines.iter().all(|line| line.ends_paragraph));
+    }
+
+    #[test]
+    fn break_lines_between_words() {
+        let lines = break_lines("one two three\nfour", &TextWrap { max_width: Some(70), max_lines: None }, measure);
+
+        assert_eq!(lines.iter().map(|line| line.text.as_str()).collect::<Vec<_>>(), vec!["one two", "three", "four"]);
+        assert_eq!(lines.iter().map(|line| line.ends_paragraph).collect::<Vec<_>>(), vec![false, true, true]);
+    }
+
+    #[test]
+    fn break_lines_breaks_long_words() {
+        assert_eq!(wrapped("abcdefghij", Some(40), None), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrapped("ab abcdefgh c", Some(40), None), vec!["ab", "abcd", "efgh", "c"]);
+    }
+
+    #[test]
+    fn break_lines_truncates_with_ellipsis() {
+        assert_eq!(wrapped("one two three four", Some(70), Some(2)), vec!["one two", "three…"]);
+        // Characters are removed until the ellipsis fits
+        assert_eq!(wrapped("one two three", Some(70), Some(1)), vec!["one tw…"]);
+        assert_eq!(wrapped("a\nb", None, Some(1)), vec!["a…"]);
+        assert!(wrapped("a\nb", None, Some(0)).is_empty());
+    }
+
+    #[test]
+    fn normalize_font_name_keeps_letters_and_digits() {
+        assert_eq!(normalize_font_name("DejaVu Sans"), "dejavusans");
+        assert_eq!(normalize_font_name("Noto Sans CJK-JP 2"), "notosanscjkjp2");
+    }
+
+    #[test]
+    fn match_score_prefers_regular_styles() {
+        let score = |file: &str| match_score("dejavusans", Path::new(file));
+
+        assert_eq!(score("/usr/share/fonts/DejaVuSans.ttf"), Some(0));
+        assert_eq!(score("DejaVuSans-Regular.otf"), Some(0));
+        assert_eq!(score("DejaVuSans-Book.ttf"), Some(1));
+        assert!(score("DejaVuSans-Bold.ttf") > score("DejaVuSans-Book.ttf"));
+        assert!(score("DejaVuSans-BoldOblique.ttf") > score("DejaVuSans-Bold.ttf"));
+        assert_eq!(score("DejaVuSerif.ttf"), None);
+    }
+
+    #[test]
+    fn config_dirs_reads_dir_elements() {
+        let config = r#"
+            <fontconfig>
+                <dir>/usr/share/fonts</dir>
+                <dir prefix="xdg">fonts</dir>
+                <dirname>/ignored</dirname>
+                <dir/>
+                <dir>  ~/.fonts  </dir>
+            </fontconfig>
+        "#;
+
+        assert_eq!(config_dirs(config), vec![
+            ("", "/usr/share/fonts"),
+            (" prefix=\"xdg\"", "fonts"),
+            ("", "~/.fonts"),
+        ]);
+    }
+
+    #[test]
+    fn fallback_fonts_can_be_assigned() {
+        set_fallback_fonts(vec!["a.ttf", "b.ttf"]);
+        assert_eq!(fallback_fonts(), vec!["a.ttf", "b.ttf"]);
+    }
+
+    #[test]
+    fn resolve_font_keeps_unknown_names() {
+        assert_eq!(resolve_font(EMBEDDED_FONT), EMBEDDED_FONT);
+        assert_eq!(resolve_font("./missing/NoSuchFont.ttf"), "./missing/NoSuchFont.ttf");
+    }
+
+    #[test]
+    fn shelves_fill_rows_with_padding() {
+        let mut shelves = Shelves::new(10);
+
+        assert_eq!(shelves.allocate(4, 3), Some(Rect::new(0, 0, 4, 3)));
+        assert_eq!(shelves.allocate(4, 5), Some(Rect::new(5, 0, 4, 5)));
+        // Continues below the tallest glyph of the row
+        assert_eq!(shelves.allocate(4, 2), Some(Rect::new(0, 6, 4, 2)));
+        assert_eq!(shelves.allocate(11, 1), None);
+        assert_eq!(shelves.allocate(4, 4), None);
+    }
+
+    #[test]
+    fn fitted_point_size_rounds_down() {
+        assert_eq!(fitted_point_size(20, 30, 60), 40);
+        assert_eq!(fitted_point_size(20, 30, 40), 26);
+        assert_eq!(fitted_point_size(20, 30, 0), 1);
+        assert_eq!(fitted_point_size(20, 0, 60), 20);
+    }
+
+    #[test]
+    fn break_word_keeps_at_least_one_character() {
+        assert_eq!(break_word("abc", 5, measure), vec!["a", "b", "c"]);
+        assert_eq!(break_word("abc", 30, measure), vec!["abc"]);
+        assert!(break_word("", 30, measure).is_empty());
+    }
+}
@@ -1,15 +1,18 @@
 extern crate sdl2;
 use sdl2::surface::Surface;
-use sdl2::pixels::{PixelFormat, PixelFormatEnum};
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Texture, TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
 
-use std::path::Path;
-
-/* TODO: Consider allowing transparent color keys for bitmaps (and others?):
+use crate::view_components::ids;
+use crate::svg::Svg;
 
-See https://docs.rs/sdl2/0.32.2/sdl2/surface/struct.SurfaceRef.html#method.set_color_key
-and https://wiki.libsdl.org/SDL_SetColorKey
-
-*/
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Duration;
 
 // TODO: Error enum (e.g.: Unsupported, LoadFailed, etc.)
 
@@ -19,6 +22,7 @@ and https://wiki.libsdl.org/SDL_SetColorKey
 /// - jpeg (`.jpg`, `.jpeg`)
 /// - png (`.png`)
 /// - bitmap (`.bmp`)
+/// - svg (`.svg`), rasterized at its intrinsic size (see `load_sized` for other sizes)
 pub fn load_image(path: &Path) -> Result<Surface, String> {
     return match path.extension() {
         Some(os_str) => {
@@ -43,6 +47,12 @@ pub fn load_image(path: &Path) -> Result<Surface, String> {
                     Err("Not implemented".to_owned())
                 }
 
+                Some("svg") => {
+                    let svg = Svg::load(path)?;
+                    let (width, height) = svg.size();
+                    svg.rasterize(width, height)
+                }
+
                 _ => {
                     // TODO: Print only file suffix?
                     Err(format!("File '{:?}' has unsupported extension, '{:?}'", path, os_str))
@@ -56,6 +66,367 @@ pub fn load_image(path: &Path) -> Result<Surface, String> {
     }
 }
 
+// ========================== Image Cache ========================== //
+
+thread_local! {
+    /// Decoded images keyed by resource path, shared by all widgets
+    static SURFACES: RefCell<HashMap<&'static str, Rc<Surface<'static>>>> = RefCell::new(HashMap::new());
+}
+
+/// Load the image at `resource_path`, decoding each file only once
+/// - Widgets of regenerated views reuse the decoded image
+pub fn load_cached(resource_path: &'static str) -> Result<Rc<Surface<'static>>, String> {
+    cached_or_insert(resource_path, || load_image(Path::new(resource_path)))
+}
+
+/// Load the image at `resource_path` with `effects` applied (cached like `load_cached`)
+/// - Returns the processed image's cache key (see `ImageCache::texture`) & the image
+pub fn load_with_effects(resource_path: &'static str, effects: &ImageEffects) -> Result<(&'static str, Rc<Surface<'static>>), String> {
+    if effects.is_empty() {
+        return load_cached(resource_path).map(|surface| (resource_path, surface));
+    }
+
+    let key = ids::intern(effects.cache_key(resource_path));
+    let surface = cached_or_insert(key, || effects.apply(load_image(Path::new(resource_path))?))?;
+
+    Ok((key, surface))
+}
+
+/// Whether the image at `resource_path` is a vector image, which can be rasterized at any size
+pub fn is_vector(resource_path: &str) -> bool {
+    Path::new(resource_path).extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"))
+}
+
+/// Load the image at `resource_path` for drawing at `size` (physical pixels), with `effects` applied
+/// - Vector images are rasterized at `size` so they stay sharp at any scale factor.
+///   Each size is rasterized once, then cached like `load_cached` until released (see `ImageCache::release`)
+/// - Other images are loaded at their own size (see `load_with_effects`)
+pub fn load_sized(resource_path: &'static str, size: (u32, u32), effects: &ImageEffects) -> Result<(&'static str, Rc<Surface<'static>>), String> {
+    if !is_vector(resource_path) {
+        return load_with_effects(resource_path, effects);
+    }
+
+    let (width, height) = (size.0.max(1), size.1.max(1));
+    let sized_key = SIZED_KEYS.with(|keys| {
+        *keys.borrow_mut().entry((resource_path, width, height))
+            .or_insert_with(|| ids::intern(format!("{}@{}x{}", resource_path, width, height)))
+    });
+
+    let key = if effects.is_empty() {
+        sized_key
+    } else {
+        ids::intern(effects.cache_key(sized_key))
+    };
+
+    let surface = cached_or_insert(key, || {
+        let surface = Svg::load(Path::new(resource_path))?.rasterize(width, height)?;
+        if effects.is_empty() { Ok(surface) } else { effects.apply(surface) }
+    })?;
+
+    Ok((key, surface))
+}
+
+thread_local! {
+    /// Cache keys of rasterized vector images by (resource path, width, height)
+    static SIZED_KEYS: RefCell<HashMap<(&'static str, u32, u32), &'static str>> = RefCell::new(HashMap::new());
+}
+
+fn cached_or_insert<F>(key: &'static str, load: F) -> Result<Rc<Surface<'static>>, String>
+where F: FnOnce() -> Result<Surface<'static>, String> {
+    if let Some(surface) = SURFACES.with(|surfaces| surfaces.borrow().get(key).cloned()) {
+        return Ok(surface);
+    }
+
+    let surface = Rc::new(load()?);
+    SURFACES.with(|surfaces| surfaces.borrow_mut().insert(key, surface.clone()));
+
+    Ok(surface)
+}
+
+/// Image textures of a window, keyed by resource path
+/// - Images are decoded (see `load_cached`) & uploaded once, then shared by all widgets
+pub struct ImageCache {
+    // Keeps the renderer alive until the textures are destroyed (see `destroy_texture`)
+    texture_creator: TextureCreator<WindowContext>,
+    textures: HashMap<&'static str, Texture>,
+}
+
+impl ImageCache {
+    pub fn new(canvas: &WindowCanvas) -> Self {
+        ImageCache {
+            texture_creator: canvas.texture_creator(),
+            textures: HashMap::new(),
+        }
+    }
+
+    /// Decode & upload images before they are first drawn
+    pub fn preload(&mut self, resource_paths: &[&'static str]) -> Result<(), String> {
+        for resource_path in resource_paths {
+            self.texture(resource_path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Texture of the image at `resource_path` (uploaded when first requested)
+    /// - Also accepts keys returned by `load_with_effects`
+    pub fn texture(&mut self, resource_path: &'static str) -> Result<&mut Texture, String> {
+        if !self.textures.contains_key(resource_path) {
+            let surface = load_cached(resource_path)?;
+            return self.texture_of(resource_path, &surface);
+        }
+
+        Ok(self.textures.get_mut(resource_path).unwrap())
+    }
+
+    /// Texture of an already loaded image, uploaded under `key` when first requested
+    pub fn texture_of(&mut self, key: &'static str, surface: &Surface) -> Result<&mut Texture, String> {
+        if !self.textures.contains_key(key) {
+            let texture = self.texture_creator.create_texture_from_surface(surface)
+                .map_err(|error| error.to_string())?;
+            self.textures.insert(key, texture);
+        }
+
+        Ok(self.textures.get_mut(key).unwrap())
+    }
+
+    /// Release an image (it is loaded again when next used)
+    /// - Includes every size of a vector image (such as sizes from a previous scale factor)
+    ///   & every variant with `ImageEffects` applied
+    pub fn evict(&mut self, resource_path: &str) {
+        let is_evicted = |key: &str| is_variant_of(key, resource_path);
+
+        let evicted: Vec<&'static str> = self.textures.keys().copied().filter(|key| is_evicted(key)).collect();
+        for key in evicted {
+            self.destroy(key);
+        }
+        SURFACES.with(|surfaces| surfaces.borrow_mut().retain(|key, _| !is_evicted(key)));
+    }
+
+    /// Release the image cached under `key` (such as a vector image rasterized at a size no longer drawn)
+    /// - Widgets holding the image keep their copy, which is uploaded again when next drawn
+    pub fn release(&mut self, key: &str) {
+        self.destroy(key);
+        SURFACES.with(|surfaces| surfaces.borrow_mut().remove(key));
+    }
+
+    /// Release all images
+    pub fn clear(&mut self) {
+        for (_, texture) in self.textures.drain() {
+            destroy_texture(&self.texture_creator, texture);
+        }
+        SURFACES.with(|surfaces| surfaces.borrow_mut().clear());
+    }
+
+    fn destroy(&mut self, key: &str) {
+        if let Some(texture) = self.textures.remove(key) {
+            destroy_texture(&self.texture_creator, texture);
+        }
+    }
+}
+
+impl Drop for ImageCache {
+    fn drop(&mut self) {
+        for (_, texture) in self.textures.drain() {
+            destroy_texture(&self.texture_creator, texture);
+        }
+    }
+}
+
+/// Whether `key` caches the image at `resource_path` (at any size & with any `ImageEffects`)
+fn is_variant_of(key: &str, resource_path: &str) -> bool {
+    match key.strip_prefix(resource_path) {
+        Some(rest) => rest.is_empty() || rest.starts_with('@') || rest.starts_with('?'),
+        None => false,
+    }
+}
+
+/// Free a texture's video memory
+/// - Textures are not freed when dropped (only when their renderer is destroyed),
+///   so caches destroy the textures they discard
+/// - Requires the `TextureCreator` which created the texture, as it keeps the renderer alive
+pub(crate) fn destroy_texture(_texture_creator: &TextureCreator<WindowContext>, texture: Texture) {
+    // Safe: the renderer outlives the creator
+    unsafe { texture.destroy() }
+}
+
+// ========================== Sprite Sheets ========================== //
+
+/// A single frame of an animation
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Frame {
+    /// Region of the sprite sheet
+    pub source: Rect,
+    /// How long the frame is shown
+    pub duration: Duration,
+}
+
+/// An animation stored as the frames of a single image
+#[derive(Clone, PartialEq, Debug)]
+pub struct SpriteSheet {
+    pub resource_path: &'static str,
+    pub frames: Vec<Frame>,
+    /// Whether playback restarts after the last frame (unless overridden by the widget)
+    pub looping: bool,
+}
+
+impl SpriteSheet {
+    /// Frames of equal size, laid out left to right & top to bottom
+    /// - `frame_count`: Number of frames (`None` for every cell of the image)
+    pub fn grid(resource_path: &'static str, frame_size: (u32, u32), frame_count: Option<usize>, frame_duration: Duration) -> Result<Self, String> {
+        let (frame_width, frame_height) = frame_size;
+        if frame_width == 0 || frame_height == 0 {
+            return Err(String::from("Frames must not be empty"));
+        }
+
+        let (width, height) = load_cached(resource_path)?.size();
+        let columns = (width / frame_width) as usize;
+        let cells = columns * (height / frame_height) as usize;
+        let frame_count = frame_count.unwrap_or(cells);
+
+        if frame_count == 0 || frame_count > cells {
+            return Err(format!("'{}' holds {} frames of {}x{}, but {} were requested", resource_path, cells, frame_width, frame_height, frame_count));
+        }
+
+        let frames = (0..frame_count)
+            .map(|index| Frame {
+                source: Rect::new(
+                    ((index % columns) as u32 * frame_width) as i32,
+                    ((index / columns) as u32 * frame_height) as i32,
+                    frame_width,
+                    frame_height,
+                ),
+                duration: frame_duration,
+            })
+            .collect();
+
+        Ok(SpriteSheet {
+            resource_path,
+            frames,
+            looping: true,
+        })
+    }
+
+    /// Load a sprite sheet from a TOML descriptor
+    /// ## Format
+    /// ```toml
+    /// image = "spinner.bmp"       # Relative to the descriptor
+    /// frame_width = 32
+    /// frame_height = 32
+    /// frames = 8                  # Optional (defaults to every cell)
+    /// frame_duration = 80         # Milliseconds
+    /// durations = [80, 80, 400]   # Optional per-frame durations (milliseconds)
+    /// loop = false                # Optional (defaults to true)
+    /// ```
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read sprite sheet {}: {}", path.display(), e))?;
+
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        SpriteSheet::from_toml_str(&contents, directory)
+            .map_err(|e| format!("Invalid sprite sheet {}: {}", path.display(), e))
+    }
+
+    /// Load a sprite sheet descriptor (see `from_file`), reading each file only once
+    /// - Widgets of regenerated views reuse the parsed sprite sheet
+    pub fn load_cached(descriptor_path: &str) -> Result<Self, String> {
+        if let Some(sheet) = SPRITE_SHEETS.with(|sheets| sheets.borrow().get(descriptor_path).cloned()) {
+            return Ok(sheet);
+        }
+
+        let sheet = SpriteSheet::from_file(descriptor_path)?;
+        SPRITE_SHEETS.with(|sheets| sheets.borrow_mut().insert(descriptor_path.to_owned(), sheet.clone()));
+
+        Ok(sheet)
+    }
+
+    /// Parse a sprite sheet descriptor (see `from_file`)
+    /// - `directory`: The image path is relative to this directory
+    pub fn from_toml_str(contents: &str, directory: &Path) -> Result<Self, String> {
+        let root: toml::Value = contents.parse().map_err(|e: toml::de::Error| e.to_string())?;
+
+        let integer = |key: &str| -> Result<Option<u64>, String> {
+            match root.get(key) {
+                None => Ok(None),
+                Some(toml::Value::Integer(value)) if *value >= 0 => Ok(Some(*value as u64)),
+                Some(_) => Err(format!("`{}` must be a non-negative integer", key)),
+            }
+        };
+        let required = |key: &str| integer(key)?.ok_or_else(|| format!("Missing `{}`", key));
+
+        let image = root.get("image").and_then(|image| image.as_str()).ok_or("Missing `image`")?;
+        let resource_path = ids::intern(directory.join(image).to_string_lossy().into_owned());
+
+        let frame_size = (required("frame_width")? as u32, required("frame_height")? as u32);
+        let frame_duration = Duration::from_millis(integer("frame_duration")?.unwrap_or(100));
+
+        let mut sheet = SpriteSheet::grid(resource_path, frame_size, integer("frames")?.map(|frames| frames as usize), frame_duration)?;
+
+        if let Some(durations) = root.get("durations") {
+            let durations = durations.as_array().ok_or("`durations` must be an array")?;
+            if durations.len() != sheet.frames.len() {
+                return Err(format!("`durations` has {} entries, but there are {} frames", durations.len(), sheet.frames.len()));
+            }
+
+            for (frame, duration) in sheet.frames.iter_mut().zip(durations) {
+                match duration.as_integer() {
+                    Some(milliseconds) if milliseconds >= 0 => frame.duration = Duration::from_millis(milliseconds as u64),
+                    _ => return Err(String::from("`durations` must contain non-negative integers")),
+                }
+            }
+        }
+
+        if let Some(looping) = root.get("loop") {
+            sheet.looping = looping.as_bool().ok_or("`loop` must be true or false")?;
+        }
+
+        Ok(sheet)
+    }
+
+    /// Size of the first frame
+    pub fn frame_size(&self) -> (u32, u32) {
+        self.frames.first().map_or((0, 0), |frame| frame.source.size())
+    }
+
+    pub fn total_duration(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.duration).sum()
+    }
+
+    /// Index of the frame shown `elapsed` after playback started
+    /// - Without `looping`, the last frame is held
+    pub fn frame_at(&self, elapsed: Duration, looping: bool) -> usize {
+        let total = self.total_duration();
+        if total == Duration::from_secs(0) {
+            return 0;
+        }
+
+        let mut remaining = if looping {
+            Duration::from_nanos((elapsed.as_nanos() % total.as_nanos()) as u64)
+        } else if elapsed >= total {
+            return self.frames.len() - 1;
+        } else {
+            elapsed
+        };
+
+        for (index, frame) in self.frames.iter().enumerate() {
+            if remaining < frame.duration {
+                return index;
+            }
+            remaining -= frame.duration;
+        }
+
+        self.frames.len() - 1
+    }
+}
+
+thread_local! {
+    /// Parsed sprite sheets keyed by descriptor path
+    static SPRITE_SHEETS: RefCell<HashMap<String, SpriteSheet>> = RefCell::new(HashMap::new());
+}
+
 // fn load_png(path: &Path) -> Surface {
     
 // }
@@ -69,34 +440,287 @@ fn load_bitmap(path: &Path) -> Surface {
     Surface::load_bmp(path).expect("Failed to load bitmap")
 }
 
-// TODO: It is probably better to just obtain a u8 array directly from the file rather than
-//        doing all this FFI stuff and working with 2 surfaces
-fn extend_surface(surface: &Surface /*, (width, height) */) -> Result<(/* Surface */), String> {
+// ========================== Pixel Operations ========================== //
 
-    // FIXME: There is no easy way to convert pixel formats. See above TODO instead.
+/// Adjustments baked into a loaded image's pixels (see `load_with_effects`)
+/// - Tint & opacity are applied when drawing instead, so that every variant shares one texture (see `Image::with_tint`)
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct ImageEffects {
+    /// Pixels of this color become transparent
+    pub color_key: Option<Color>,
+}
+
+impl ImageEffects {
+    pub fn is_empty(&self) -> bool {
+        *self == ImageEffects::default()
+    }
+
+    /// Apply the effects, converting the image to RGBA8888
+    pub fn apply<'a>(&self, surface: Surface<'a>) -> Result<Surface<'a>, String> {
+        match self.color_key {
+            Some(color) => apply_color_key(surface, color),
+            None => to_rgba8888(surface),
+        }
+    }
+
+    /// Identifies an image processed with these effects
+    /// - Starts with `resource_path?` so that `ImageCache::evict` finds it
+    fn cache_key(&self, resource_path: &str) -> String {
+        match self.color_key {
+            Some(color) => format!("{}?color_key={:02X}{:02X}{:02X}", resource_path, color.r, color.g, color.b),
+            None => format!("{}?rgba", resource_path),
+        }
+    }
+}
+
+/// Convert an image to RGBA8888 (required by the pixel operations below)
+/// - Pixels matching the image's color key (if any) become transparent
+pub fn to_rgba8888<'a>(mut surface: Surface<'a>) -> Result<Surface<'a>, String> {
+    if surface.pixel_format_enum() == PixelFormatEnum::RGBA8888 && surface.color_key().is_err() {
+        return Ok(surface);
+    }
 
-    // let RGBA8888_surface = surface.convert(&PixelFormat::from(PixelFormatEnum::RGBA8888));
-    // let pixel_format = surface.pixel_format_enum();
+    let mut converted = Surface::new(surface.width(), surface.height(), PixelFormatEnum::RGBA8888)?;
 
-    unsafe {
-        // Handle to raw SDL_Surface
-        let raw_surface = *surface.raw();
+    // Copy pixels unblended. Color keyed pixels are skipped, leaving them transparent.
+    surface.set_blend_mode(BlendMode::None)?;
+    surface.blit(None, &mut converted, None)?;
+
+    Ok(converted)
+}
+
+/// Make pixels of `color` transparent, converting the image to RGBA8888
+pub fn apply_color_key<'a>(mut surface: Surface<'a>, color: Color) -> Result<Surface<'a>, String> {
+    surface.set_color_key(true, color)?;
+    to_rgba8888(surface)
+}
+
+/// Multiply the color of each pixel by its alpha (RGBA8888 images only)
+/// - The result must be drawn with a premultiplied blend mode; SDL's default `BlendMode::Blend` darkens its edges
+pub fn premultiply_alpha(surface: &mut Surface) -> Result<(), String> {
+    map_pixels(surface, |[r, g, b, a]| [multiply(r, a), multiply(g, a), multiply(b, a), a])
+}
+
+/// Multiply the color & alpha of each pixel by `color` (RGBA8888 images only)
+pub fn tint(surface: &mut Surface, color: Color) -> Result<(), String> {
+    map_pixels(surface, |[r, g, b, a]| {
+        [multiply(r, color.r), multiply(g, color.g), multiply(b, color.b), multiply(a, color.a)]
+    })
+}
+
+/// Multiply the alpha of each pixel by `opacity` (0.0 - 1.0) (RGBA8888 images only)
+pub fn set_opacity(surface: &mut Surface, opacity: f32) -> Result<(), String> {
+    let opacity = (opacity.clamp(0.0, 1.0) * 255.0).round() as u8;
+    map_pixels(surface, |[r, g, b, a]| [r, g, b, multiply(a, opacity)])
+}
+
+/// `value * factor / 255`, rounded
+fn multiply(value: u8, factor: u8) -> u8 {
+    ((value as u32 * factor as u32 + 127) / 255) as u8
+}
 
-        // Total number of pixels
-        let num_pixels = (raw_surface.w * raw_surface.h) as isize;
+/// Replace each pixel's [r, g, b, a] components
+fn map_pixels<F: Fn([u8; 4]) -> [u8; 4]>(surface: &mut Surface, map: F) -> Result<(), String> {
+    if surface.pixel_format_enum() != PixelFormatEnum::RGBA8888 {
+        return Err("Image must be converted to RGBA8888 (see `to_rgba8888`)".to_owned());
+    }
 
-        // Represent pixel data as unsigned 8-bit values
-        let pixels = raw_surface.pixels as *mut u8;
+    let row_length = surface.width() as usize * 4;
+    let pitch = surface.pitch() as usize;
 
-        // Note that range is exclusive on [0, n)
-        for i in 0..num_pixels {
-            // WARNING: incorrect offsets introduce undefined behavior (does not crash program)
-            if i % 4 == 0 {
-                *pixels.offset(i) = 0;
+    surface.with_lock_mut(|pixels| {
+        for row in pixels.chunks_mut(pitch) {
+            let row_length = row_length.min(row.len());
+            for pixel in row[..row_length].chunks_exact_mut(4) {
+                // Pixels are packed as 0xRRGGBBAA in native byte order
+                let packed = u32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+                let mapped = u32::from_be_bytes(map(packed.to_be_bytes()));
+                pixel.copy_from_slice(&mapped.to_ne_bytes());
             }
         }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn images_are_loaded_once() {
+        let loads = Cell::new(0);
+        let load = || {
+            loads.set(loads.get() + 1);
+            Surface::new(2, 2, PixelFormatEnum::RGBA8888)
+        };
+
+        let first = cached_or_insert("tests/loaded_once.bmp", load).unwrap();
+        let second = cached_or_insert("tests/loaded_once.bmp", load).unwrap();
+        assert!(Rc::ptr_eq(&first, &second));
+        assert_eq!(loads.get(), 1);
     }
-    
-    // temporary
-    Err("Failed".to_owned())
-}
\ No newline at end of file
+
+    #[test]
+    fn failed_loads_are_not_cached() {
+        assert!(cached_or_insert("tests/failed.bmp", || Err(String::from("missing"))).is_err());
+        assert!(cached_or_insert("tests/failed.bmp", || Surface::new(1, 1, PixelFormatEnum::RGBA8888)).is_ok());
+    }
+
+    #[test]
+    fn variants_share_the_resource_path() {
+        assert!(is_variant_of("icons/a.svg", "icons/a.svg"));
+        assert!(is_variant_of("icons/a.svg@32x32", "icons/a.svg"));
+        assert!(is_variant_of("icons/a.svg@32x32?rgba", "icons/a.svg"));
+        assert!(is_variant_of("icons/a.bmp?color_key=FF00FF", "icons/a.bmp"));
+        assert!(!is_variant_of("icons/a.svg.bak", "icons/a.svg"));
+        assert!(!is_variant_of("icons/b.svg", "icons/a.svg"));
+    }
+
+    /// A 1x1 RGBA8888 image
+    fn pixel_image(pixel: [u8; 4]) -> Surface<'static> {
+        let mut surface = Surface::new(1, 1, PixelFormatEnum::RGBA8888).unwrap();
+        map_pixels(&mut surface, |_| pixel).unwrap();
+        surface
+    }
+
+    fn pixel_of(surface: &Surface) -> [u8; 4] {
+        surface.with_lock(|pixels| u32::from_ne_bytes([pixels[0], pixels[1], pixels[2], pixels[3]]).to_be_bytes())
+    }
+
+    #[test]
+    fn multiply_rounds() {
+        assert_eq!(multiply(255, 255), 255);
+        assert_eq!(multiply(255, 0), 0);
+        assert_eq!(multiply(200, 128), 100);
+        assert_eq!(multiply(1, 128), 1);
+    }
+
+    #[test]
+    fn premultiply_alpha_scales_colors() {
+        let mut surface = pixel_image([200, 100, 50, 128]);
+        premultiply_alpha(&mut surface).unwrap();
+        assert_eq!(pixel_of(&surface), [100, 50, 25, 128]);
+    }
+
+    #[test]
+    fn tint_multiplies_every_component() {
+        let mut surface = pixel_image([255, 200, 100, 255]);
+        tint(&mut surface, Color::RGBA(128, 255, 0, 128)).unwrap();
+        assert_eq!(pixel_of(&surface), [128, 200, 0, 128]);
+    }
+
+    #[test]
+    fn set_opacity_clamps() {
+        let mut surface = pixel_image([10, 20, 30, 200]);
+        set_opacity(&mut surface, 0.5).unwrap();
+        assert_eq!(pixel_of(&surface), [10, 20, 30, 100]);
+
+        set_opacity(&mut surface, 2.0).unwrap();
+        assert_eq!(pixel_of(&surface), [10, 20, 30, 100]);
+        set_opacity(&mut surface, -1.0).unwrap();
+        assert_eq!(pixel_of(&surface), [10, 20, 30, 0]);
+    }
+
+    #[test]
+    fn effects_identify_processed_images() {
+        let keyed = ImageEffects { color_key: Some(Color::RGB(255, 0, 255)) };
+
+        assert!(ImageEffects::default().is_empty());
+        assert!(!keyed.is_empty());
+        assert_eq!(keyed.cache_key("a.bmp"), "a.bmp?color_key=FF00FF");
+        assert_eq!(ImageEffects::default().cache_key("a.bmp"), "a.bmp?rgba");
+        assert!(is_variant_of(&keyed.cache_key("a.bmp"), "a.bmp"));
+    }
+
+    fn sheet(durations: &[u64]) -> SpriteSheet {
+        SpriteSheet {
+            resource_path: "sheet.bmp",
+            frames: durations.iter().enumerate().map(|(index, duration)| Frame {
+                source: Rect::new(index as i32 * 8, 0, 8, 8),
+                duration: Duration::from_millis(*duration),
+            }).collect(),
+            looping: true,
+        }
+    }
+
+    #[test]
+    fn frame_at_looping() {
+        let sheet = sheet(&[100, 100, 400]);
+        let frame_at = |milliseconds: u64| sheet.frame_at(Duration::from_millis(milliseconds), true);
+
+        assert_eq!(frame_at(0), 0);
+        assert_eq!(frame_at(99), 0);
+        assert_eq!(frame_at(100), 1);
+        assert_eq!(frame_at(250), 2);
+        assert_eq!(frame_at(600), 0);
+        assert_eq!(frame_at(750), 1);
+    }
+
+    #[test]
+    fn frame_at_holds_last_frame() {
+        let sheet = sheet(&[100, 100, 400]);
+
+        assert_eq!(sheet.frame_at(Duration::from_millis(150), false), 1);
+        assert_eq!(sheet.frame_at(Duration::from_millis(600), false), 2);
+        assert_eq!(sheet.frame_at(Duration::from_secs(60), false), 2);
+        assert_eq!(self::sheet(&[0, 0]).frame_at(Duration::from_millis(50), true), 0);
+    }
+
+    #[test]
+    fn sprite_sheet_descriptor() {
+        let directory = std::env::temp_dir();
+        std::fs::write(directory.join("rustui_sprite_sheet_test.svg"), r#"<svg width="64" height="32"></svg>"#).unwrap();
+
+        let sheet = SpriteSheet::from_toml_str(r#"
+            image = "rustui_sprite_sheet_test.svg"
+            frame_width = 16
+            frame_height = 16
+            frames = 6
+            durations = [10, 20, 30, 40, 50, 60]
+            loop = false
+        "#, &directory).unwrap();
+
+        assert!(sheet.resource_path.ends_with("rustui_sprite_sheet_test.svg"));
+        assert_eq!(sheet.frames.len(), 6);
+        assert_eq!(sheet.frames[5].source, Rect::new(16, 16, 16, 16));
+        assert_eq!(sheet.frames[5].duration, Duration::from_millis(60));
+        assert!(!sheet.looping);
+
+        let descriptor = |extra: &str| format!("image = \"rustui_sprite_sheet_test.svg\"\nframe_width = 16\nframe_height = 16\n{}", extra);
+        let grid = SpriteSheet::from_toml_str(&descriptor(""), &directory).unwrap();
+        assert_eq!(grid.frames.len(), 8);
+        assert_eq!(grid.frames[0].duration, Duration::from_millis(100));
+        assert!(grid.looping);
+
+        assert!(SpriteSheet::from_toml_str(&descriptor("frames = 9"), &directory).is_err());
+        assert!(SpriteSheet::from_toml_str(&descriptor("durations = [10, 20]"), &directory).is_err());
+        assert!(SpriteSheet::from_toml_str(&descriptor("loop = \"yes\""), &directory).is_err());
+    }
+
+    #[test]
+    fn sprite_sheet_descriptor_errors() {
+        let directory = Path::new("");
+        let from_toml_str = |contents: &str| SpriteSheet::from_toml_str(contents, directory);
+
+        assert!(from_toml_str("image = ").is_err());
+        assert!(from_toml_str("frame_width = 16\nframe_height = 16").is_err());
+        assert!(from_toml_str("image = \"a.bmp\"\nframe_height = 16").is_err());
+        assert!(from_toml_str("image = \"a.bmp\"\nframe_width = 16\nframe_height = -1").is_err());
+        assert!(from_toml_str("image = \"a.bmp\"\nframe_width = \"16\"\nframe_height = 16").is_err());
+    }
+
+    #[test]
+    fn sprite_sheets_are_parsed_once() {
+        let path = std::env::temp_dir().join("rustui_cached_sprite_sheet_test.toml");
+        std::fs::write(&path, "image = \"rustui_sprite_sheet_test.svg\"\nframe_width = 16\nframe_height = 16").unwrap();
+        std::fs::write(std::env::temp_dir().join("rustui_sprite_sheet_test.svg"), r#"<svg width="64" height="32"></svg>"#).unwrap();
+        let path = path.to_string_lossy().into_owned();
+
+        let sheet = SpriteSheet::load_cached(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(SpriteSheet::load_cached(&path), Ok(sheet));
+        assert!(SpriteSheet::from_file(&path).is_err());
+    }
+}
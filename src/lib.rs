@@ -25,7 +25,71 @@ by Jonathan Swerdlow
 //! let mut application_state = State::new();
 //! let mut main_window = Window::init("Title", &mut application_state);
 //!```
-//! 
+//!
+//! `Window::start` regenerates the view by cloning & comparing state (`T: Clone + PartialEq`).
+//! For large states, implement `state::TrackChanges` and call `Window::start_tracked` instead:
+//! ```rust,ignore
+//! struct State {
+//!     counter: Observable<i32>,
+//!     items: Observable<Vec<String>>,
+//! }
+//!
+//! impl TrackChanges for State {
+//!     fn take_changed(&mut self) -> bool {
+//!         // Non-short-circuiting `|` clears every field
+//!         self.counter.take_changed() | self.items.take_changed()
+//!     }
+//! }
+//! ...
+//! Button::new("add_id").with_on_click(|state: &mut State| state.counter.modify(|c| *c += 1))
+//!```
+//!
+//! # Background Tasks
+//! Callbacks run on the UI thread. Long-running work (file IO, computation) should be
+//! spawned as a `tasks::Task`, whose result is applied to state on the UI thread:
+//! ```rust,ignore
+//! .with_on_click(|state: &mut State| {
+//!     let handle = Task::new(|context: &TaskContext| {
+//!             for i in 0..100 {
+//!                 if context.is_cancelled() { return None; }
+//!                 context.set_progress(i as f32 / 100.0);
+//!                 ...
+//!             }
+//!             Some(result)
+//!         })
+//!         .with_on_progress(|state: &mut State, progress| state.progress = progress)
+//!         .with_on_complete(|state: &mut State, result| state.result = result)
+//!         .spawn();
+//!
+//!     // Keep the handle to cancel the task or check whether it is finished
+//!     state.task = Some(handle);
+//! })
+//! ```
+//!
+//! # Themes
+//! Widgets & views take their default colors, font & spacing from the current `Theme`.
+//! Builder methods (such as `with_base_color`) override the theme for a single widget.
+//! ```rust,ignore
+//! let mut theme = Theme::light();
+//! theme.button.base = colors::WHITE;
+//! main_window.set_theme(theme);
+//!
+//! // Or load a TOML style file, which is reloaded whenever it changes
+//! main_window.set_theme_file("./res/style/example.toml").unwrap();
+//! ```
+//!
+//! # Custom Drawing
+//! A `Canvas` draws charts, diagrams, etc. each frame using a `Painter` (canvas-relative coordinates):
+//! ```rust,ignore
+//! Canvas::new("chart_id", (300, 200))
+//!     .with_background(colors::WHITE)
+//!     .with_on_draw(|state: &State, painter: &mut Painter| {
+//!         painter.polyline(&state.points, 2, colors::DARK_PURPLE);
+//!         painter.text("Samples", (4, 4), colors::BLACK);
+//!     })
+//!     .with_on_mouse_down(|state: &mut State, x, y| state.points.push((x, y)))
+//! ```
+//!
 //! # Building Views
 //! Views are built using macros and builder methods.
 //! Views are created using declarative syntax.
@@ -51,6 +115,42 @@ by Jonathan Swerdlow
 //! .align(Alignment::Center);
 //! ```
 //! 
+//! ## Conditional & Repeated Items
+//! ```rust,ignore
+//! let view = VStack!(
+//!     if state.show_title => Text::new("title_id", "Title"),
+//! 
+//!     if state.logged_in => {
+//!         Text::new("welcome_id", "Welcome")
+//!     } else {
+//!         Button::new("login_id").with_text("Log In"),
+//!         Button::new("register_id").with_text("Register")
+//!     },
+//! 
+//!     // `Option<impl IntoViewComponent>` items are skipped when `None`
+//!     state.error.as_ref().map(|error| Text::new("error_id", error)),
+//! 
+//!     for item in &state.items => Text::new("", item)
+//! );
+//! ```
+//! 
+//! ## `view!` Macro
+//! The `view!` macro accepts the same builders, but validates them at compile time:
+//! duplicate ids and unknown builder methods are reported as errors.
+//! Widgets created with `_` as their id receive a generated id.
+//! ```rust,ignore
+//! let view = view! {
+//!     VStack {
+//!         Text::new("text_id", "default_text"),
+//!         HStack {
+//!             Button::new(_).with_text("Button1"),
+//!             Button::new(_).with_text("Button2"),
+//!         },
+//!     }
+//!     .alignment(Alignment::Center)
+//! };
+//! ```
+//! 
 //! ...
 
 /// Library backend for handling windowing, events, etc.
@@ -61,21 +161,65 @@ pub mod view_components;
 pub mod font;
 /// Image functionality
 pub mod images;
+/// UI scale factor for high-DPI displays
+pub mod scale;
+/// Minimal SVG rasterizer (used for `.svg` images)
+pub mod svg;
+/// Anti-aliased shape drawing (rounded rects, circles, lines, polygons)
+pub mod draw;
+/// Background tasks with results delivered to the UI thread
+pub mod tasks;
+/// Delayed & repeating callbacks
+pub mod timers;
+/// Property animation & view transitions
+pub mod animation;
 
 // ========================== Convenience Re-Exports ========================== //
 pub use view_components::{views, widgets, components};
 pub use view_components::colors;
+pub use view_components::theme::{self, Theme};
 // TODO: This may not be necessary
 pub use view_components::{
     widgets::Widget, 
     views::View,
     components::Component,
 };
-pub use view_components::views::Alignment;
+pub use view_components::views::{Alignment, VerticalAlignment};
 pub use view_components::Orientation;
 pub use backend::system::window::Window;
 pub use backend::system::state;
 
+#[doc(hidden)]
+pub use rustui_macros::view as __view;
+
+/// Declarative view builder with compile-time validation
+/// - Views are declared as `VStack { .. }`, `HStack { .. }` or `Overlay { .. }`
+/// - Widgets are declared using their usual builder methods
+/// - Duplicate widget ids and unknown builder methods are compile errors
+/// - Use `_` as a widget's id to generate a unique id
+/// ## Example
+/// ```rust,ignore
+/// let view = view! {
+///     VStack {
+///         Text::new("CounterText", "Counter")
+///             .with_color(colors::WHITE),
+///
+///         HStack {
+///             Button::new(_).with_text("+"),
+///             Button::new(_).with_text("-"),
+///         }
+///         .padding(10, 10, 5, 0),
+///     }
+///     .alignment(Alignment::Center)
+/// };
+/// ```
+#[macro_export]
+macro_rules! view {
+    ( $($tokens:tt)+ ) => {
+        $crate::__view!($crate; $($tokens)+)
+    };
+}
+
 // ========================== Macro Import Macro ========================== //
 /// Imports items required by macros
 /// - Required Traits
@@ -0,0 +1,97 @@
+/*
+
+UI scale factor
+
+Sizes given to widgets & views (builder arguments, theme spacing, font point sizes)
+are logical pixels. They are multiplied by the current scale factor as widgets are
+created & fonts are loaded, so layout & rendering happen at the display's physical
+resolution. Text is rasterized at the scaled point size instead of stretching textures.
+
+The factor is detected from the display when a window is created
+(see `Window::set_scale_factor` to override it).
+
+*/
+
+use std::cell::Cell;
+
+/// DPI of a display which needs no scaling
+const BASE_DPI: f32 = 96.0;
+
+thread_local! {
+    static FACTOR: Cell<f32> = const { Cell::new(1.0) };
+}
+
+/// The current scale factor (physical pixels per logical pixel)
+pub fn factor() -> f32 {
+    FACTOR.with(|factor| factor.get())
+}
+
+/// Set the scale factor used by widgets & views created afterwards
+/// - Non-positive factors are ignored
+pub fn set_factor(factor: f32) {
+    if factor > 0.0 && factor.is_finite() {
+        FACTOR.with(|current| current.set(factor));
+    }
+}
+
+/// Scale factor for a display with the given DPI
+/// - Rounded to the nearest quarter & never below 1
+pub fn factor_from_dpi(dpi: f32) -> f32 {
+    ((dpi / BASE_DPI) * 4.0).round().max(4.0) / 4.0
+}
+
+/// Convert logical pixels to physical pixels
+pub fn px(logical: u32) -> u32 {
+    (logical as f32 * factor()).round() as u32
+}
+
+/// Convert a logical offset to physical pixels
+pub fn px_i32(logical: i32) -> i32 {
+    (logical as f32 * factor()).round() as i32
+}
+
+/// Point size at which text is rasterized
+pub fn point_size(logical: u16) -> u16 {
+    ((logical as f32 * factor()).round() as u16).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn factor_from_dpi_rounds_to_quarters() {
+        assert_eq!(factor_from_dpi(96.0), 1.0);
+        assert_eq!(factor_from_dpi(120.0), 1.25);
+        assert_eq!(factor_from_dpi(144.0), 1.5);
+        assert_eq!(factor_from_dpi(192.0), 2.0);
+        assert_eq!(factor_from_dpi(200.0), 2.0);
+        assert_eq!(factor_from_dpi(110.0), 1.25);
+    }
+
+    #[test]
+    fn factor_from_dpi_is_at_least_one() {
+        assert_eq!(factor_from_dpi(72.0), 1.0);
+        assert_eq!(factor_from_dpi(0.0), 1.0);
+    }
+
+    #[test]
+    fn set_factor_ignores_invalid_factors() {
+        set_factor(2.0);
+        set_factor(0.0);
+        set_factor(-1.0);
+        set_factor(f32::NAN);
+        assert_eq!(factor(), 2.0);
+        assert_eq!(px(10), 20);
+        assert_eq!(px_i32(-3), -6);
+        set_factor(1.0);
+    }
+
+    #[test]
+    fn point_size_is_at_least_one() {
+        set_factor(1.5);
+        assert_eq!(point_size(20), 30);
+        assert_eq!(point_size(0), 1);
+        set_factor(1.0);
+    }
+}
@@ -0,0 +1,1337 @@
+/*
+
+Minimal SVG rendering (for icons)
+
+Supported subset:
+- Elements: svg, g, path, rect, circle, ellipse, line, polyline & polygon
+  (other elements, along with the contents of defs, clipPath, mask, etc., are skipped)
+- Attributes & `style` properties: fill, stroke, stroke-width, opacity, fill-opacity,
+  stroke-opacity, fill-rule, transform & viewBox
+- Colors: #rgb, #rrggbb, rgb(r, g, b), common names, none & currentColor (black)
+  (paints in other syntaxes, such as gradients or hsl(..), are not drawn)
+
+Paths are kept as curves until rasterization, where they are flattened for the target
+size. Fills use 4x vertical supersampling with exact horizontal coverage. Strokes are
+anti-aliased by distance from the outline (giving round joins & caps).
+
+*/
+
+extern crate sdl2;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::surface::Surface;
+
+use std::path::Path;
+
+type Point = (f32, f32);
+
+// ========================== Document ========================== //
+
+/// A parsed SVG document
+pub struct Svg {
+    /// Intrinsic size (`width` & `height`, or the view box's size)
+    width: f32,
+    height: f32,
+    /// (x, y, width, height) of the drawing area in user units
+    view_box: (f32, f32, f32, f32),
+    shapes: Vec<Shape>,
+}
+
+impl Svg {
+    pub fn load(path: &Path) -> Result<Svg, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+        Svg::parse(&source).map_err(|e| format!("Invalid SVG {}: {}", path.display(), e))
+    }
+
+    pub fn parse(source: &str) -> Result<Svg, String> {
+        let mut parser = DocumentParser {
+            svg: None,
+            styles: Vec::new(),
+            skip_depth: 0,
+        };
+
+        for tag in tokenize(source)? {
+            parser.tag(tag)?;
+        }
+
+        parser.svg.ok_or_else(|| String::from("Missing <svg> element"))
+    }
+
+    /// Intrinsic size in pixels
+    pub fn size(&self) -> (u32, u32) {
+        (self.width.round().max(1.0) as u32, self.height.round().max(1.0) as u32)
+    }
+
+    /// Render to a `width` x `height` RGBA8888 image
+    /// - The view box is scaled uniformly & centered (`preserveAspectRatio="xMidYMid meet"`)
+    pub fn rasterize(&self, width: u32, height: u32) -> Result<Surface<'static>, String> {
+        let (width, height) = (width.max(1), height.max(1));
+        let (view_x, view_y, view_width, view_height) = self.view_box;
+
+        let scale = (width as f32 / view_width).min(height as f32 / view_height);
+        let offset_x = (width as f32 - view_width * scale) / 2.0 - view_x * scale;
+        let offset_y = (height as f32 - view_height * scale) / 2.0 - view_y * scale;
+        let to_pixels = Transform { a: scale, b: 0.0, c: 0.0, d: scale, e: offset_x, f: offset_y };
+
+        let mut canvas = Raster::new(width as usize, height as usize);
+        for shape in &self.shapes {
+            canvas.draw(shape, &to_pixels, scale);
+        }
+
+        canvas.into_surface()
+    }
+}
+
+/// Geometry with its paint, in the document's user space
+struct Shape {
+    contours: Vec<Contour>,
+    fill: Option<[f32; 4]>,
+    fill_rule: FillRule,
+    stroke: Option<[f32; 4]>,
+    /// Stroke width in user space
+    stroke_width: f32,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum FillRule {
+    NonZero,
+    EvenOdd,
+}
+
+struct Contour {
+    start: Point,
+    segments: Vec<Segment>,
+    closed: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Segment {
+    Line(Point),
+    Quadratic(Point, Point),
+    Cubic(Point, Point, Point),
+}
+
+impl Contour {
+    fn transformed(&self, transform: &Transform) -> Contour {
+        let t = |point: Point| transform.apply(point);
+
+        Contour {
+            start: t(self.start),
+            segments: self.segments.iter().map(|segment| match *segment {
+                Segment::Line(end) => Segment::Line(t(end)),
+                Segment::Quadratic(control, end) => Segment::Quadratic(t(control), t(end)),
+                Segment::Cubic(control1, control2, end) => Segment::Cubic(t(control1), t(control2), t(end)),
+            }).collect(),
+            closed: self.closed,
+        }
+    }
+
+    /// Approximate the contour with line segments no longer than about 3 units
+    fn flatten(&self) -> Vec<Point> {
+        let mut points = vec![self.start];
+        let mut current = self.start;
+
+        for segment in &self.segments {
+            match *segment {
+                Segment::Line(end) => points.push(end),
+                Segment::Quadratic(control, end) => {
+                    let steps = curve_steps(&[current, control, end]);
+                    for step in 1..=steps {
+                        let t = step as f32 / steps as f32;
+                        let mt = 1.0 - t;
+                        points.push((
+                            mt * mt * current.0 + 2.0 * mt * t * control.0 + t * t * end.0,
+                            mt * mt * current.1 + 2.0 * mt * t * control.1 + t * t * end.1,
+                        ));
+                    }
+                }
+                Segment::Cubic(control1, control2, end) => {
+                    let steps = curve_steps(&[current, control1, control2, end]);
+                    for step in 1..=steps {
+                        let t = step as f32 / steps as f32;
+                        let mt = 1.0 - t;
+                        let (w0, w1, w2, w3) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+                        points.push((
+                            w0 * current.0 + w1 * control1.0 + w2 * control2.0 + w3 * end.0,
+                            w0 * current.1 + w1 * control1.1 + w2 * control2.1 + w3 * end.1,
+                        ));
+                    }
+                }
+            }
+
+            current = *points.last().unwrap();
+        }
+
+        points
+    }
+}
+
+/// Number of line segments used for a curve, from the length of its control polygon
+fn curve_steps(points: &[Point]) -> usize {
+    let length: f32 = points.windows(2).map(|pair| distance(pair[0], pair[1])).sum();
+    ((length / 3.0).ceil() as usize).clamp(2, 128)
+}
+
+fn distance(a: Point, b: Point) -> f32 {
+    ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt()
+}
+
+// ========================== Transforms ========================== //
+
+/// Affine transform: (a*x + c*y + e, b*x + d*y + f)
+#[derive(Clone, Copy)]
+struct Transform {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    e: f32,
+    f: f32,
+}
+
+impl Transform {
+    const IDENTITY: Transform = Transform { a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: 0.0, f: 0.0 };
+
+    fn apply(&self, (x, y): Point) -> Point {
+        (self.a * x + self.c * y + self.e, self.b * x + self.d * y + self.f)
+    }
+
+    /// Apply `other` first, then `self`
+    fn then(&self, other: &Transform) -> Transform {
+        Transform {
+            a: self.a * other.a + self.c * other.b,
+            b: self.b * other.a + self.d * other.b,
+            c: self.a * other.c + self.c * other.d,
+            d: self.b * other.c + self.d * other.d,
+            e: self.a * other.e + self.c * other.f + self.e,
+            f: self.b * other.e + self.d * other.f + self.f,
+        }
+    }
+
+    /// Average scale (used for stroke widths)
+    fn scale(&self) -> f32 {
+        (self.a * self.d - self.b * self.c).abs().sqrt()
+    }
+
+    /// Parses a transform list such as `translate(4 2) rotate(45)`
+    fn parse(value: &str) -> Result<Transform, String> {
+        let mut transform = Transform::IDENTITY;
+
+        for item in value.split(')') {
+            let item = item.trim().trim_start_matches(',').trim();
+            if item.is_empty() {
+                continue;
+            }
+
+            let open = item.find('(').ok_or_else(|| format!("Invalid transform `{}`", value))?;
+            let name = item[..open].trim();
+            let args = parse_numbers(&item[open + 1..])?;
+            let arg = |index: usize| args.get(index).cloned().unwrap_or(0.0);
+
+            let next = match (name, args.len()) {
+                ("matrix", 6) => Transform { a: arg(0), b: arg(1), c: arg(2), d: arg(3), e: arg(4), f: arg(5) },
+                ("translate", 1) | ("translate", 2) => Transform { e: arg(0), f: arg(1), ..Transform::IDENTITY },
+                ("scale", 1) => Transform { a: arg(0), d: arg(0), ..Transform::IDENTITY },
+                ("scale", 2) => Transform { a: arg(0), d: arg(1), ..Transform::IDENTITY },
+                ("rotate", 1) | ("rotate", 3) => {
+                    let (sin, cos) = arg(0).to_radians().sin_cos();
+                    let rotation = Transform { a: cos, b: sin, c: -sin, d: cos, ..Transform::IDENTITY };
+                    // Rotate about (cx, cy)
+                    let to_center = Transform { e: arg(1), f: arg(2), ..Transform::IDENTITY };
+                    let from_center = Transform { e: -arg(1), f: -arg(2), ..Transform::IDENTITY };
+                    to_center.then(&rotation).then(&from_center)
+                }
+                ("skewX", 1) => Transform { c: arg(0).to_radians().tan(), ..Transform::IDENTITY },
+                ("skewY", 1) => Transform { b: arg(0).to_radians().tan(), ..Transform::IDENTITY },
+                _ => return Err(format!("Unsupported transform `{}`", item)),
+            };
+
+            transform = transform.then(&next);
+        }
+
+        Ok(transform)
+    }
+}
+
+// ========================== Styles ========================== //
+
+/// Inherited presentation attributes
+#[derive(Clone, Copy)]
+struct Style {
+    transform: Transform,
+    fill: Option<[f32; 4]>,
+    fill_opacity: f32,
+    fill_rule: FillRule,
+    stroke: Option<[f32; 4]>,
+    stroke_opacity: f32,
+    stroke_width: f32,
+    /// Product of the `opacity` of the element & its ancestors
+    opacity: f32,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            transform: Transform::IDENTITY,
+            fill: Some([0.0, 0.0, 0.0, 1.0]),
+            fill_opacity: 1.0,
+            fill_rule: FillRule::NonZero,
+            stroke: None,
+            stroke_opacity: 1.0,
+            stroke_width: 1.0,
+            opacity: 1.0,
+        }
+    }
+}
+
+impl Style {
+    /// Apply an element's attributes & `style` properties (which take precedence)
+    fn inherit(&self, attributes: &[(String, String)]) -> Result<Style, String> {
+        let mut style = *self;
+
+        let css = attribute(attributes, "style").unwrap_or("");
+        let properties = css.split(';').filter_map(|declaration| {
+            let colon = declaration.find(':')?;
+            Some((declaration[..colon].trim(), declaration[colon + 1..].trim()))
+        });
+
+        let presentation = attributes.iter().map(|(name, value)| (name.as_str(), value.as_str()));
+        for (name, value) in presentation.chain(properties) {
+            match name {
+                "transform" => style.transform = self.transform.then(&Transform::parse(value)?),
+                "fill" => style.fill = parse_paint(value),
+                "stroke" => style.stroke = parse_paint(value),
+                "fill-opacity" => style.fill_opacity = parse_number(value)?,
+                "stroke-opacity" => style.stroke_opacity = parse_number(value)?,
+                "stroke-width" => style.stroke_width = parse_number(value)?,
+                "opacity" => style.opacity = self.opacity * parse_number(value)?,
+                "fill-rule" => style.fill_rule = if value == "evenodd" { FillRule::EvenOdd } else { FillRule::NonZero },
+                _ => {}
+            }
+        }
+
+        Ok(style)
+    }
+
+    fn shape(&self, contours: Vec<Contour>) -> Shape {
+        let with_opacity = |color: [f32; 4], opacity: f32| {
+            [color[0], color[1], color[2], color[3] * opacity * self.opacity]
+        };
+
+        Shape {
+            contours: contours.iter().map(|contour| contour.transformed(&self.transform)).collect(),
+            fill: self.fill.map(|color| with_opacity(color, self.fill_opacity)),
+            fill_rule: self.fill_rule,
+            stroke: self.stroke.map(|color| with_opacity(color, self.stroke_opacity)),
+            stroke_width: self.stroke_width * self.transform.scale(),
+        }
+    }
+}
+
+/// Parses a paint (`none` & unsupported paints such as gradients or `hsl(..)` are not drawn)
+fn parse_paint(value: &str) -> Option<[f32; 4]> {
+    // TODO: Support the remaining CSS color syntaxes & named colors
+    parse_color(value).ok()
+}
+
+fn parse_color(value: &str) -> Result<[f32; 4], String> {
+    let rgb = |r: u8, g: u8, b: u8| [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0];
+    let hex = |digits: &str| u8::from_str_radix(digits, 16).map_err(|_| format!("Invalid color `{}`", value));
+
+    if let Some(digits) = value.strip_prefix('#') {
+        if !digits.is_ascii() {
+            return Err(format!("Invalid color `{}`", value));
+        }
+
+        return match digits.len() {
+            3 => {
+                let channel = |index: usize| hex(&digits[index..index + 1].repeat(2));
+                Ok(rgb(channel(0)?, channel(1)?, channel(2)?))
+            }
+            6 => Ok(rgb(hex(&digits[0..2])?, hex(&digits[2..4])?, hex(&digits[4..6])?)),
+            _ => Err(format!("Invalid color `{}`", value)),
+        };
+    }
+
+    if value.starts_with("rgb(") && value.ends_with(')') {
+        let channels = parse_numbers(&value[4..value.len() - 1])?;
+        if channels.len() == 3 {
+            let channel = |value: f32| value.clamp(0.0, 255.0) as u8;
+            return Ok(rgb(channel(channels[0]), channel(channels[1]), channel(channels[2])));
+        }
+    }
+
+    match value.to_ascii_lowercase().as_str() {
+        "black" | "currentcolor" => Ok(rgb(0, 0, 0)),
+        "white" => Ok(rgb(255, 255, 255)),
+        "red" => Ok(rgb(255, 0, 0)),
+        "lime" => Ok(rgb(0, 255, 0)),
+        "green" => Ok(rgb(0, 128, 0)),
+        "blue" => Ok(rgb(0, 0, 255)),
+        "yellow" => Ok(rgb(255, 255, 0)),
+        "cyan" | "aqua" => Ok(rgb(0, 255, 255)),
+        "magenta" | "fuchsia" => Ok(rgb(255, 0, 255)),
+        "gray" | "grey" => Ok(rgb(128, 128, 128)),
+        "silver" => Ok(rgb(192, 192, 192)),
+        "orange" => Ok(rgb(255, 165, 0)),
+        "purple" => Ok(rgb(128, 0, 128)),
+        "navy" => Ok(rgb(0, 0, 128)),
+        "transparent" => Ok([0.0, 0.0, 0.0, 0.0]),
+        _ => Err(format!("Unsupported color `{}`", value)),
+    }
+}
+
+// ========================== Elements ========================== //
+
+/// Builds shapes from tags, tracking inherited styles
+struct DocumentParser {
+    svg: Option<Svg>,
+    /// Styles of the open elements
+    styles: Vec<Style>,
+    /// Depth within an element whose content is not drawn (such as `defs`)
+    skip_depth: usize,
+}
+
+impl DocumentParser {
+    fn tag(&mut self, tag: Tag) -> Result<(), String> {
+        match tag {
+            Tag::Start { name, attributes, self_closing } => {
+                if self.skip_depth > 0 || is_hidden_container(&name) {
+                    if !self_closing {
+                        self.skip_depth += 1;
+                    }
+                    return Ok(());
+                }
+
+                let parent = self.styles.last().cloned().unwrap_or_default();
+                let style = parent.inherit(&attributes)?;
+
+                self.element(&name, &attributes, &style)?;
+
+                if !self_closing {
+                    self.styles.push(style);
+                }
+            }
+            Tag::End => {
+                if self.skip_depth > 0 {
+                    self.skip_depth -= 1;
+                } else {
+                    self.styles.pop();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn element(&mut self, name: &str, attributes: &[(String, String)], style: &Style) -> Result<(), String> {
+        let number = |name: &str| -> Result<f32, String> {
+            attribute(attributes, name).map_or(Ok(0.0), parse_number)
+        };
+
+        if name == "svg" {
+            // Nested documents are drawn as groups
+            if self.svg.is_none() {
+                self.svg = Some(root_svg(attributes)?);
+            }
+            return Ok(());
+        }
+
+        let contours = match name {
+            "path" => parse_path(attribute(attributes, "d").unwrap_or(""))?,
+            "rect" => {
+                let (width, height) = (number("width")?, number("height")?);
+                // A missing radius takes the other's value
+                let (rx, ry) = match (attribute(attributes, "rx"), attribute(attributes, "ry")) {
+                    (None, None) => (0.0, 0.0),
+                    (Some(_), None) => (number("rx")?, number("rx")?),
+                    (None, Some(_)) => (number("ry")?, number("ry")?),
+                    (Some(_), Some(_)) => (number("rx")?, number("ry")?),
+                };
+                rect_contour(number("x")?, number("y")?, width, height, rx.min(width / 2.0), ry.min(height / 2.0))
+            }
+            "circle" => {
+                let radius = number("r")?;
+                ellipse_contour((number("cx")?, number("cy")?), radius, radius)
+            }
+            "ellipse" => ellipse_contour((number("cx")?, number("cy")?), number("rx")?, number("ry")?),
+            "line" => vec![Contour {
+                start: (number("x1")?, number("y1")?),
+                segments: vec![Segment::Line((number("x2")?, number("y2")?))],
+                closed: false,
+            }],
+            "polyline" | "polygon" => {
+                let values = parse_numbers(attribute(attributes, "points").unwrap_or(""))?;
+                let points: Vec<Point> = values.chunks_exact(2).map(|pair| (pair[0], pair[1])).collect();
+
+                match points.split_first() {
+                    Some((start, rest)) => vec![Contour {
+                        start: *start,
+                        segments: rest.iter().map(|point| Segment::Line(*point)).collect(),
+                        closed: name == "polygon",
+                    }],
+                    None => Vec::new(),
+                }
+            }
+            _ => return Ok(()),
+        };
+
+        if let Some(svg) = &mut self.svg {
+            if !contours.is_empty() {
+                svg.shapes.push(style.shape(contours));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Elements whose contents are only drawn when referenced (unsupported)
+fn is_hidden_container(name: &str) -> bool {
+    matches!(name, "defs" | "clipPath" | "mask" | "symbol" | "marker" | "pattern" | "linearGradient"
+        | "radialGradient" | "filter" | "style" | "title" | "desc" | "metadata" | "text")
+}
+
+fn root_svg(attributes: &[(String, String)]) -> Result<Svg, String> {
+    let view_box = match attribute(attributes, "viewBox") {
+        Some(value) => match parse_numbers(value)?.as_slice() {
+            [x, y, width, height] if *width > 0.0 && *height > 0.0 => Some((*x, *y, *width, *height)),
+            _ => return Err(format!("Invalid viewBox `{}`", value)),
+        },
+        None => None,
+    };
+
+    // Percentages are relative to an unknown viewport, so they are ignored
+    let length = |name: &str| attribute(attributes, name)
+        .filter(|value| !value.ends_with('%'))
+        .and_then(|value| parse_number(value).ok())
+        .filter(|value| *value > 0.0);
+
+    let width = length("width").or(view_box.map(|view_box| view_box.2)).unwrap_or(100.0);
+    let height = length("height").or(view_box.map(|view_box| view_box.3)).unwrap_or(100.0);
+
+    Ok(Svg {
+        width,
+        height,
+        view_box: view_box.unwrap_or((0.0, 0.0, width, height)),
+        shapes: Vec::new(),
+    })
+}
+
+fn rect_contour(x: f32, y: f32, width: f32, height: f32, rx: f32, ry: f32) -> Vec<Contour> {
+    if width <= 0.0 || height <= 0.0 {
+        return Vec::new();
+    }
+
+    if rx <= 0.0 || ry <= 0.0 {
+        return vec![Contour {
+            start: (x, y),
+            segments: vec![
+                Segment::Line((x + width, y)),
+                Segment::Line((x + width, y + height)),
+                Segment::Line((x, y + height)),
+            ],
+            closed: true,
+        }];
+    }
+
+    let mut segments = Vec::new();
+    let corner = |segments: &mut Vec<Segment>, from: Point, to: Point| {
+        segments.extend(arc_segments(from, (rx, ry), 0.0, false, true, to));
+    };
+
+    segments.push(Segment::Line((x + width - rx, y)));
+    corner(&mut segments, (x + width - rx, y), (x + width, y + ry));
+    segments.push(Segment::Line((x + width, y + height - ry)));
+    corner(&mut segments, (x + width, y + height - ry), (x + width - rx, y + height));
+    segments.push(Segment::Line((x + rx, y + height)));
+    corner(&mut segments, (x + rx, y + height), (x, y + height - ry));
+    segments.push(Segment::Line((x, y + ry)));
+    corner(&mut segments, (x, y + ry), (x + rx, y));
+
+    vec![Contour { start: (x + rx, y), segments, closed: true }]
+}
+
+fn ellipse_contour(center: Point, rx: f32, ry: f32) -> Vec<Contour> {
+    if rx <= 0.0 || ry <= 0.0 {
+        return Vec::new();
+    }
+
+    let start = (center.0 + rx, center.1);
+    let middle = (center.0 - rx, center.1);
+    let mut segments = arc_segments(start, (rx, ry), 0.0, false, true, middle);
+    segments.extend(arc_segments(middle, (rx, ry), 0.0, false, true, start));
+
+    vec![Contour { start, segments, closed: true }]
+}
+
+// ========================== Path Data ========================== //
+
+/// Parses path data (`d` attribute)
+fn parse_path(data: &str) -> Result<Vec<Contour>, String> {
+    let mut scanner = Scanner::new(data);
+    let mut contours: Vec<Contour> = Vec::new();
+
+    let mut current = (0.0, 0.0);
+    // Reflected by smooth curves (S & T)
+    let mut last_control: Option<Point> = None;
+    let mut command = None;
+
+    loop {
+        scanner.skip_separators();
+        if scanner.at_end() {
+            break;
+        }
+
+        // Commands may be omitted when repeated
+        if let Some(next) = scanner.command() {
+            command = Some(next);
+        } else if command.is_none() {
+            return Err(format!("Path data must begin with a command: `{}`", data));
+        }
+
+        let letter = command.unwrap();
+        let relative = letter.is_ascii_lowercase();
+        let offset = |point: Point| if relative { (current.0 + point.0, current.1 + point.1) } else { point };
+
+        let mut previous_control = None;
+
+        match letter.to_ascii_uppercase() {
+            'M' => {
+                let point = offset(scanner.point()?);
+                contours.push(Contour { start: point, segments: Vec::new(), closed: false });
+                current = point;
+                // Following pairs are lines
+                command = Some(if relative { 'l' } else { 'L' });
+            }
+            'Z' => {
+                if let Some(contour) = contours.last_mut() {
+                    contour.closed = true;
+                    current = contour.start;
+                    // Drawing continues from the start of the closed contour
+                    contours.push(Contour { start: current, segments: Vec::new(), closed: false });
+                }
+                command = None;
+            }
+            'L' => {
+                current = offset(scanner.point()?);
+                push_segment(&mut contours, current, Segment::Line(current));
+            }
+            'H' => {
+                let x = scanner.number()?;
+                current = (if relative { current.0 + x } else { x }, current.1);
+                push_segment(&mut contours, current, Segment::Line(current));
+            }
+            'V' => {
+                let y = scanner.number()?;
+                current = (current.0, if relative { current.1 + y } else { y });
+                push_segment(&mut contours, current, Segment::Line(current));
+            }
+            'C' | 'S' => {
+                let control1 = if letter.eq_ignore_ascii_case(&'C') {
+                    offset(scanner.point()?)
+                } else {
+                    reflect(last_control, current)
+                };
+                let control2 = offset(scanner.point()?);
+                let end = offset(scanner.point()?);
+
+                push_segment(&mut contours, current, Segment::Cubic(control1, control2, end));
+                previous_control = Some(control2);
+                current = end;
+            }
+            'Q' | 'T' => {
+                let control = if letter.eq_ignore_ascii_case(&'Q') {
+                    offset(scanner.point()?)
+                } else {
+                    reflect(last_control, current)
+                };
+                let end = offset(scanner.point()?);
+
+                push_segment(&mut contours, current, Segment::Quadratic(control, end));
+                previous_control = Some(control);
+                current = end;
+            }
+            'A' => {
+                let radii = scanner.point()?;
+                let rotation = scanner.number()?;
+                let large_arc = scanner.flag()?;
+                let sweep = scanner.flag()?;
+                let end = offset(scanner.point()?);
+
+                for segment in arc_segments(current, radii, rotation, large_arc, sweep, end) {
+                    push_segment(&mut contours, current, segment);
+                }
+                current = end;
+            }
+            _ => return Err(format!("Unknown path command `{}`", letter)),
+        }
+
+        last_control = previous_control;
+    }
+
+    contours.retain(|contour| !contour.segments.is_empty());
+    Ok(contours)
+}
+
+/// Append to the current contour (starting one at `current` if needed)
+fn push_segment(contours: &mut Vec<Contour>, current: Point, segment: Segment) {
+    if contours.last().is_none_or(|contour| contour.closed) {
+        contours.push(Contour { start: current, segments: Vec::new(), closed: false });
+    }
+
+    contours.last_mut().unwrap().segments.push(segment);
+}
+
+/// Control point of a smooth curve: the previous control point reflected about `current`
+fn reflect(control: Option<Point>, current: Point) -> Point {
+    match control {
+        Some(control) => (2.0 * current.0 - control.0, 2.0 * current.1 - control.1),
+        None => current,
+    }
+}
+
+/// Converts an elliptical arc to cubic curves (see SVG implementation notes, F.6.5)
+fn arc_segments(from: Point, radii: Point, rotation: f32, large_arc: bool, sweep: bool, to: Point) -> Vec<Segment> {
+    let (mut rx, mut ry) = (radii.0.abs(), radii.1.abs());
+    if rx == 0.0 || ry == 0.0 || from == to {
+        return vec![Segment::Line(to)];
+    }
+
+    let (sin, cos) = rotation.to_radians().sin_cos();
+
+    // Midpoint in the ellipse's rotated frame
+    let dx = (from.0 - to.0) / 2.0;
+    let dy = (from.1 - to.1) / 2.0;
+    let x1 = cos * dx + sin * dy;
+    let y1 = -sin * dx + cos * dy;
+
+    // Enlarge radii which cannot span the endpoints
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut factor = (numerator / denominator).max(0.0).sqrt();
+    if large_arc == sweep {
+        factor = -factor;
+    }
+
+    let center_x1 = factor * rx * y1 / ry;
+    let center_y1 = -factor * ry * x1 / rx;
+    let center = (
+        cos * center_x1 - sin * center_y1 + (from.0 + to.0) / 2.0,
+        sin * center_x1 + cos * center_y1 + (from.1 + to.1) / 2.0,
+    );
+
+    let angle = |ux: f32, uy: f32| uy.atan2(ux);
+    let start_angle = angle((x1 - center_x1) / rx, (y1 - center_y1) / ry);
+    let mut sweep_angle = angle((-x1 - center_x1) / rx, (-y1 - center_y1) / ry) - start_angle;
+
+    let full_turn = 2.0 * std::f32::consts::PI;
+    if sweep && sweep_angle < 0.0 {
+        sweep_angle += full_turn;
+    } else if !sweep && sweep_angle > 0.0 {
+        sweep_angle -= full_turn;
+    }
+
+    // Split into pieces of at most 90 degrees
+    let pieces = (sweep_angle.abs() / (full_turn / 4.0)).ceil().max(1.0) as usize;
+    let step = sweep_angle / pieces as f32;
+    let handle = 4.0 / 3.0 * (step / 4.0).tan();
+
+    let point_at = |theta: f32| {
+        let (sin_theta, cos_theta) = theta.sin_cos();
+        (
+            center.0 + rx * cos * cos_theta - ry * sin * sin_theta,
+            center.1 + rx * sin * cos_theta + ry * cos * sin_theta,
+        )
+    };
+    let derivative_at = |theta: f32| {
+        let (sin_theta, cos_theta) = theta.sin_cos();
+        (
+            -rx * cos * sin_theta - ry * sin * cos_theta,
+            -rx * sin * sin_theta + ry * cos * cos_theta,
+        )
+    };
+
+    (0..pieces).map(|piece| {
+        let theta1 = start_angle + step * piece as f32;
+        let theta2 = theta1 + step;
+        let (p1, p2) = (point_at(theta1), point_at(theta2));
+        let (d1, d2) = (derivative_at(theta1), derivative_at(theta2));
+        let end = if piece + 1 == pieces { to } else { p2 };
+
+        Segment::Cubic(
+            (p1.0 + handle * d1.0, p1.1 + handle * d1.1),
+            (p2.0 - handle * d2.0, p2.1 - handle * d2.1),
+            end,
+        )
+    }).collect()
+}
+
+/// Reads commands, numbers & flags from path data
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(data: &'a str) -> Self {
+        Scanner { bytes: data.as_bytes(), position: 0 }
+    }
+
+    fn at_end(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+
+    fn skip_separators(&mut self) {
+        while !self.at_end() && (self.bytes[self.position].is_ascii_whitespace() || self.bytes[self.position] == b',') {
+            self.position += 1;
+        }
+    }
+
+    fn command(&mut self) -> Option<char> {
+        let byte = *self.bytes.get(self.position)?;
+        // `e` & `E` only appear within numbers
+        if byte.is_ascii_alphabetic() && byte != b'e' && byte != b'E' {
+            self.position += 1;
+            Some(byte as char)
+        } else {
+            None
+        }
+    }
+
+    fn number(&mut self) -> Result<f32, String> {
+        self.skip_separators();
+        let start = self.position;
+        let peek = |scanner: &Self| scanner.bytes.get(scanner.position).cloned().unwrap_or(0);
+
+        if peek(self) == b'+' || peek(self) == b'-' {
+            self.position += 1;
+        }
+
+        let mut seen_point = false;
+        let mut seen_exponent = false;
+        while !self.at_end() {
+            match peek(self) {
+                b'0'..=b'9' => {}
+                // A second decimal point starts the next number (`0.5.5`)
+                b'.' if !seen_point && !seen_exponent => seen_point = true,
+                b'e' | b'E' if !seen_exponent => {
+                    seen_exponent = true;
+                    if let Some(b'+') | Some(b'-') = self.bytes.get(self.position + 1) {
+                        self.position += 1;
+                    }
+                }
+                _ => break,
+            }
+            self.position += 1;
+        }
+
+        std::str::from_utf8(&self.bytes[start..self.position])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .ok_or_else(|| String::from("Expected a number in path data"))
+    }
+
+    fn point(&mut self) -> Result<Point, String> {
+        Ok((self.number()?, self.number()?))
+    }
+
+    /// Arc flags may be written without separators (`a1 1 0 00 1 1`)
+    fn flag(&mut self) -> Result<bool, String> {
+        self.skip_separators();
+        match self.bytes.get(self.position) {
+            Some(b'0') => { self.position += 1; Ok(false) }
+            Some(b'1') => { self.position += 1; Ok(true) }
+            _ => Err(String::from("Expected an arc flag in path data")),
+        }
+    }
+}
+
+fn parse_number(value: &str) -> Result<f32, String> {
+    value.trim().trim_end_matches("px").parse().map_err(|_| format!("Invalid number `{}`", value))
+}
+
+fn parse_numbers(value: &str) -> Result<Vec<f32>, String> {
+    let mut scanner = Scanner::new(value);
+    let mut numbers = Vec::new();
+
+    loop {
+        scanner.skip_separators();
+        if scanner.at_end() {
+            return Ok(numbers);
+        }
+        numbers.push(scanner.number()?);
+    }
+}
+
+// ========================== XML ========================== //
+
+enum Tag {
+    Start { name: String, attributes: Vec<(String, String)>, self_closing: bool },
+    End,
+}
+
+fn attribute<'a>(attributes: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+}
+
+/// Splits a document into element tags (text, comments & declarations are skipped)
+fn tokenize(source: &str) -> Result<Vec<Tag>, String> {
+    let mut tags = Vec::new();
+    let mut rest = source;
+
+    while let Some(open) = rest.find('<') {
+        rest = &rest[open..];
+
+        let skip_until = |rest: &str, end: &str| rest.find(end).map(|index| index + end.len());
+        let skipped = if rest.starts_with("<!--") {
+            skip_until(rest, "-->")
+        } else if rest.starts_with("<![CDATA[") {
+            skip_until(rest, "]]>")
+        } else if rest.starts_with("<?") || rest.starts_with("<!") {
+            skip_until(rest, ">")
+        } else {
+            None
+        };
+        if let Some(length) = skipped {
+            rest = &rest[length..];
+            continue;
+        }
+
+        let close = find_tag_end(rest).ok_or("Unterminated tag")?;
+        let content = &rest[1..close];
+        rest = &rest[close + 1..];
+
+        if content.starts_with('/') {
+            tags.push(Tag::End);
+            continue;
+        }
+
+        let self_closing = content.ends_with('/');
+        let content = content.trim_end_matches('/');
+        let name_end = content.find(|c: char| c.is_whitespace()).unwrap_or(content.len());
+
+        tags.push(Tag::Start {
+            name: content[..name_end].to_owned(),
+            attributes: parse_attributes(&content[name_end..])?,
+            self_closing,
+        });
+    }
+
+    Ok(tags)
+}
+
+/// Index of the `>` closing a tag (ignoring any within quoted values)
+fn find_tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+
+    for (index, character) in tag.char_indices() {
+        match (quote, character) {
+            (None, '"') | (None, '\'') => quote = Some(character),
+            (Some(open), _) if open == character => quote = None,
+            (None, '>') => return Some(index),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn parse_attributes(mut text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut attributes = Vec::new();
+
+    loop {
+        text = text.trim_start();
+        if text.is_empty() {
+            return Ok(attributes);
+        }
+
+        let equals = text.find('=').ok_or_else(|| format!("Invalid attribute `{}`", text))?;
+        let name = text[..equals].trim();
+        text = text[equals + 1..].trim_start();
+
+        let quote = text.chars().next().filter(|c| *c == '"' || *c == '\'')
+            .ok_or_else(|| format!("Unquoted value for attribute `{}`", name))?;
+        let end = text[1..].find(quote).ok_or_else(|| format!("Unterminated value for attribute `{}`", name))? + 1;
+
+        // Namespaced attributes (such as `xlink:href`) are kept with their prefix
+        attributes.push((name.to_owned(), decode_entities(&text[1..end])));
+        text = &text[end + 1..];
+    }
+}
+
+fn decode_entities(value: &str) -> String {
+    value.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
+
+// ========================== Rasterization ========================== //
+
+/// Vertical samples per pixel row when filling
+const FILL_SAMPLES: usize = 4;
+
+/// Premultiplied RGBA pixels
+struct Raster {
+    width: usize,
+    height: usize,
+    pixels: Vec<[f32; 4]>,
+    /// Coverage of the shape being drawn
+    coverage: Vec<f32>,
+}
+
+impl Raster {
+    fn new(width: usize, height: usize) -> Self {
+        Raster {
+            width,
+            height,
+            pixels: vec![[0.0; 4]; width * height],
+            coverage: vec![0.0; width * height],
+        }
+    }
+
+    fn draw(&mut self, shape: &Shape, to_pixels: &Transform, scale: f32) {
+        let outlines: Vec<(Vec<Point>, bool)> = shape.contours.iter()
+            .map(|contour| (contour.transformed(to_pixels).flatten(), contour.closed))
+            .collect();
+
+        if let Some(color) = shape.fill {
+            self.fill(&outlines, shape.fill_rule);
+            self.composite(color);
+        }
+
+        if let Some(color) = shape.stroke {
+            self.stroke(&outlines, shape.stroke_width * scale);
+            self.composite(color);
+        }
+    }
+
+    /// Accumulate the coverage of the (implicitly closed) outlines
+    fn fill(&mut self, outlines: &[(Vec<Point>, bool)], fill_rule: FillRule) {
+        // (x0, y0, x1, y1, winding direction)
+        let mut edges = Vec::new();
+        for (points, _) in outlines {
+            for index in 0..points.len() {
+                let (a, b) = (points[index], points[(index + 1) % points.len()]);
+                if a.1 != b.1 {
+                    edges.push((a.0, a.1, b.0, b.1, if b.1 > a.1 { 1 } else { -1 }));
+                }
+            }
+        }
+
+        let mut crossings: Vec<(f32, i32)> = Vec::new();
+
+        for row in 0..self.height {
+            for sample in 0..FILL_SAMPLES {
+                let y = row as f32 + (sample as f32 + 0.5) / FILL_SAMPLES as f32;
+
+                crossings.clear();
+                for &(x0, y0, x1, y1, direction) in &edges {
+                    if (y0 <= y && y < y1) || (y1 <= y && y < y0) {
+                        crossings.push((x0 + (y - y0) / (y1 - y0) * (x1 - x0), direction));
+                    }
+                }
+                crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+                let mut winding = 0;
+                for pair in crossings.windows(2) {
+                    winding += pair[0].1;
+                    let inside = match fill_rule {
+                        FillRule::NonZero => winding != 0,
+                        FillRule::EvenOdd => winding % 2 != 0,
+                    };
+
+                    if inside {
+                        self.cover_span(row, pair[0].0, pair[1].0, 1.0 / FILL_SAMPLES as f32);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Add `amount` of coverage between `start` & `end` (exact at partially covered pixels)
+    fn cover_span(&mut self, row: usize, start: f32, end: f32, amount: f32) {
+        let start = start.max(0.0);
+        let end = end.min(self.width as f32);
+        if start >= end {
+            return;
+        }
+
+        let offset = row * self.width;
+        for x in (start.floor() as usize)..(end.ceil() as usize).min(self.width) {
+            let overlap = end.min(x as f32 + 1.0) - start.max(x as f32);
+            self.coverage[offset + x] += overlap * amount;
+        }
+    }
+
+    /// Accumulate the coverage of lines of `width` along the outlines
+    fn stroke(&mut self, outlines: &[(Vec<Point>, bool)], width: f32) {
+        let half_width = width / 2.0;
+        let margin = half_width + 1.0;
+
+        for (points, closed) in outlines {
+            let mut segments: Vec<(Point, Point)> = points.windows(2).map(|pair| (pair[0], pair[1])).collect();
+            if *closed && points.len() > 2 {
+                segments.push((points[points.len() - 1], points[0]));
+            }
+
+            for (a, b) in segments {
+                let left = ((a.0.min(b.0) - margin).floor().max(0.0)) as usize;
+                let right = ((a.0.max(b.0) + margin).ceil().max(0.0) as usize).min(self.width);
+                let top = ((a.1.min(b.1) - margin).floor().max(0.0)) as usize;
+                let bottom = ((a.1.max(b.1) + margin).ceil().max(0.0) as usize).min(self.height);
+
+                for y in top..bottom {
+                    for x in left..right {
+                        let distance = segment_distance(a, b, (x as f32 + 0.5, y as f32 + 0.5));
+                        // Thin lines are drawn fainter rather than thinner
+                        let coverage = (half_width.max(0.5) - distance + 0.5).clamp(0.0, 1.0) * width.min(1.0);
+
+                        let pixel = &mut self.coverage[y * self.width + x];
+                        *pixel = pixel.max(coverage);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Blend `color` (straight alpha) by the accumulated coverage & reset the coverage
+    fn composite(&mut self, color: [f32; 4]) {
+        for (pixel, coverage) in self.pixels.iter_mut().zip(self.coverage.iter_mut()) {
+            if *coverage > 0.0 {
+                let alpha = color[3] * coverage.min(1.0);
+                for channel in 0..3 {
+                    pixel[channel] = color[channel] * alpha + pixel[channel] * (1.0 - alpha);
+                }
+                pixel[3] = alpha + pixel[3] * (1.0 - alpha);
+
+                *coverage = 0.0;
+            }
+        }
+    }
+
+    /// Straight-alpha RGBA8888 image
+    fn into_surface(self) -> Result<Surface<'static>, String> {
+        let mut surface = Surface::new(self.width as u32, self.height as u32, PixelFormatEnum::RGBA8888)?;
+        let pitch = surface.pitch() as usize;
+        let (width, pixels) = (self.width, self.pixels);
+
+        surface.with_lock_mut(|bytes| {
+            for (index, pixel) in pixels.iter().enumerate() {
+                let alpha = pixel[3];
+                let channel = |value: f32| {
+                    let straight = if alpha > 0.0 { value / alpha } else { 0.0 };
+                    (straight.clamp(0.0, 1.0) * 255.0).round() as u8
+                };
+
+                // Pixels are packed as 0xRRGGBBAA in native byte order
+                let packed = u32::from_be_bytes([
+                    channel(pixel[0]),
+                    channel(pixel[1]),
+                    channel(pixel[2]),
+                    (alpha.clamp(0.0, 1.0) * 255.0).round() as u8,
+                ]);
+
+                let offset = (index / width) * pitch + (index % width) * 4;
+                bytes[offset..offset + 4].copy_from_slice(&packed.to_ne_bytes());
+            }
+        });
+
+        Ok(surface)
+    }
+}
+
+/// Distance from `point` to the line segment `a`-`b`
+fn segment_distance(a: Point, b: Point, point: Point) -> f32 {
+    let (abx, aby) = (b.0 - a.0, b.1 - a.1);
+    let (apx, apy) = (point.0 - a.0, point.1 - a.1);
+
+    let length_squared = abx * abx + aby * aby;
+    let t = if length_squared > 0.0 {
+        ((apx * abx + apy * aby) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    ((apx - abx * t).powi(2) + (apy - aby * t).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: Point, expected: Point) {
+        assert!((actual.0 - expected.0).abs() < 1e-4 && (actual.1 - expected.1).abs() < 1e-4,
+                "{:?} != {:?}", actual, expected);
+    }
+
+    fn end_points(contour: &Contour) -> Vec<Point> {
+        contour.segments.iter().map(|segment| match *segment {
+            Segment::Line(end) | Segment::Quadratic(_, end) | Segment::Cubic(_, _, end) => end,
+        }).collect()
+    }
+
+    // ====== Path Data ====== //
+
+    #[test]
+    fn parse_path_absolute_and_relative_commands() {
+        let contours = parse_path("M1 2 L5 2 l0 3 h-4 z").unwrap();
+
+        assert_eq!(contours.len(), 1);
+        assert!(contours[0].closed);
+        assert_eq!(contours[0].start, (1.0, 2.0));
+        assert_eq!(end_points(&contours[0]), vec![(5.0, 2.0), (5.0, 5.0), (1.0, 5.0)]);
+    }
+
+    #[test]
+    fn parse_path_repeated_commands_and_compact_numbers() {
+        // Pairs after a move are lines, and a second decimal point starts the next number
+        let contours = parse_path("m1,1 2 0V-1.5.5").unwrap();
+
+        assert_eq!(contours.len(), 1);
+        assert!(!contours[0].closed);
+        assert_eq!(end_points(&contours[0]), vec![(3.0, 1.0), (3.0, -1.5), (3.0, 0.5)]);
+    }
+
+    #[test]
+    fn parse_path_smooth_curves_reflect_control_points() {
+        let contours = parse_path("M0 0 C0 10 10 10 10 0 S20 -10 20 0").unwrap();
+
+        match contours[0].segments[1] {
+            Segment::Cubic(control1, control2, end) => {
+                assert_eq!(control1, (10.0, -10.0));
+                assert_eq!(control2, (20.0, -10.0));
+                assert_eq!(end, (20.0, 0.0));
+            }
+            _ => panic!("Expected a cubic curve"),
+        }
+    }
+
+    #[test]
+    fn parse_path_arcs_become_cubic_curves() {
+        let contours = parse_path("M0 0 A5 5 0 0 1 10 0").unwrap();
+        let segments = &contours[0].segments;
+
+        // A half circle is split into two quarters
+        assert_eq!(segments.len(), 2);
+        assert!(segments.iter().all(|segment| matches!(segment, Segment::Cubic(..))));
+        assert_near(end_points(&contours[0])[0], (5.0, -5.0));
+        assert_near(end_points(&contours[0])[1], (10.0, 0.0));
+    }
+
+    #[test]
+    fn parse_path_errors() {
+        assert!(parse_path("1 1").is_err());
+        assert!(parse_path("M0 0 X1 1").is_err());
+        assert!(parse_path("M0 0 L1").is_err());
+    }
+
+    // ====== Transforms ====== //
+
+    #[test]
+    fn transform_lists_apply_last_first() {
+        let transform = Transform::parse("translate(4 2) scale(2)").unwrap();
+        assert_near(transform.apply((1.0, 1.0)), (6.0, 4.0));
+
+        let transform = Transform::parse("translate(1,2), scale(3, 1)").unwrap();
+        assert_near(transform.apply((1.0, 1.0)), (4.0, 3.0));
+    }
+
+    #[test]
+    fn transform_functions() {
+        let apply = |value: &str, point: Point| Transform::parse(value).unwrap().apply(point);
+
+        assert_near(apply("", (1.0, 2.0)), (1.0, 2.0));
+        assert_near(apply("matrix(1 0 0 1 3 4)", (1.0, 1.0)), (4.0, 5.0));
+        assert_near(apply("translate(5)", (1.0, 1.0)), (6.0, 1.0));
+        assert_near(apply("rotate(90)", (1.0, 0.0)), (0.0, 1.0));
+        assert_near(apply("rotate(90 1 1)", (2.0, 1.0)), (1.0, 2.0));
+        assert_near(apply("skewX(45)", (0.0, 1.0)), (1.0, 1.0));
+        assert_near(apply("skewY(45)", (1.0, 0.0)), (1.0, 1.0));
+    }
+
+    #[test]
+    fn transform_errors() {
+        assert!(Transform::parse("spin(3)").is_err());
+        assert!(Transform::parse("translate 3").is_err());
+        assert!(Transform::parse("matrix(1 2)").is_err());
+    }
+
+    // ====== Colors ====== //
+
+    #[test]
+    fn parse_color_syntaxes() {
+        assert_eq!(parse_color("#f80").unwrap(), [1.0, 136.0 / 255.0, 0.0, 1.0]);
+        assert_eq!(parse_color("#FF8000").unwrap(), [1.0, 128.0 / 255.0, 0.0, 1.0]);
+        assert_eq!(parse_color("rgb(255, 0, 300)").unwrap(), [1.0, 0.0, 1.0, 1.0]);
+        assert_eq!(parse_color("Red").unwrap(), [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(parse_color("currentColor").unwrap(), [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(parse_color("transparent").unwrap(), [0.0; 4]);
+    }
+
+    #[test]
+    fn parse_color_errors() {
+        assert!(parse_color("#ff").is_err());
+        assert!(parse_color("#ggg").is_err());
+        // Non-ASCII digits must not be sliced mid-character
+        assert!(parse_color("#aé1").is_err());
+        assert!(parse_color("#aé123").is_err());
+        assert!(parse_color("#ééé").is_err());
+        assert!(parse_color("hsl(0, 100%, 50%)").is_err());
+    }
+
+    #[test]
+    fn unsupported_paints_are_not_drawn() {
+        assert_eq!(parse_paint("none"), None);
+        assert_eq!(parse_paint("url(#gradient)"), None);
+        assert_eq!(parse_paint("darkgray"), None);
+        assert_eq!(parse_paint("blue"), Some([0.0, 0.0, 1.0, 1.0]));
+
+        let svg = Svg::parse(r#"<svg width="4" height="4"><rect width="4" height="4" fill="hsl(0, 100%, 50%)"/></svg>"#);
+        assert!(svg.is_ok());
+    }
+
+    // ====== XML ====== //
+
+    #[test]
+    fn tokenize_skips_declarations_and_comments() {
+        let tags = tokenize(concat!(
+            r#"<?xml version="1.0"?><!DOCTYPE svg><!-- <g> -->"#,
+            r#"<svg width="10" viewBox='0 0 1 1'><path d="M0 0" title="a > b &amp; c"/><g></g></svg>"#,
+        )).unwrap();
+
+        let names: Vec<Option<&str>> = tags.iter().map(|tag| match tag {
+            Tag::Start { name, .. } => Some(name.as_str()),
+            Tag::End => None,
+        }).collect();
+        assert_eq!(names, vec![Some("svg"), Some("path"), Some("g"), None, None]);
+
+        match &tags[0] {
+            Tag::Start { attributes, self_closing, .. } => {
+                assert!(!self_closing);
+                assert_eq!(attribute(attributes, "width"), Some("10"));
+                assert_eq!(attribute(attributes, "viewBox"), Some("0 0 1 1"));
+            }
+            Tag::End => unreachable!(),
+        }
+        match &tags[1] {
+            Tag::Start { attributes, self_closing, .. } => {
+                assert!(self_closing);
+                assert_eq!(attribute(attributes, "title"), Some("a > b & c"));
+            }
+            Tag::End => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn tokenize_errors() {
+        assert!(tokenize("<svg width=\"10\"").is_err());
+        assert!(tokenize("<svg width=10>").is_err());
+        assert!(tokenize("<svg width=\"10>").is_err());
+    }
+}
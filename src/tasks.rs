@@ -0,0 +1,396 @@
+/*
+
+Background tasks
+
+Work spawned from a callback runs on its own thread. Progress reports & results
+are sent back over a channel and applied to user state on the UI thread at the
+start of the next frame, after which the view is regenerated as usual.
+
+Results are only delivered to the state type a task was spawned with
+(events of other state types wait for a `deliver` call with that type).
+
+TODO: Thread pool rather than one thread per task
+
+*/
+
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+
+// ========================== Shared Task Status ========================== //
+
+/// Status shared between a task's thread, its handle & the UI thread
+struct TaskStatus {
+    /// Progress (`f32` bits) within `0.0..=1.0`
+    progress: AtomicU32,
+    cancelled: AtomicBool,
+    finished: AtomicBool,
+}
+
+// ========================== TaskContext Struct ========================== //
+
+/// Passed to the work function of a task for reporting progress & checking for cancellation
+pub struct TaskContext {
+    id: u64,
+    status: Arc<TaskStatus>,
+    sender: Sender<TaskEvent>,
+}
+
+impl TaskContext {
+    /// Report progress within `0.0..=1.0`
+    /// - Delivered to the task's `on_progress` callback on the UI thread
+    pub fn set_progress(&self, progress: f32) {
+        let progress = progress.clamp(0.0, 1.0);
+        self.status.progress.store(progress.to_bits(), Ordering::Relaxed);
+        // The UI thread may have exited
+        let _ = self.sender.send(TaskEvent::Progress(self.id, progress));
+    }
+
+    /// Whether the task was cancelled. Long-running work should check this periodically and return early.
+    pub fn is_cancelled(&self) -> bool {
+        self.status.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+// ========================== TaskHandle Struct ========================== //
+
+/// Handle to a spawned task, usable for progress indicators & cancellation
+/// - May be stored in user state (handles are equal if they refer to the same task)
+#[derive(Clone)]
+pub struct TaskHandle {
+    id: u64,
+    status: Arc<TaskStatus>,
+}
+
+impl TaskHandle {
+    /// Most recently reported progress within `0.0..=1.0`
+    pub fn progress(&self) -> f32 {
+        f32::from_bits(self.status.progress.load(Ordering::Relaxed))
+    }
+
+    /// Request cancellation. The task's `on_complete` callback will not be called.
+    pub fn cancel(&self) {
+        self.status.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.status.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Whether the task's result (if any) has been delivered
+    pub fn is_finished(&self) -> bool {
+        self.status.finished.load(Ordering::Relaxed)
+    }
+}
+
+impl PartialEq for TaskHandle {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+// ========================== Task Queue ========================== //
+
+enum TaskEvent {
+    Progress(u64, f32),
+    Complete(u64, Box<dyn Any + Send>),
+    /// The work function panicked
+    Failed(u64),
+}
+
+impl TaskEvent {
+    fn task_id(&self) -> u64 {
+        match self {
+            TaskEvent::Progress(id, _) | TaskEvent::Complete(id, _) | TaskEvent::Failed(id) => *id,
+        }
+    }
+}
+
+/// Progress callback with type-erased state
+type AnyProgressFn = Box<dyn Fn(&mut dyn Any, f32)>;
+/// Completion callback with type-erased state & result
+type AnyCompleteFn = Box<dyn FnOnce(&mut dyn Any, Box<dyn Any + Send>)>;
+
+/// UI-thread callbacks of a running task. State is type-erased so the queue can be thread-local.
+struct PendingTask {
+    /// `TypeId` of the state the callbacks expect
+    state_type: TypeId,
+    status: Arc<TaskStatus>,
+    on_progress: Option<AnyProgressFn>,
+    on_complete: Option<AnyCompleteFn>,
+}
+
+struct TaskQueue {
+    next_id: u64,
+    sender: Sender<TaskEvent>,
+    receiver: Receiver<TaskEvent>,
+    pending: HashMap<u64, PendingTask>,
+    /// Received events of tasks spawned with another state type than the last `deliver` call
+    waiting: Vec<TaskEvent>,
+}
+
+thread_local! {
+    /// Tasks spawned from this (UI) thread
+    static TASKS: RefCell<TaskQueue> = {
+        let (sender, receiver) = mpsc::channel();
+        RefCell::new(TaskQueue {
+            next_id: 0,
+            sender,
+            receiver,
+            pending: HashMap::new(),
+            waiting: Vec::new(),
+        })
+    };
+}
+
+// ========================== Task Builder ========================== //
+
+/// Work to be run on a background thread
+/// ## Example
+/// ```rust,ignore
+/// Button::new("load_id")
+///     .with_on_click(|state: &mut State| {
+///         let handle = Task::new(|context| std::fs::read_to_string("data.txt"))
+///             .with_on_complete(|state: &mut State, contents| state.contents = contents.ok())
+///             .spawn();
+///         state.loading = Some(handle);
+///     })
+/// ```
+pub struct Task<T, R> {
+    work: Box<dyn FnOnce(&TaskContext) -> R + Send>,
+    on_progress: Option<ProgressFn<T>>,
+    on_complete: Option<CompleteFn<T, R>>,
+}
+
+type ProgressFn<T> = Box<dyn Fn(&mut T, f32)>;
+type CompleteFn<T, R> = Box<dyn FnOnce(&mut T, R)>;
+
+impl<T: 'static, R: Send + 'static> Task<T, R> {
+    pub fn new<W: 'static + Send + FnOnce(&TaskContext) -> R>(work: W) -> Self {
+        Task {
+            work: Box::new(work),
+            on_progress: None,
+            on_complete: None,
+        }
+    }
+
+    /// Called on the UI thread when the task reports progress
+    pub fn with_on_progress<F: 'static + Fn(&mut T, f32)>
+    (mut self, callback: F) -> Self {
+        self.on_progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Called on the UI thread with the task's result, unless the task was cancelled
+    pub fn with_on_complete<F: 'static + FnOnce(&mut T, R)>
+    (mut self, callback: F) -> Self {
+        self.on_complete = Some(Box::new(callback));
+        self
+    }
+
+    /// Start the task on a new thread
+    pub fn spawn(self) -> TaskHandle {
+        let status = Arc::new(TaskStatus {
+            progress: AtomicU32::new(0f32.to_bits()),
+            cancelled: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+        });
+
+        let on_progress = self.on_progress.map(|callback| {
+            Box::new(move |state: &mut dyn Any, progress: f32| {
+                callback(state.downcast_mut::<T>().expect("Task state type mismatch"), progress);
+            }) as AnyProgressFn
+        });
+
+        let on_complete = self.on_complete.map(|callback| {
+            Box::new(move |state: &mut dyn Any, result: Box<dyn Any + Send>| {
+                let result = *result.downcast::<R>().expect("Task result type mismatch");
+                callback(state.downcast_mut::<T>().expect("Task state type mismatch"), result);
+            }) as AnyCompleteFn
+        });
+
+        let (id, sender) = TASKS.with(|tasks| {
+            let mut tasks = tasks.borrow_mut();
+            let id = tasks.next_id;
+            tasks.next_id += 1;
+
+            tasks.pending.insert(id, PendingTask {
+                state_type: TypeId::of::<T>(),
+                status: status.clone(),
+                on_progress,
+                on_complete,
+            });
+
+            (id, tasks.sender.clone())
+        });
+
+        let context = TaskContext {
+            id,
+            status: status.clone(),
+            sender,
+        };
+        let work = self.work;
+
+        std::thread::spawn(move || {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| work(&context)));
+
+            let event = match result {
+                Ok(result) => TaskEvent::Complete(id, Box::new(result)),
+                Err(_) => TaskEvent::Failed(id),
+            };
+            let _ = context.sender.send(event);
+        });
+
+        TaskHandle {
+            id,
+            status,
+        }
+    }
+}
+
+// ========================== Delivery ========================== //
+
+/// Applies progress reports & results of finished tasks to user state
+/// - Called by the window loop once per frame
+/// - Only tasks spawned with the state type `T` are delivered. Others wait for a call with their type.
+pub fn deliver<T: 'static>(state: &mut T) {
+    let state_type = TypeId::of::<T>();
+
+    // Collect first so callbacks may spawn further tasks
+    let events: Vec<TaskEvent> = TASKS.with(|tasks| {
+        let mut tasks = tasks.borrow_mut();
+        let received: Vec<TaskEvent> = tasks.receiver.try_iter().collect();
+        let events = std::mem::take(&mut tasks.waiting).into_iter().chain(received);
+
+        // Events of finished (or unknown) tasks are ignored below
+        let (events, waiting) = events.partition(|event| {
+            tasks.pending.get(&event.task_id()).is_none_or(|task| task.state_type == state_type)
+        });
+        tasks.waiting = waiting;
+
+        events
+    });
+
+    for event in events {
+        match event {
+            TaskEvent::Progress(id, progress) => {
+                let callback = TASKS.with(|tasks| {
+                    tasks.borrow_mut().pending.get_mut(&id).and_then(|task| task.on_progress.take())
+                });
+
+                if let Some(callback) = callback {
+                    callback(state, progress);
+
+                    TASKS.with(|tasks| {
+                        if let Some(task) = tasks.borrow_mut().pending.get_mut(&id) {
+                            task.on_progress = Some(callback);
+                        }
+                    });
+                }
+            }
+
+            TaskEvent::Complete(id, result) => {
+                if let Some(task) = TASKS.with(|tasks| tasks.borrow_mut().pending.remove(&id)) {
+                    task.status.finished.store(true, Ordering::Relaxed);
+
+                    if !task.status.cancelled.load(Ordering::Relaxed) {
+                        if let Some(callback) = task.on_complete {
+                            callback(state, result);
+                        }
+                    }
+                }
+            }
+
+            TaskEvent::Failed(id) => {
+                if let Some(task) = TASKS.with(|tasks| tasks.borrow_mut().pending.remove(&id)) {
+                    task.status.finished.store(true, Ordering::Relaxed);
+                    eprintln!("Warning: background task {} panicked", id);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    /// Delivers events to `state` until `handle` finishes
+    fn wait_for<T: 'static>(state: &mut T, handle: &TaskHandle) {
+        let start = Instant::now();
+        while !handle.is_finished() {
+            assert!(start.elapsed() < Duration::from_secs(5), "Task did not finish");
+            deliver(state);
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn delivers_progress_and_results() {
+        let mut state: Vec<String> = Vec::new();
+        let handle = Task::new(|context| {
+            context.set_progress(0.5);
+            context.set_progress(2.0);
+            42
+        })
+            .with_on_progress(|state: &mut Vec<String>, progress| state.push(format!("progress {}", progress)))
+            .with_on_complete(|state: &mut Vec<String>, result| state.push(format!("result {}", result)))
+            .spawn();
+
+        wait_for(&mut state, &handle);
+        assert_eq!(state, vec!["progress 0.5", "progress 1", "result 42"]);
+        assert_eq!(handle.progress(), 1.0);
+    }
+
+    #[test]
+    fn cancelled_tasks_do_not_complete() {
+        let mut state = 0;
+        let (sender, receiver) = mpsc::channel::<()>();
+
+        let handle = Task::new(move |_| receiver.recv().unwrap())
+            .with_on_complete(|state: &mut i32, _| *state = 1)
+            .spawn();
+        handle.cancel();
+        sender.send(()).unwrap();
+
+        wait_for(&mut state, &handle);
+        assert!(handle.is_cancelled());
+        assert_eq!(state, 0);
+    }
+
+    #[test]
+    fn panicking_tasks_finish() {
+        let mut state = 0;
+        let handle = Task::new(|_| -> i32 { panic!("Task failure") })
+            .with_on_complete(|state: &mut i32, result| *state = result)
+            .spawn();
+
+        wait_for(&mut state, &handle);
+        assert_eq!(state, 0);
+    }
+
+    #[test]
+    fn results_wait_for_their_state_type() {
+        let mut text = String::new();
+        let handle = Task::new(|_| "done")
+            .with_on_complete(|state: &mut String, result| state.push_str(result))
+            .spawn();
+
+        // Another state type receives (but does not apply) the result
+        let start = Instant::now();
+        while TASKS.with(|tasks| tasks.borrow().waiting.is_empty()) {
+            assert!(start.elapsed() < Duration::from_secs(5), "Task did not finish");
+            deliver(&mut 0u32);
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(!handle.is_finished());
+
+        deliver(&mut text);
+        assert!(handle.is_finished());
+        assert_eq!(text, "done");
+    }
+}
@@ -0,0 +1,249 @@
+/*
+
+Timers & scheduled callbacks
+
+Timers are checked once per frame by the window loop, so callbacks
+run on the UI thread within one frame (~16ms) of their deadline.
+
+Like background tasks, timers are kept per (UI) thread, so they may be
+scheduled & cancelled from anywhere on that thread: widget callbacks,
+other timer callbacks or before the window starts (see `Window::after`).
+Callbacks only run for the state type they were scheduled with.
+
+*/
+
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+/// Shortest interval of repeating timers (one frame)
+/// - Shorter intervals would keep the window loop from sleeping
+pub const MIN_INTERVAL: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+// ========================== TimerHandle Struct ========================== //
+
+/// Handle to a scheduled callback, used for cancellation
+/// - May be stored in user state (handles are equal if they refer to the same timer)
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct TimerHandle {
+    id: u64,
+}
+
+impl TimerHandle {
+    /// Stop the timer. Its callback will not be called again.
+    pub fn cancel(&self) {
+        TIMERS.with(|timers| {
+            timers.borrow_mut().timers.retain(|timer| timer.id != self.id);
+        });
+    }
+
+    /// Whether the timer was cancelled or has finished
+    pub fn is_cancelled(&self) -> bool {
+        TIMERS.with(|timers| !timers.borrow().timers.iter().any(|timer| timer.id == self.id))
+    }
+}
+
+// ========================== Timer Queue ========================== //
+
+/// Timer callback with type-erased state
+type AnyTimerFn = Box<dyn FnMut(&mut dyn Any)>;
+
+struct Timer {
+    id: u64,
+    /// `TypeId` of the state the callback expects
+    state_type: TypeId,
+    deadline: Instant,
+    /// `Some` for repeating timers
+    interval: Option<Duration>,
+    /// Taken while the callback runs
+    callback: Option<AnyTimerFn>,
+}
+
+#[derive(Default)]
+struct TimerQueue {
+    next_id: u64,
+    timers: Vec<Timer>,
+}
+
+thread_local! {
+    /// Timers scheduled from this (UI) thread
+    static TIMERS: RefCell<TimerQueue> = RefCell::new(TimerQueue::default());
+}
+
+// ========================== Scheduling ========================== //
+
+/// Call `callback` once after `delay`
+pub fn after<T: 'static, F: 'static + FnMut(&mut T)>(delay: Duration, callback: F) -> TimerHandle {
+    schedule(delay, None, callback)
+}
+
+/// Call `callback` every `interval`, starting one `interval` from now
+/// - Intervals are at least `MIN_INTERVAL`
+pub fn every<T: 'static, F: 'static + FnMut(&mut T)>(interval: Duration, callback: F) -> TimerHandle {
+    let interval = interval.max(MIN_INTERVAL);
+    schedule(interval, Some(interval), callback)
+}
+
+fn schedule<T: 'static, F: 'static + FnMut(&mut T)>(delay: Duration, interval: Option<Duration>, mut callback: F) -> TimerHandle {
+    let callback = Box::new(move |state: &mut dyn Any| {
+        callback(state.downcast_mut::<T>().expect("Timer state type mismatch"));
+    }) as AnyTimerFn;
+
+    TIMERS.with(|timers| {
+        let mut timers = timers.borrow_mut();
+        let id = timers.next_id;
+        timers.next_id += 1;
+
+        timers.timers.push(Timer {
+            id,
+            state_type: TypeId::of::<T>(),
+            deadline: Instant::now() + delay,
+            interval,
+            callback: Some(callback),
+        });
+
+        TimerHandle { id }
+    })
+}
+
+// ========================== Running ========================== //
+
+/// Run the callbacks of all due timers scheduled with the state type `T`
+/// - Called by the window loop once per frame
+/// - Repeating timers which fell behind run once, then are rescheduled from now
+/// - Timers scheduled by callbacks are first checked on the next call
+pub fn run_due<T: 'static>(state: &mut T) {
+    let now = Instant::now();
+    let state_type = TypeId::of::<T>();
+
+    let due: Vec<u64> = TIMERS.with(|timers| {
+        timers.borrow().timers.iter()
+            .filter(|timer| timer.state_type == state_type && timer.deadline <= now)
+            .map(|timer| timer.id)
+            .collect()
+    });
+
+    for id in due {
+        // Callbacks run without borrowing the queue, so they may schedule & cancel timers
+        let callback = TIMERS.with(|timers| {
+            let mut timers = timers.borrow_mut();
+            let index = timers.timers.iter().position(|timer| timer.id == id)?;
+            let timer = &mut timers.timers[index];
+
+            match timer.interval {
+                Some(interval) => {
+                    timer.deadline += interval;
+                    if timer.deadline <= now {
+                        timer.deadline = now + interval;
+                    }
+                    timer.callback.take()
+                }
+                // Finished
+                None => timers.timers.remove(index).callback,
+            }
+        });
+
+        let mut callback = match callback {
+            Some(callback) => callback,
+            // Cancelled by an earlier callback
+            None => continue,
+        };
+
+        callback(state);
+
+        // Return the callback unless the timer cancelled itself
+        TIMERS.with(|timers| {
+            if let Some(timer) = timers.borrow_mut().timers.iter_mut().find(|timer| timer.id == id) {
+                timer.callback = Some(callback);
+            }
+        });
+    }
+}
+
+/// When the next timer is due (if any)
+pub fn next_deadline() -> Option<Instant> {
+    TIMERS.with(|timers| timers.borrow().timers.iter().map(|timer| timer.deadline).min())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_shot_timers_run_once() {
+        let mut count = 0;
+        let handle = after(Duration::from_millis(0), |count: &mut i32| *count += 1);
+        assert!(!handle.is_cancelled());
+
+        run_due(&mut count);
+        run_due(&mut count);
+        assert_eq!(count, 1);
+        assert!(handle.is_cancelled());
+        assert_eq!(next_deadline(), None);
+    }
+
+    #[test]
+    fn timers_wait_for_their_deadline() {
+        let mut count = 0;
+        after(Duration::from_secs(60), |count: &mut i32| *count += 1);
+
+        run_due(&mut count);
+        assert_eq!(count, 0);
+        assert!(next_deadline().unwrap() > Instant::now() + Duration::from_secs(59));
+    }
+
+    #[test]
+    fn repeating_timers_are_rescheduled() {
+        let mut count = 0;
+        let handle = every(Duration::from_millis(0), |count: &mut i32| *count += 1);
+
+        // Zero intervals are clamped so the window loop can sleep
+        let deadline = next_deadline().unwrap();
+        assert!(deadline >= Instant::now() + MIN_INTERVAL / 2);
+
+        std::thread::sleep(MIN_INTERVAL);
+        run_due(&mut count);
+        assert_eq!(count, 1);
+        assert!(next_deadline().unwrap() > deadline);
+
+        handle.cancel();
+        assert!(handle.is_cancelled());
+        assert_eq!(next_deadline(), None);
+    }
+
+    #[test]
+    fn callbacks_schedule_and_cancel_timers() {
+        #[derive(Default)]
+        struct State {
+            ticks: i32,
+            ticker: Option<TimerHandle>,
+        }
+
+        let mut state = State::default();
+        state.ticker = Some(after(Duration::from_secs(60), |state: &mut State| state.ticks += 1));
+
+        // A callback cancels a stored handle & schedules another timer
+        after(Duration::from_millis(0), |state: &mut State| {
+            state.ticker.take().unwrap().cancel();
+            after(Duration::from_millis(0), |state: &mut State| state.ticks += 10);
+        });
+
+        run_due(&mut state);
+        assert_eq!(state.ticks, 0);
+        run_due(&mut state);
+        assert_eq!(state.ticks, 10);
+        assert_eq!(next_deadline(), None);
+    }
+
+    #[test]
+    fn timers_only_run_for_their_state_type() {
+        let mut text = String::new();
+        after(Duration::from_millis(0), |text: &mut String| text.push('a'));
+
+        run_due(&mut 0u32);
+        assert!(next_deadline().is_some());
+
+        run_due(&mut text);
+        assert_eq!(text, "a");
+    }
+}
@@ -3,6 +3,7 @@ use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 
 use crate::{Window, colors};
+use crate::scale;
 use super::Component;
 use super::super::{Orientation, IntoViewComponent, ViewComponent};
 
@@ -28,18 +29,18 @@ impl<T> Divider<T> {
             y: 0,
 
             orientation: orientation,
-            thickness: 4,
+            thickness: scale::px(4),
             color: colors::DARKER_PURPLE,
             
-            padding_before: 20,
-            padding_after: 20,
+            padding_before: scale::px(20) as u16,
+            padding_after: scale::px(20) as u16,
 
             state_type: std::marker::PhantomData,
         }
     }
 
     pub fn with_thickness(mut self, thickness: u32) -> Self {
-        self.thickness = thickness;
+        self.thickness = scale::px(thickness);
         self
     }
 
@@ -0,0 +1,271 @@
+/*
+
+Widget id validation & generation
+
+Widget ids drive hover, click & focus tracking within the backend,
+so they must be unique within a view tree.
+
+*/
+
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::fmt;
+
+use crate::view_components::ViewComponent;
+use crate::view_components::views::View;
+use crate::view_components::widgets::Widget;
+
+// ========================== IdScheme Enum ========================== //
+
+/// How ids are generated for widgets created without one (id of `""`)
+/// ## Schemes
+/// - `Path` - The widget's location in the tree, such as `"VStack/HStack[2]/Button[0]"` (default).
+///   Indices count siblings of the same type, so optional siblings of other types do not change the id
+/// - `Hashed` - A short hash of the widget's path, such as `"#3fa2c1d07be9e412"`
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum IdScheme {
+    #[default]
+    Path,
+    Hashed,
+}
+
+// ========================== DuplicateId Struct ========================== //
+
+/// An id shared by multiple widgets
+#[derive(Clone, PartialEq, Debug)]
+pub struct DuplicateId {
+    pub id: &'static str,
+    /// Tree path of each widget using the id
+    pub paths: Vec<String>,
+}
+
+/// Error returned by `validate_ids` (in order of first use)
+/// - Displays as a readable diagnostic listing each id & the widgets using it
+#[derive(Clone, PartialEq, Debug)]
+pub struct DuplicateIds(pub Vec<DuplicateId>);
+
+impl fmt::Display for DuplicateIds {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "duplicate widget ids break hover, click & focus tracking:")?;
+
+        for duplicate in &self.0 {
+            write!(f, "\n  id \"{}\" is used by {} widgets:", duplicate.id, duplicate.paths.len())?;
+            for path in &duplicate.paths {
+                write!(f, "\n    - {}", path)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for DuplicateIds {}
+
+// ========================== Id Interning ========================== //
+
+thread_local! {
+    /// Generated ids. Each distinct id is allocated once and reused between view generations.
+    static INTERNED_IDS: RefCell<HashSet<&'static str>> = RefCell::new(HashSet::new());
+}
+
+/// Obtain a `&'static str` for a generated id.
+/// - Interned strings are never freed, as widget ids are `&'static str` & may outlive their view
+///   (e.g.: the backend's hover state or widgets leaving during a transition)
+/// - Memory therefore grows with every distinct id ever generated, not only those in the current view.
+///   Generated ids are positional, so this is bounded by the largest trees shown (such as the longest
+///   `for` list), but not by the current view. Give widgets of long-lived, changing lists explicit ids
+pub fn intern(id: String) -> &'static str {
+    INTERNED_IDS.with(|ids| {
+        let mut ids = ids.borrow_mut();
+
+        if let Some(existing) = ids.get(id.as_str()) {
+            return *existing;
+        }
+
+        let leaked: &'static str = Box::leak(id.into_boxed_str());
+        ids.insert(leaked);
+        leaked
+    })
+}
+
+/// Strips module paths & generics from `std::any::type_name` output
+/// - `"RustUI::view_components::widgets::button::Button<State>"` becomes `"Button"`
+pub fn short_type_name(full_name: &'static str) -> &'static str {
+    let without_generics = match full_name.find('<') {
+        Some(index) => &full_name[..index],
+        None => full_name,
+    };
+
+    match without_generics.rfind("::") {
+        Some(index) => &without_generics[index + 2..],
+        None => without_generics,
+    }
+}
+
+// ========================== Validation ========================== //
+
+/// Assigns ids to widgets without one, then reports any ids used more than once.
+/// - Sub-widgets (such as a `Button`'s text) receive their parent's id with a suffix
+/// - Call after `View::init` so that views have created their internal widgets
+/// - Ids are assigned even when an error is returned
+pub fn validate_ids<T>(view: &mut dyn View<T>, scheme: IdScheme) -> Result<(), DuplicateIds> {
+    let mut paths: HashMap<&'static str, Vec<String>> = HashMap::new();
+    let mut order = Vec::new();
+
+    let root_path = String::from(view.kind());
+    visit_view(view, &root_path, scheme, &mut paths, &mut order);
+
+    let duplicates: Vec<DuplicateId> = order.into_iter()
+        .filter(|id| paths[id].len() > 1)
+        .map(|id| DuplicateId { id, paths: paths[id].clone() })
+        .collect();
+
+    if duplicates.is_empty() {
+        Ok(())
+    } else {
+        Err(DuplicateIds(duplicates))
+    }
+}
+
+fn visit_view<T>(view: &mut dyn View<T>, view_path: &str, scheme: IdScheme,
+                 paths: &mut HashMap<&'static str, Vec<String>>, order: &mut Vec<&'static str>) {
+    // Index of each component among siblings of the same type
+    let mut kind_counts: HashMap<&'static str, usize> = HashMap::new();
+    let mut next_index = |kind: &'static str| {
+        let count = kind_counts.entry(kind).or_insert(0);
+        *count += 1;
+        *count - 1
+    };
+
+    for component in view.components_mut() {
+        match component {
+            ViewComponent::Widget(widget) => {
+                let path = format!("{}/{}[{}]", view_path, widget.kind(), next_index(widget.kind()));
+
+                if widget.id() == "" {
+                    widget.set_id(generate_id(&path, scheme));
+                }
+
+                let id = widget.id();
+
+                // Name unnamed sub-widgets after their parent
+                if let Some(text) = widget.text_component() {
+                    if text.id() == "" {
+                        text.set_id(intern(format!("{}.text", id)));
+                    }
+                }
+
+                let widget_paths = paths.entry(id).or_default();
+                if widget_paths.is_empty() {
+                    order.push(id);
+                }
+                widget_paths.push(path);
+            }
+            ViewComponent::View(subview) => {
+                let path = format!("{}/{}[{}]", view_path, subview.kind(), next_index(subview.kind()));
+                visit_view(&mut **subview, &path, scheme, paths, order);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn generate_id(path: &str, scheme: IdScheme) -> &'static str {
+    match scheme {
+        IdScheme::Path => intern(String::from(path)),
+        IdScheme::Hashed => {
+            let mut hasher = DefaultHasher::new();
+            path.hash(&mut hasher);
+            intern(format!("#{:016x}", hasher.finish()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view_components::IntoViewComponent;
+    use crate::view_components::views::{HStack, VStack};
+    use crate::view_components::widgets::Button;
+
+    fn tree() -> VStack<()> {
+        VStack::new(vec![
+            Button::new("").as_component(),
+            Button::new("shared").as_component(),
+            None::<Button<()>>.as_component(),
+            HStack::new(vec![
+                Button::new("shared").as_component(),
+                Button::new("").as_component(),
+            ]).as_component(),
+            Button::new("").as_component(),
+        ])
+    }
+
+    /// Widget ids in tree order
+    fn ids(view: &mut dyn View<()>) -> Vec<&'static str> {
+        let mut ids = Vec::new();
+        for component in view.components_mut() {
+            match component {
+                ViewComponent::Widget(widget) => ids.push(widget.id()),
+                ViewComponent::View(subview) => ids.extend(self::ids(&mut **subview)),
+                _ => {}
+            }
+        }
+        ids
+    }
+
+    #[test]
+    fn generated_path_ids() {
+        let mut view = tree();
+        // Explicit duplicates do not prevent ids from being assigned
+        assert!(validate_ids(&mut view, IdScheme::Path).is_err());
+
+        // Indices count siblings of the same type
+        assert_eq!(ids(&mut view), vec![
+            "VStack/Button[0]",
+            "shared",
+            "shared",
+            "VStack/HStack[0]/Button[1]",
+            "VStack/Button[2]",
+        ]);
+    }
+
+    #[test]
+    fn generated_hashed_ids() {
+        let mut first = tree();
+        let mut second = tree();
+        let _ = validate_ids(&mut first, IdScheme::Hashed);
+        let _ = validate_ids(&mut second, IdScheme::Hashed);
+
+        let ids = ids(&mut first);
+        assert!(ids[0].starts_with('#') && ids[0].len() == 17);
+        assert_ne!(ids[0], ids[3]);
+        // Regenerated trees receive the same ids
+        assert_eq!(ids, self::ids(&mut second));
+    }
+
+    #[test]
+    fn duplicate_ids_are_reported() {
+        let mut view = tree();
+        let error = validate_ids(&mut view, IdScheme::Path).unwrap_err();
+
+        assert_eq!(error.0.len(), 1);
+        assert_eq!(error.0[0].id, "shared");
+        assert_eq!(error.0[0].paths, vec!["VStack/Button[1]", "VStack/HStack[0]/Button[0]"]);
+        assert!(error.to_string().contains("id \"shared\" is used by 2 widgets:\n    - VStack/Button[1]"));
+    }
+
+    #[test]
+    fn unique_ids_are_valid() {
+        let mut view: VStack<()> = VStack::new(vec![Button::new("a").as_component(), Button::new("b").as_component()]);
+        assert_eq!(validate_ids(&mut view, IdScheme::Path), Ok(()));
+    }
+
+    #[test]
+    fn short_type_names() {
+        assert_eq!(short_type_name("RustUI::view_components::widgets::button::Button<State>"), "Button");
+        assert_eq!(short_type_name("VStack"), "VStack");
+    }
+}
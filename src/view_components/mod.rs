@@ -1,6 +1,9 @@
 pub mod views;
 pub mod widgets;
 pub mod components;
+pub mod ids;
+pub mod reconcile;
+pub mod theme;
 
 // ========================== ViewComponent enum ========================== //
 
@@ -9,11 +12,14 @@ pub enum ViewComponent<T> {
     Widget(Box<dyn widgets::Widget<T>>),
     View(Box<dyn views::View<T>>),
     Component(Box<dyn components::Component<T>>),
+    /// Placeholder for an absent optional item. Discarded by views.
+    Empty,
 }
 
 // ========================== Orientation enum ========================== //
 
 /// Determines a component's layout as either horizontal or vertical
+#[derive(Clone, Copy, PartialEq)]
 pub enum Orientation {
     Horizontal,
     Vertical,
@@ -27,6 +33,95 @@ pub trait IntoViewComponent<T> {
     fn as_component(self) -> ViewComponent<T>;
 }
 
+/// Optional items can be placed directly within view macros
+impl<T, V: IntoViewComponent<T>> IntoViewComponent<T> for Option<V> {
+    fn as_component(self) -> ViewComponent<T> {
+        match self {
+            Some(item) => item.as_component(),
+            None => ViewComponent::Empty,
+        }
+    }
+}
+
+// ========================== View Macro Internals ========================== //
+
+/// Collects view macro items into a `Vec<ViewComponent<T>>`
+/// ## Supported items (comma separated)
+/// - `widget_or_view`
+/// - `if condition => item`
+/// - `if condition => { items.. } else { items.. }`
+/// - `if let pattern = expression => item` (or `{ items.. }` with optional `else`)
+/// - `for pattern in iterator => item` (or `{ items.. }`)
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __view_components {
+    (@push $components:ident;) => {};
+
+    // ---------- if let ---------- //
+    (@push $components:ident; if let $pattern:pat = $value:expr => { $($then:tt)* } else { $($otherwise:tt)* } $(, $($rest:tt)*)?) => {
+        if let $pattern = $value {
+            $crate::__view_components!(@push $components; $($then)*);
+        } else {
+            $crate::__view_components!(@push $components; $($otherwise)*);
+        }
+        $crate::__view_components!(@push $components; $($($rest)*)?);
+    };
+    (@push $components:ident; if let $pattern:pat = $value:expr => { $($then:tt)* } $(, $($rest:tt)*)?) => {
+        if let $pattern = $value {
+            $crate::__view_components!(@push $components; $($then)*);
+        }
+        $crate::__view_components!(@push $components; $($($rest)*)?);
+    };
+    (@push $components:ident; if let $pattern:pat = $value:expr => $item:expr $(, $($rest:tt)*)?) => {
+        if let $pattern = $value {
+            $components.push($crate::view_components::IntoViewComponent::as_component($item));
+        }
+        $crate::__view_components!(@push $components; $($($rest)*)?);
+    };
+
+    // ---------- if ---------- //
+    (@push $components:ident; if $condition:expr => { $($then:tt)* } else { $($otherwise:tt)* } $(, $($rest:tt)*)?) => {
+        if $condition {
+            $crate::__view_components!(@push $components; $($then)*);
+        } else {
+            $crate::__view_components!(@push $components; $($otherwise)*);
+        }
+        $crate::__view_components!(@push $components; $($($rest)*)?);
+    };
+    (@push $components:ident; if $condition:expr => { $($then:tt)* } $(, $($rest:tt)*)?) => {
+        if $condition {
+            $crate::__view_components!(@push $components; $($then)*);
+        }
+        $crate::__view_components!(@push $components; $($($rest)*)?);
+    };
+    (@push $components:ident; if $condition:expr => $item:expr $(, $($rest:tt)*)?) => {
+        if $condition {
+            $components.push($crate::view_components::IntoViewComponent::as_component($item));
+        }
+        $crate::__view_components!(@push $components; $($($rest)*)?);
+    };
+
+    // ---------- for ---------- //
+    (@push $components:ident; for $pattern:pat in $iterator:expr => { $($body:tt)* } $(, $($rest:tt)*)?) => {
+        for $pattern in $iterator {
+            $crate::__view_components!(@push $components; $($body)*);
+        }
+        $crate::__view_components!(@push $components; $($($rest)*)?);
+    };
+    (@push $components:ident; for $pattern:pat in $iterator:expr => $item:expr $(, $($rest:tt)*)?) => {
+        for $pattern in $iterator {
+            $components.push($crate::view_components::IntoViewComponent::as_component($item));
+        }
+        $crate::__view_components!(@push $components; $($($rest)*)?);
+    };
+
+    // ---------- Single item ---------- //
+    (@push $components:ident; $item:expr $(, $($rest:tt)*)?) => {
+        $components.push($crate::view_components::IntoViewComponent::as_component($item));
+        $crate::__view_components!(@push $components; $($($rest)*)?);
+    };
+}
+
 // ========================== Padding Struct ========================== //
 
 pub struct Padding {
@@ -57,4 +152,92 @@ pub mod colors {
     pub const LIGHT_GRAY: Color = Color {r: 200, g: 200, b: 200, a: 0xff};
     /// `Color::RGB(80, 80, 80)` - Light Gray
     pub const DARK_GRAY: Color = Color {r: 80, g: 80, b: 80, a: 0xff};
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view_components::views::{View, VStack};
+    use crate::view_components::widgets::Canvas;
+
+    fn canvas(id: &'static str) -> Canvas<()> {
+        Canvas::new(id, (10, 10))
+    }
+
+    /// Ids of the collected components (`None` for absent items)
+    fn ids(components: Vec<ViewComponent<()>>) -> Vec<Option<&'static str>> {
+        components.into_iter().map(|component| match component {
+            ViewComponent::Widget(widget) => Some(widget.id()),
+            _ => None,
+        }).collect()
+    }
+
+    #[test]
+    fn conditional_items() {
+        let mut components = Vec::new();
+        let shown = true;
+        crate::__view_components!(@push components;
+            canvas("first"),
+            if shown => canvas("shown"),
+            if !shown => canvas("hidden"),
+            if shown => { canvas("then"), canvas("then too") } else { canvas("otherwise") },
+            if !shown => { canvas("then") } else { canvas("otherwise") },
+        );
+
+        assert_eq!(ids(components), vec![
+            Some("first"), Some("shown"), Some("then"), Some("then too"), Some("otherwise"),
+        ]);
+    }
+
+    #[test]
+    fn if_let_items() {
+        let mut components = Vec::new();
+        let some = Some("some");
+        let none: Option<&'static str> = None;
+        crate::__view_components!(@push components;
+            if let Some(id) = some => canvas(id),
+            if let Some(id) = none => canvas(id),
+            if let Some(id) = none => { canvas(id) } else { canvas("else") }
+        );
+
+        assert_eq!(ids(components), vec![Some("some"), Some("else")]);
+    }
+
+    #[test]
+    fn for_and_optional_items() {
+        let mut components = Vec::new();
+        crate::__view_components!(@push components;
+            for id in &["a", "b"] => canvas(id),
+            for id in &["c"] => { canvas(id), Some(canvas("d")) },
+            None::<Canvas<()>>,
+        );
+
+        assert_eq!(ids(components), vec![Some("a"), Some("b"), Some("c"), Some("d"), None]);
+    }
+
+    fn auto_id_view() -> VStack<()> {
+        crate::view! { VStack { Canvas::new(_, (10, 10)), Canvas::new("named", (10, 10)) } }
+    }
+
+    fn widget_ids(view: &mut dyn View<()>) -> Vec<&'static str> {
+        view.child_widgets_mut().into_iter().map(|widget| widget.id()).collect()
+    }
+
+    #[test]
+    fn view_macro_generates_ids() {
+        let first = widget_ids(&mut auto_id_view());
+        let same_site = widget_ids(&mut auto_id_view());
+        let other_site = widget_ids(&mut crate::view! { VStack { Canvas::<()>::new(_, (10, 10)) } });
+
+        assert!(first[0].starts_with("__auto_Canvas_"));
+        assert_eq!(first[1], "named");
+        // Regenerated views keep their ids
+        assert_eq!(first, same_site);
+        assert_ne!(first[0], other_site[0]);
+    }
+
+    #[test]
+    fn views_discard_absent_items() {
+        let mut view = VStack::new(vec![canvas("a").as_component(), None::<Canvas<()>>.as_component()]);
+        assert_eq!(view.components_mut().len(), 1);
+    }
+}
@@ -0,0 +1,317 @@
+/*
+
+View reconciliation
+
+Views are regenerated whenever state changes. Rather than measuring every
+text surface again (which loads fonts), the newly generated tree is matched
+against the current tree and measurements are carried over wherever the
+text & font are unchanged. Only new or modified text is measured by `View::init`.
+
+Widgets are matched by type & id. Widgets without an id, and nested views,
+are matched by type & position within their parent view.
+
+When the regenerated tree has the same structure, layout parameters & widget sizes
+as the current one, `patch` moves its widgets into the current tree in place
+so the current layout is kept (`View::init` & `View::align` are skipped).
+Otherwise the regenerated tree replaces the current one & is laid out in full.
+
+Widget-internal state (such as animation playback) is also carried over
+(see `Widget::persistent_state`).
+
+*/
+
+extern crate sdl2;
+use sdl2::ttf;
+
+use std::collections::HashMap;
+
+use crate::font::Fonts;
+use crate::view_components::ViewComponent;
+use crate::view_components::views::View;
+use crate::view_components::widgets::Widget;
+
+/// Transfers text measurements & persistent state from `current` to the matching widgets of `next`
+/// - Call before `next.init()` so that unchanged text is not measured again
+/// - Returns the number of measurements reused
+pub fn reconcile<T>(current: &mut dyn View<T>, next: &mut dyn View<T>) -> usize {
+    let mut current_components = current.components_mut();
+
+    // (kind, id) -> index of the current widget
+    let mut named_widgets = HashMap::new();
+    for (index, component) in current_components.iter().enumerate() {
+        if let ViewComponent::Widget(widget) = component {
+            if !widget.id().is_empty() {
+                named_widgets.insert((widget.kind(), widget.id()), index);
+            }
+        }
+    }
+
+    let mut reused = 0;
+
+    for (index, component) in next.components_mut().into_iter().enumerate() {
+        match component {
+            ViewComponent::Widget(widget) => {
+                let matched_index = if !widget.id().is_empty() {
+                    named_widgets.get(&(widget.kind(), widget.id())).cloned()
+                } else {
+                    Some(index)
+                };
+
+                let previous = match matched_index.and_then(|index| current_components.get_mut(index)) {
+                    Some(ViewComponent::Widget(previous)) if previous.kind() == widget.kind() => previous,
+                    _ => continue,
+                };
+
+                if let Some(state) = previous.persistent_state() {
+                    widget.restore_state(state);
+                }
+
+                if reuse_measurement(&mut **previous, &mut **widget) {
+                    reused += 1;
+                }
+            }
+
+            ViewComponent::View(subview) => {
+                if let Some(ViewComponent::View(previous)) = current_components.get_mut(index) {
+                    if previous.kind() == subview.kind() {
+                        reused += reconcile(&mut **previous, &mut **subview);
+                    }
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    reused
+}
+
+/// Copies the text size of `previous` into `next` if the text & font are unchanged
+fn reuse_measurement<T>(previous: &mut dyn Widget<T>, next: &mut dyn Widget<T>) -> bool {
+    let dimensions = match (previous.text_component(), next.text_component()) {
+        (Some(previous_text), Some(next_text)) => {
+            if !previous_text.is_measured()
+                || previous_text.text != next_text.text
+                || previous_text.font != next_text.font
+                || previous_text.wrap != next_text.wrap {
+                return false;
+            }
+            (previous_text.text_width, previous_text.text_height)
+        }
+        _ => return false,
+    };
+
+    next.assign_text_dimensions(dimensions);
+    true
+}
+
+/// Moves the widgets of `next` into the matching places of `current`, keeping the current layout
+/// - Call after `reconcile`, so that state & unchanged measurements are already carried over
+/// - Only succeeds when both trees contain the same views (by type & `View::layout_hash`)
+///   and widgets (by type & id), with every widget of `next` measuring the same as the one it replaces
+/// - Returns `false` without modifying `current` when `next` must be laid out instead (`View::init`)
+/// - Widgets without an id must be assigned one again afterwards (see `ids::validate_ids`)
+pub fn patch<T>(current: &mut dyn View<T>, next: &mut dyn View<T>, ttf_context: &ttf::Sdl2TtfContext) -> bool {
+    let mut font_manager = Fonts::new();
+
+    if !can_patch(current, next, ttf_context, &mut font_manager) {
+        return false;
+    }
+
+    apply_patch(current, next);
+    true
+}
+
+/// Whether `next` would be laid out exactly like `current` (measuring the widgets of `next`)
+fn can_patch<'ttf, T>(current: &mut dyn View<T>, next: &mut dyn View<T>,
+                      ttf_context: &'ttf ttf::Sdl2TtfContext, font_manager: &mut Fonts<'ttf>) -> bool {
+    if current.kind() != next.kind() {
+        return false;
+    }
+
+    // Views which do not describe their layout are always laid out again
+    match (current.layout_hash(), next.layout_hash()) {
+        (Some(current_hash), Some(next_hash)) if current_hash == next_hash => {}
+        _ => return false,
+    }
+
+    let mut current_components = current.components_mut();
+    let next_components = next.components_mut();
+    if current_components.len() != next_components.len() {
+        return false;
+    }
+
+    for (previous, component) in current_components.iter_mut().zip(next_components) {
+        let matches = match (&mut **previous, component) {
+            (ViewComponent::Widget(previous), ViewComponent::Widget(widget)) => {
+                previous.kind() == widget.kind()
+                    && (widget.id().is_empty() || widget.id() == previous.id())
+                    && {
+                        measure(&mut **widget, ttf_context, font_manager);
+                        widget.draw_width() == previous.draw_width() && widget.draw_height() == previous.draw_height()
+                    }
+            }
+            (ViewComponent::View(previous), ViewComponent::View(subview)) => {
+                can_patch(&mut **previous, &mut **subview, ttf_context, font_manager)
+            }
+            (ViewComponent::Empty, ViewComponent::Empty) => true,
+            // Components are placed by their views
+            _ => false,
+        };
+
+        if !matches {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Swaps each widget of `next` into the position of its match in `current`
+fn apply_patch<T>(current: &mut dyn View<T>, next: &mut dyn View<T>) {
+    for (previous, component) in current.components_mut().into_iter().zip(next.components_mut()) {
+        match (&mut *previous, &mut *component) {
+            (ViewComponent::Widget(previous_widget), ViewComponent::Widget(widget)) => {
+                let (previous_rect, rect) = (previous_widget.rect(), widget.rect());
+                widget.translate(previous_rect.x() - rect.x(), previous_rect.y() - rect.y());
+            }
+            (ViewComponent::View(previous_view), ViewComponent::View(subview)) => {
+                apply_patch(&mut **previous_view, &mut **subview);
+                continue;
+            }
+            _ => continue,
+        }
+
+        std::mem::swap(previous, component);
+    }
+}
+
+/// Measures a widget's font-dependent content, as `View::init` would
+fn measure<'ttf, T>(widget: &mut dyn Widget<T>, ttf_context: &'ttf ttf::Sdl2TtfContext, font_manager: &mut Fonts<'ttf>) {
+    widget.measure(ttf_context);
+
+    if let Some(text_component) = widget.text_component() {
+        if text_component.is_measured() {
+            return;
+        }
+
+        font_manager.load_font(ttf_context, &text_component.font);
+        let text_surface_size = font_manager.size_text(&text_component.font, &text_component.text, &text_component.wrap);
+        widget.assign_text_dimensions(text_surface_size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view_components::IntoViewComponent;
+    use crate::view_components::views::{VStack, HStack};
+    use crate::view_components::widgets::Text;
+
+    /// Text which is already measured as `width` x 20
+    fn text(id: &'static str, content: &str, width: u32) -> ViewComponent<()> {
+        let mut text = Text::new(id, content);
+        text.assign_text_dimensions((width, 20));
+        text.as_component()
+    }
+
+    /// Ids & text of the view's (text) widgets
+    fn texts(view: &mut dyn View<()>) -> Vec<(&'static str, String)> {
+        view.child_widgets_mut().into_iter()
+            .map(|widget| (widget.id(), widget.text_component().unwrap().text.clone()))
+            .collect()
+    }
+
+    fn rects(view: &mut dyn View<()>) -> Vec<sdl2::rect::Rect> {
+        view.child_widgets_mut().into_iter().map(|widget| widget.rect()).collect()
+    }
+
+    /// An initialized view, as shown by the window
+    fn shown(ttf_context: &ttf::Sdl2TtfContext) -> VStack<()> {
+        let mut view = VStack::new(vec![
+            text("title", "Title", 60),
+            HStack::new(vec![text("", "one", 30), text("", "two", 30)]).as_component(),
+        ]);
+        view.init(ttf_context);
+        view.align();
+        view
+    }
+
+    #[test]
+    fn reuses_unchanged_measurements() {
+        let mut current = VStack::new(vec![text("a", "same", 40), text("b", "old", 40)]);
+        let mut next = VStack::new(vec![Text::new("b", "new").as_component(), Text::new("a", "same").as_component()]);
+
+        assert_eq!(reconcile(&mut current, &mut next), 1);
+
+        // Named widgets are matched regardless of position
+        let mut widgets = next.widgets_mut();
+        assert!(!widgets[0].text_component().unwrap().is_measured());
+        let same = widgets[1].text_component().unwrap();
+        assert!(same.is_measured());
+        assert_eq!((same.text_width, same.text_height), (40, 20));
+    }
+
+    #[test]
+    fn unnamed_widgets_match_by_position_and_type() {
+        let mut current = VStack::new(vec![HStack::new(vec![text("", "text", 40)]).as_component()]);
+        let mut next = VStack::new(vec![HStack::new(vec![Text::new("", "text").as_component()]).as_component()]);
+        let mut other_type = VStack::new(vec![VStack::new(vec![Text::new("", "text").as_component()]).as_component()]);
+
+        assert_eq!(reconcile(&mut current, &mut next), 1);
+        // Nested views of another type are not matched
+        assert_eq!(reconcile(&mut current, &mut other_type), 0);
+    }
+
+    #[test]
+    fn patch_keeps_layout_and_replaces_widgets() {
+        let ttf_context = ttf::init().unwrap();
+        let mut current = shown(&ttf_context);
+        let layout = rects(&mut current);
+
+        // Changed text of the same size
+        let mut next = VStack::new(vec![
+            text("title", "Other", 60),
+            HStack::new(vec![text("", "one", 30), text("", "TWO", 30)]).as_component(),
+        ]);
+        reconcile(&mut current, &mut next);
+
+        assert!(patch(&mut current, &mut next, &ttf_context));
+        assert_eq!(rects(&mut current), layout);
+        assert_eq!(texts(&mut current), vec![
+            ("title", String::from("Other")),
+            ("", String::from("one")),
+            ("", String::from("TWO")),
+        ]);
+    }
+
+    #[test]
+    fn patch_rejects_layout_changes() {
+        let ttf_context = ttf::init().unwrap();
+        let mut current = shown(&ttf_context);
+        let unchanged = texts(&mut current);
+
+        let resized = VStack::new(vec![
+            text("title", "Title", 60),
+            HStack::new(vec![text("", "one", 30), text("", "three", 50)]).as_component(),
+        ]);
+        let padded = VStack::new(vec![
+            text("title", "Title", 60),
+            HStack::new(vec![text("", "one", 30), text("", "two", 30)]).padding(1, 1, 1, 1).as_component(),
+        ]);
+        let extended = VStack::new(vec![
+            text("title", "Title", 60),
+            HStack::new(vec![text("", "one", 30), text("", "two", 30)]).as_component(),
+            text("", "more", 30),
+        ]);
+        let renamed = VStack::new(vec![
+            text("heading", "Title", 60),
+            HStack::new(vec![text("", "one", 30), text("", "two", 30)]).as_component(),
+        ]);
+
+        for mut next in [resized, padded, extended, renamed] {
+            assert!(!patch(&mut current, &mut next, &ttf_context));
+            assert_eq!(texts(&mut current), unchanged);
+        }
+    }
+}
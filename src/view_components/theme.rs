@@ -0,0 +1,753 @@
+/*
+
+Themes
+
+The current theme provides default colors, font & spacing to widgets and views
+as they are created. Builder methods (such as `Button::with_base_color`) override
+the theme for individual widgets.
+
+Themes may be loaded from TOML style files (see `Theme::from_file`).
+
+*/
+
+extern crate sdl2;
+use sdl2::pixels::Color;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::font::{self, FontParams};
+use crate::colors;
+
+// ========================== Palette Struct ========================== //
+
+/// Colors shared across widgets & views
+#[derive(Clone, PartialEq)]
+pub struct Palette {
+    /// Window background
+    pub background: Color,
+    /// Default text color
+    pub text: Color,
+    /// Placeholder & secondary text color
+    pub muted_text: Color,
+    /// Dims the content behind overlays
+    pub overlay: Color,
+}
+
+// ========================== Style Properties ========================== //
+
+/// Value of a style property
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StyleValue {
+    /// Such as `base = "#F0F0C8"`
+    Color(Color),
+    /// Unscaled pixels, such as `padding = 8`
+    Length(u32),
+}
+
+/// Property name -> value
+pub type StyleProperties = HashMap<String, StyleValue>;
+
+/// A set of named style properties which can be overridden by style files
+pub trait Style: Clone {
+    /// Color property names accepted by `apply`
+    fn properties() -> &'static [&'static str];
+
+    /// Length property names accepted by `apply`
+    fn lengths() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Override properties with those present in `properties`
+    fn apply(&mut self, properties: &StyleProperties);
+}
+
+/// Implements `Style` for a struct of `Color` fields & (optionally) `u32` length fields
+macro_rules! style {
+    ($name:ident { $($field:ident),+ } $(lengths { $($length:ident),+ })?) => {
+        impl Style for $name {
+            fn properties() -> &'static [&'static str] {
+                &[$(stringify!($field)),+]
+            }
+
+            $(
+                fn lengths() -> &'static [&'static str] {
+                    &[$(stringify!($length)),+]
+                }
+            )?
+
+            fn apply(&mut self, properties: &StyleProperties) {
+                $(
+                    if let Some(StyleValue::Color(color)) = properties.get(stringify!($field)) {
+                        self.$field = *color;
+                    }
+                )+
+                $($(
+                    if let Some(StyleValue::Length(length)) = properties.get(stringify!($length)) {
+                        self.$length = *length;
+                    }
+                )+)?
+            }
+        }
+    };
+}
+
+// ========================== Widget Styles ========================== //
+
+/// Style of a single `Text` widget (see `Theme::text_style`)
+#[derive(Clone, PartialEq)]
+pub struct TextStyle {
+    pub color: Color,
+    /// Space kept around auto-resized text within its container
+    pub padding: u32,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ButtonStyle {
+    pub base: Color,
+    pub hover: Color,
+    pub click: Color,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct CheckBoxStyle {
+    pub base: Color,
+    pub hover: Color,
+    pub click: Color,
+    pub check: Color,
+    pub label: Color,
+    /// Space between the box & its label
+    pub padding: u32,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct TextBoxStyle {
+    pub background: Color,
+    pub focus: Color,
+    pub cursor: Color,
+    /// Space between the left edge & the text
+    pub padding: u32,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ScrollBarStyle {
+    pub slider: Color,
+    pub slider_hover: Color,
+    pub slider_active: Color,
+    pub rail: Color,
+    pub rail_hover: Color,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ImageStyle {
+    /// Border or shade color on hover
+    pub hover: Color,
+    /// Border or shade color on click
+    pub click: Color,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct SplitStyle {
+    pub bar: Color,
+    pub bar_hover: Color,
+    pub bar_drag: Color,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct TabStyle {
+    pub strip: Color,
+    pub tab: Color,
+    pub tab_hover: Color,
+    pub selected: Color,
+    pub label: Color,
+}
+
+style!(TextStyle { color } lengths { padding });
+style!(ButtonStyle { base, hover, click });
+style!(CheckBoxStyle { base, hover, click, check, label } lengths { padding });
+style!(TextBoxStyle { background, focus, cursor } lengths { padding });
+style!(ScrollBarStyle { slider, slider_hover, slider_active, rail, rail_hover });
+style!(ImageStyle { hover, click });
+style!(SplitStyle { bar, bar_hover, bar_drag });
+style!(TabStyle { strip, tab, tab_hover, selected, label });
+style!(Palette { background, text, muted_text, overlay });
+
+// ========================== Theme Struct ========================== //
+
+/// Default appearance of widgets & views
+/// - Set with `Window::set_theme` before the window is started
+#[derive(Clone, PartialEq)]
+pub struct Theme {
+    pub palette: Palette,
+    /// Default font for all text
+    pub font: FontParams,
+    /// Space between items within stacks & around their edges
+    pub spacing: u32,
+    /// Corner radius of buttons & checkboxes
+    pub corner_radius: u32,
+    /// Space kept around auto-resized text (see `TextStyle`)
+    pub text_padding: u32,
+
+    pub button: ButtonStyle,
+    pub checkbox: CheckBoxStyle,
+    pub textbox: TextBoxStyle,
+    pub scrollbar: ScrollBarStyle,
+    pub image: ImageStyle,
+    pub split: SplitStyle,
+    pub tabs: TabStyle,
+
+    /// Styles of individual widgets, keyed by `"Type#id"` (such as `"Button#ResetCounter"`)
+    pub id_styles: HashMap<String, StyleProperties>,
+}
+
+impl Theme {
+    /// The original RustUI appearance (default)
+    pub fn dark() -> Self {
+        Theme {
+            palette: Palette {
+                background: colors::DARK_PURPLE,
+                text: colors::BLACK,
+                muted_text: colors::DARK_GRAY,
+                overlay: Color::RGBA(0, 0, 0, 180),
+            },
+            font: FontParams::default_font(),
+            spacing: 10,
+            corner_radius: 0,
+            text_padding: 10,
+
+            button: ButtonStyle {
+                base: colors::MANILLA,
+                hover: Color::RGB(200, 200, 200),
+                click: Color::RGB(100, 100, 100),
+            },
+            checkbox: CheckBoxStyle {
+                base: colors::MANILLA,
+                hover: Color::RGB(200, 200, 200),
+                click: Color::RGB(140, 140, 140),
+                check: Color::RGB(80, 80, 80),
+                label: colors::WHITE,
+                padding: 10,
+            },
+            textbox: TextBoxStyle {
+                background: colors::LIGHT_GRAY,
+                focus: colors::WHITE,
+                cursor: colors::BLACK,
+                padding: 0,
+            },
+            scrollbar: ScrollBarStyle {
+                slider: colors::MANILLA,
+                slider_hover: Color::RGB(200, 200, 200),
+                slider_active: colors::DARK_GRAY,
+                rail: colors::LIGHT_GRAY,
+                rail_hover: colors::WHITE,
+            },
+            image: ImageStyle {
+                hover: colors::DARKER_PURPLE,
+                click: colors::BLACK,
+            },
+            split: SplitStyle {
+                bar: colors::DARKER_PURPLE,
+                bar_hover: colors::LIGHT_GRAY,
+                bar_drag: colors::MANILLA,
+            },
+            tabs: TabStyle {
+                strip: colors::DARKER_PURPLE,
+                tab: colors::DARK_PURPLE,
+                tab_hover: Color::RGB(80, 80, 140),
+                selected: colors::DARK_GRAY,
+                label: colors::WHITE,
+            },
+
+            id_styles: HashMap::new(),
+        }
+    }
+
+    /// Dark text on light backgrounds
+    pub fn light() -> Self {
+        Theme {
+            palette: Palette {
+                background: Color::RGB(236, 236, 240),
+                text: Color::RGB(30, 30, 30),
+                muted_text: Color::RGB(130, 130, 130),
+                overlay: Color::RGBA(255, 255, 255, 180),
+            },
+            font: FontParams::default_font(),
+            spacing: 10,
+            corner_radius: 0,
+            text_padding: 10,
+
+            button: ButtonStyle {
+                base: Color::RGB(210, 214, 224),
+                hover: Color::RGB(190, 196, 210),
+                click: Color::RGB(150, 156, 175),
+            },
+            checkbox: CheckBoxStyle {
+                base: colors::WHITE,
+                hover: Color::RGB(220, 222, 230),
+                click: Color::RGB(180, 184, 196),
+                check: Color::RGB(60, 90, 170),
+                label: Color::RGB(30, 30, 30),
+                padding: 10,
+            },
+            textbox: TextBoxStyle {
+                background: colors::WHITE,
+                focus: Color::RGB(250, 250, 255),
+                cursor: Color::RGB(30, 30, 30),
+                padding: 0,
+            },
+            scrollbar: ScrollBarStyle {
+                slider: Color::RGB(60, 90, 170),
+                slider_hover: Color::RGB(80, 110, 190),
+                slider_active: Color::RGB(40, 60, 120),
+                rail: Color::RGB(190, 192, 200),
+                rail_hover: Color::RGB(160, 162, 172),
+            },
+            image: ImageStyle {
+                hover: Color::RGB(60, 90, 170),
+                click: Color::RGB(40, 60, 120),
+            },
+            split: SplitStyle {
+                bar: Color::RGB(200, 202, 210),
+                bar_hover: Color::RGB(160, 162, 172),
+                bar_drag: Color::RGB(60, 90, 170),
+            },
+            tabs: TabStyle {
+                strip: Color::RGB(210, 212, 220),
+                tab: Color::RGB(226, 228, 234),
+                tab_hover: Color::RGB(240, 240, 246),
+                selected: colors::WHITE,
+                label: Color::RGB(30, 30, 30),
+            },
+
+            id_styles: HashMap::new(),
+        }
+    }
+}
+
+impl Theme {
+    /// Style of `Text` widgets (the palette's text color & `text_padding`)
+    pub fn text_style(&self) -> TextStyle {
+        TextStyle { color: self.palette.text, padding: self.text_padding }
+    }
+
+    /// `style` with any overrides for the widget `"kind#id"` applied
+    /// - Used by widget constructors, e.g. `theme.styled(&theme.button, "Button", id)`
+    pub fn styled<S: Style>(&self, style: &S, kind: &str, id: &str) -> S {
+        let mut style = style.clone();
+
+        if !id.is_empty() {
+            if let Some(properties) = self.id_styles.get(&format!("{}#{}", kind, id)) {
+                style.apply(properties);
+            }
+        }
+
+        style
+    }
+
+    /// Load a theme from a TOML style file
+    /// ## Format
+    /// ```toml
+    /// base = "dark"           # or "light"
+    /// spacing = 10
+    /// font_size = 20
+    ///
+    /// [palette]
+    /// background = "#323264"  # "#RRGGBB", "#RRGGBBAA" or [r, g, b(, a)]
+    ///
+    /// [Button]                # Every button
+    /// base = "#F0F0C8"
+    ///
+    /// ["Button#ResetCounter"] # The button with id "ResetCounter"
+    /// hover = [255, 80, 80]
+    ///
+    /// [TextBox]               # Lengths are in unscaled pixels
+    /// padding = 6
+    /// ```
+    /// - `Text`, `CheckBox` & `TextBox` sections also accept `padding`
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Theme, String> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read style file {}: {}", path.display(), e))?;
+
+        Theme::from_toml_str(&contents)
+            .map_err(|e| format!("Invalid style file {}: {}", path.display(), e))
+    }
+
+    /// Parse a theme from the contents of a TOML style file (see `from_file`)
+    pub fn from_toml_str(contents: &str) -> Result<Theme, String> {
+        let root: toml::Value = contents.parse().map_err(|e: toml::de::Error| e.to_string())?;
+        let root = match root {
+            toml::Value::Table(table) => table,
+            _ => return Err(String::from("Expected a table")),
+        };
+
+        let mut theme = match root.get("base").and_then(|base| base.as_str()) {
+            Some("dark") | None => Theme::dark(),
+            Some("light") => Theme::light(),
+            Some(other) => return Err(format!("Unknown base theme `{}`; expected `dark` or `light`", other)),
+        };
+
+        for (key, value) in &root {
+            match (key.as_str(), value) {
+                ("base", _) => {}
+                ("spacing", _) => theme.spacing = parse_number(key, value, u32::MAX as f64)? as u32,
+                ("corner_radius", _) => theme.corner_radius = parse_number(key, value, u32::MAX as f64)? as u32,
+                ("font_size", _) => theme.font.point_size = parse_number(key, value, u16::MAX as f64)? as u16,
+                // A font file path or an installed font's family name
+                ("font", toml::Value::String(name)) => theme.font.path = font::resolve_font(name),
+
+                (section, toml::Value::Table(table)) => {
+                    let (kind, id) = match section.find('#') {
+                        Some(index) => (&section[..index], Some(&section[index + 1..])),
+                        None => (section, None),
+                    };
+
+                    let known = match kind_properties(kind) {
+                        Some(known) => known,
+                        None => return Err(format!("Unknown style section `[{}]`", section)),
+                    };
+                    let properties = parse_properties(section, table, known)?;
+
+                    match id {
+                        Some(_) => { theme.id_styles.insert(String::from(section), properties); }
+                        None => theme.apply_kind(kind, &properties),
+                    }
+                }
+
+                _ => return Err(format!("Unknown or invalid setting `{}`", key)),
+            }
+        }
+
+        Ok(theme)
+    }
+
+    /// Apply a `[Type]` section to the matching style
+    fn apply_kind(&mut self, kind: &str, properties: &StyleProperties) {
+        match kind {
+            "palette" => self.palette.apply(properties),
+            "Text" => {
+                let mut text = self.text_style();
+                text.apply(properties);
+                self.palette.text = text.color;
+                self.text_padding = text.padding;
+            }
+            "Button" => self.button.apply(properties),
+            "CheckBox" => self.checkbox.apply(properties),
+            "TextBox" => self.textbox.apply(properties),
+            "ScrollBar" => self.scrollbar.apply(properties),
+            "Image" => self.image.apply(properties),
+            "SplitView" => self.split.apply(properties),
+            "TabView" => self.tabs.apply(properties),
+            _ => {}
+        }
+    }
+}
+
+/// Color & length properties accepted by each style section
+fn kind_properties(kind: &str) -> Option<(&'static [&'static str], &'static [&'static str])> {
+    match kind {
+        "palette" => Some((Palette::properties(), Palette::lengths())),
+        "Text" => Some((TextStyle::properties(), TextStyle::lengths())),
+        "Button" => Some((ButtonStyle::properties(), ButtonStyle::lengths())),
+        "CheckBox" => Some((CheckBoxStyle::properties(), CheckBoxStyle::lengths())),
+        "TextBox" => Some((TextBoxStyle::properties(), TextBoxStyle::lengths())),
+        "ScrollBar" => Some((ScrollBarStyle::properties(), ScrollBarStyle::lengths())),
+        "Image" => Some((ImageStyle::properties(), ImageStyle::lengths())),
+        "SplitView" => Some((SplitStyle::properties(), SplitStyle::lengths())),
+        "TabView" => Some((TabStyle::properties(), TabStyle::lengths())),
+        _ => None,
+    }
+}
+
+fn parse_properties(section: &str, table: &toml::value::Table, (colors, lengths): (&[&str], &[&str])) -> Result<StyleProperties, String> {
+    let mut properties = HashMap::new();
+
+    for (name, value) in table {
+        let value = if colors.contains(&name.as_str()) {
+            StyleValue::Color(parse_color(value).map_err(|e| format!("`{}` in `[{}]`: {}", name, section, e))?)
+        } else if lengths.contains(&name.as_str()) {
+            let name = format!("{}` in `[{}]", name, section);
+            StyleValue::Length(parse_number(&name, value, u32::MAX as f64)? as u32)
+        } else {
+            let known: Vec<&str> = colors.iter().chain(lengths).copied().collect();
+            return Err(format!("Unknown property `{}` in `[{}]`; expected one of: {}", name, section, known.join(", ")));
+        };
+
+        properties.insert(name.clone(), value);
+    }
+
+    Ok(properties)
+}
+
+/// Parses a number within `0..=max` (so that it fits the setting's type)
+fn parse_number(name: &str, value: &toml::Value, max: f64) -> Result<f64, String> {
+    let number = match value {
+        toml::Value::Integer(integer) => *integer as f64,
+        toml::Value::Float(float) => *float,
+        _ => return Err(format!("`{}` must be a number", name)),
+    };
+
+    if (0.0..=max).contains(&number) {
+        Ok(number)
+    } else {
+        Err(format!("`{}` must be a number from 0 to {}, found {}", name, max, number))
+    }
+}
+
+/// Parses `"#RRGGBB"`, `"#RRGGBBAA"` or `[r, g, b]`/`[r, g, b, a]`
+fn parse_color(value: &toml::Value) -> Result<Color, String> {
+    match value {
+        toml::Value::String(hex) => {
+            let digits = hex.trim_start_matches('#');
+            if !digits.is_ascii() || !(digits.len() == 6 || digits.len() == 8) {
+                return Err(format!("expected \"#RRGGBB\" or \"#RRGGBBAA\", found \"{}\"", hex));
+            }
+
+            let mut channels = [0xff; 4];
+            for (index, channel) in channels.iter_mut().enumerate().take(digits.len() / 2) {
+                *channel = u8::from_str_radix(&digits[index * 2..index * 2 + 2], 16)
+                    .map_err(|_| format!("invalid hex color \"{}\"", hex))?;
+            }
+
+            Ok(Color::RGBA(channels[0], channels[1], channels[2], channels[3]))
+        }
+
+        toml::Value::Array(values) if values.len() == 3 || values.len() == 4 => {
+            let mut channels = [0xff; 4];
+            for (channel, value) in channels.iter_mut().zip(values) {
+                match value.as_integer() {
+                    Some(component) if (0..=255).contains(&component) => *channel = component as u8,
+                    _ => return Err(String::from("color components must be integers from 0 to 255")),
+                }
+            }
+
+            Ok(Color::RGBA(channels[0], channels[1], channels[2], channels[3]))
+        }
+
+        _ => Err(String::from("expected a hex string or an array of 3 or 4 integers")),
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+// ========================== Current Theme ========================== //
+
+thread_local! {
+    /// Theme used by widgets & views created on this (UI) thread
+    static CURRENT_THEME: RefCell<Theme> = RefCell::new(Theme::default());
+}
+
+/// Replace the current theme
+/// - Affects widgets & views created afterwards
+pub fn set_current(theme: Theme) {
+    CURRENT_THEME.with(|current| *current.borrow_mut() = theme);
+}
+
+/// A copy of the current theme
+pub fn current() -> Theme {
+    CURRENT_THEME.with(|current| current.borrow().clone())
+}
+
+/// Read the current theme without copying it
+pub fn with_current<R, F: FnOnce(&Theme) -> R>(read_fn: F) -> R {
+    CURRENT_THEME.with(|current| read_fn(&current.borrow()))
+}
+
+// ========================== ThemeWatcher Struct ========================== //
+
+/// Reloads a style file when it is modified (see `Window::set_theme_file`)
+pub struct ThemeWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+}
+
+impl ThemeWatcher {
+    /// How often the file's modification time is checked
+    const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let modified = modified_time(&path);
+
+        ThemeWatcher {
+            path,
+            modified,
+            last_check: Instant::now(),
+        }
+    }
+
+    /// Returns the reloaded theme if the file changed since the last call
+    /// - Errors (such as a half-saved file) are returned so they can be reported; the file is checked again once modified
+    pub fn poll(&mut self) -> Option<Result<Theme, String>> {
+        if self.last_check.elapsed() < Self::CHECK_INTERVAL {
+            return None;
+        }
+        self.last_check = Instant::now();
+
+        let modified = modified_time(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+
+        Some(Theme::from_file(&self.path))
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view_components::widgets::{Button, CheckBox, TextBox, Widget};
+
+    #[test]
+    fn default_theme_is_dark() {
+        assert!(Theme::default() == Theme::dark());
+        assert!(Theme::light() != Theme::dark());
+        assert_eq!(Theme::dark().button.base, colors::MANILLA);
+        assert_eq!(Theme::dark().palette.background, colors::DARK_PURPLE);
+    }
+
+    #[test]
+    fn widgets_use_the_current_theme() {
+        set_current(Theme::light());
+        let button: Button<()> = Button::new("themed");
+        set_current(Theme::default());
+
+        assert_eq!(button.passive_color, Theme::light().button.base);
+        assert_eq!(button.hover_color, Theme::light().button.hover);
+
+        // Changing the theme does not affect existing widgets
+        assert_eq!(button.passive_color, Theme::light().button.base);
+    }
+
+    #[test]
+    fn builder_methods_override_the_theme() {
+        set_current(Theme::light());
+        let button: Button<()> = Button::new("custom").with_base_color(colors::BLACK);
+        set_current(Theme::default());
+
+        assert_eq!(button.passive_color, colors::BLACK);
+        assert_eq!(button.hover_color, Theme::light().button.hover);
+    }
+
+    #[test]
+    fn empty_style_file_is_the_dark_theme() {
+        assert!(Theme::from_toml_str("").unwrap() == Theme::dark());
+        assert!(Theme::from_toml_str("base = \"light\"").unwrap() == Theme::light());
+    }
+
+    #[test]
+    fn style_file_overrides() {
+        let theme = Theme::from_toml_str(r##"
+            base = "light"
+            spacing = 6
+            corner_radius = 4.0
+            font_size = 18
+
+            [palette]
+            background = "#10203040"
+
+            [Text]
+            color = [1, 2, 3]
+
+            [Button]
+            base = "#F0F0C8"
+
+            ["Button#Reset"]
+            hover = [255, 80, 80, 128]
+        "##).unwrap();
+
+        assert_eq!(theme.spacing, 6);
+        assert_eq!(theme.corner_radius, 4);
+        assert_eq!(theme.font.point_size, 18);
+        assert_eq!(theme.palette.background, Color::RGBA(0x10, 0x20, 0x30, 0x40));
+        assert_eq!(theme.palette.text, Color::RGB(1, 2, 3));
+        assert_eq!(theme.button.base, Color::RGB(0xF0, 0xF0, 0xC8));
+        assert_eq!(theme.button.hover, Theme::light().button.hover);
+
+        // Id styles apply only to the widget with that id
+        let reset = theme.styled(&theme.button, "Button", "Reset");
+        assert_eq!(reset.hover, Color::RGBA(255, 80, 80, 128));
+        assert_eq!(reset.base, theme.button.base);
+        assert!(theme.styled(&theme.button, "Button", "Other") == theme.button);
+    }
+
+    #[test]
+    fn style_file_errors() {
+        let errors = [
+            "base = \"sepia\"",
+            "spacing = -1",
+            "spacing = \"wide\"",
+            "font_size = 70000",
+            "unknown = 1",
+            "[Slider]\nbase = \"#FFFFFF\"",
+            "[Button]\nborder = \"#FFFFFF\"",
+            "[Button]\nbase = \"#FFF\"",
+            "[Button]\nbase = \"#ééé\"",
+            "[Button]\nbase = [256, 0, 0]",
+            "[Button]\npadding = 4",
+            "[TextBox]\npadding = \"wide\"",
+            "[TextBox]\npadding = -1",
+            "[CheckBox]\nlabel = 4",
+            "[palette",
+        ];
+
+        for contents in errors.iter() {
+            assert!(Theme::from_toml_str(contents).is_err(), "`{}` should be rejected", contents);
+        }
+    }
+
+    #[test]
+    fn style_file_paddings() {
+        let theme = Theme::from_toml_str(r##"
+            [Text]
+            padding = 4
+
+            [CheckBox]
+            padding = 2
+
+            ["TextBox#Search"]
+            padding = 8
+            cursor = "#FF0000"
+        "##).unwrap();
+
+        assert_eq!(theme.text_padding, 4);
+        assert_eq!(theme.text_style().color, Theme::dark().palette.text);
+        assert_eq!(theme.checkbox.padding, 2);
+        assert_eq!(theme.textbox.padding, Theme::dark().textbox.padding);
+
+        let search = theme.styled(&theme.textbox, "TextBox", "Search");
+        assert_eq!(search.padding, 8);
+        assert_eq!(search.cursor, Color::RGB(255, 0, 0));
+        assert_eq!(search.background, theme.textbox.background);
+    }
+
+    #[test]
+    fn widgets_read_paddings() {
+        set_current(Theme::from_toml_str("[CheckBox]\npadding = 30\n\n[\"TextBox#Search\"]\npadding = 12").unwrap());
+        let mut checkbox: CheckBox<()> = CheckBox::new("check", false).with_text("Label");
+        let mut search: TextBox<()> = TextBox::new("Search", "query");
+        let mut other: TextBox<()> = TextBox::new("Other", "query");
+        set_current(Theme::default());
+
+        // The label follows the box (20) & padding
+        checkbox.assign_text_dimensions((50, 20));
+        assert_eq!(checkbox.rect().width(), crate::scale::px(20) + crate::scale::px(30) + 50);
+
+        search.place(100, 0);
+        other.place(100, 0);
+        assert_eq!(search.text_component().unwrap().rect().x(), 100 + crate::scale::px_i32(12));
+        assert_eq!(other.text_component().unwrap().rect().x(), 100);
+    }
+}
@@ -1,9 +1,14 @@
 extern crate sdl2;
 use sdl2::ttf;
 
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
 use crate::font::Fonts;
 
 use crate::view_components::{ViewComponent, IntoViewComponent, Padding};
+use crate::view_components::theme;
+use crate::scale;
 use crate::view_components::widgets::Widget;
 use crate::view_components::components::Component;
 use crate::view_components::views::{View, ViewData, Alignment};
@@ -12,11 +17,32 @@ use crate::view_components::views::{View, ViewData, Alignment};
 pub struct HStack<T> {
     data: ViewData<T>,
     padding: Padding,
+    /// Space between components
+    spacing: u32,
+    /// X position each component was placed at (relative to the view)
+    offsets: Vec<i32>,
+    /// Dimensions before `init` (0 unless fixed), as `init` assigns the drawn size
+    requested_size: Option<(u32, u32)>,
 }
 
 impl<T> HStack<T> {
-    pub fn new(components: Vec<ViewComponent<T>>) -> Self {
-        HStack {
+    /// Stacks the components horizontally (left to right)
+    pub fn new(mut components: Vec<ViewComponent<T>>) -> Self {
+        let spacing = scale::px(theme::with_current(|theme| theme.spacing));
+
+        // Absent optional items take up no space
+        components.retain(|component| !matches!(component, ViewComponent::Empty));
+
+        for component in &mut components {
+            if let ViewComponent::Widget(widget) = component {
+                widget.place(0, 0);
+            }
+        }
+
+        let mut stack = HStack {
+            spacing,
+            offsets: vec![0; components.len()],
+            requested_size: None,
             data:
                 ViewData {
                     component_map: std::collections::HashMap::new(),
@@ -28,17 +54,49 @@ impl<T> HStack<T> {
                 },
             padding:
                 Padding {
-                    left: 10,
-                    right: 10,
-                    top: 10,
-                    bottom: 10,
+                    left: spacing,
+                    right: spacing,
+                    top: spacing,
+                    bottom: spacing,
                 },
+        };
+
+        // Positions are estimated until text is measured (see `init`)
+        stack.stack();
+        stack
+    }
+
+    /// Position the components left to right according to their current widths
+    /// - Components are moved relative to where they were last placed, so translations of the view are kept
+    fn stack(&mut self) {
+        let spacing = self.spacing as i32;
+        let mut current_x = spacing;
+
+        for (component, offset) in self.data.components.iter_mut().zip(self.offsets.iter_mut()) {
+            let dx = current_x - *offset;
+            *offset = current_x;
+
+            match component {
+                ViewComponent::Widget(widget) => {
+                    widget.translate(dx, 0);
+                    current_x += widget.draw_width() as i32 + spacing;
+                }
+
+                ViewComponent::View(subview) => {
+                    subview.translate(dx, 0);
+                    current_x += subview.draw_width() as i32 + spacing;
+                }
+
+                _ => {}
+            }
         }
     }
 }
 
 impl<T> View<T> for HStack<T> {
     fn init(&mut self, ttf_context: &ttf::Sdl2TtfContext) {
+        self.requested_size = Some((self.data.view_width, self.data.view_height));
+
         // TODO: How to extend this lifetime and implement for text rendering?
         let mut font_manager = Fonts::new();
 
@@ -46,10 +104,17 @@ impl<T> View<T> for HStack<T> {
         for item in &mut self.data.components {
             match item {
                 ViewComponent::Widget(widget) => {
+                    // Size font-dependent content (such as `RichText`)
+                    widget.measure(ttf_context);
                     // If the widget has a text component, obtain its surface size
                     if let Some(text_component) = widget.text_component() {
+                        // Unchanged text was already measured during reconciliation
+                        if text_component.is_measured() {
+                            continue;
+                        }
+
                         font_manager.load_font(ttf_context, &text_component.font);
-                        let text_surface_size = font_manager.size_surface(&text_component.font, &text_component.text);
+                        let text_surface_size = font_manager.size_text(&text_component.font, &text_component.text, &text_component.wrap);
                         widget.assign_text_dimensions(text_surface_size);
                     }
                 }
@@ -62,6 +127,9 @@ impl<T> View<T> for HStack<T> {
             }
         }
 
+        // Measured widths (such as text) differ from the estimates used by `new`
+        self.stack();
+
         // Assign view dimensions if not defined
         if self.data.view_width == 0 {
             self.data.view_width = self.draw_width();
@@ -81,6 +149,17 @@ impl<T> View<T> for HStack<T> {
         (self.data.view_width, self.data.view_height)
     }
 
+    fn layout_hash(&self) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+
+        self.requested_size.unwrap_or((self.data.view_width, self.data.view_height)).hash(&mut hasher);
+        (self.padding.left, self.padding.right, self.padding.top, self.padding.bottom).hash(&mut hasher);
+        self.spacing.hash(&mut hasher);
+        self.data.alignment.hash(&mut hasher);
+
+        Some(hasher.finish())
+    }
+
     fn widgets_mut(&mut self) -> Vec<&mut Box<dyn Widget<T>>> {
         let mut widgets = Vec::new();
 
@@ -111,6 +190,10 @@ impl<T> View<T> for HStack<T> {
         widgets
     }
 
+    fn components_mut(&mut self) -> Vec<&mut ViewComponent<T>> {
+        self.data.components.iter_mut().collect()
+    }
+
     fn child_comps(&self) -> Vec<&Box<dyn Component<T>>> {
         let mut comps = Vec::new();
 
@@ -153,26 +236,26 @@ impl<T> View<T> for HStack<T> {
     }
 
     fn fixed_width(mut self, width: u32) -> Self {
-        self.data.view_width = width;
+        self.data.view_width = scale::px(width);
         self
     }
 
     fn fixed_height(mut self, height: u32) -> Self {
-        self.data.view_height = height;
+        self.data.view_height = scale::px(height);
         self
     }
 
     fn fixed_size(mut self, width: u32, height: u32) -> Self {
-        self.data.view_width = width;
-        self.data.view_height = height;
+        self.data.view_width = scale::px(width);
+        self.data.view_height = scale::px(height);
         self
     }
 
     fn padding(mut self, left: u32, right: u32, top: u32, bottom: u32) -> Self {
-        self.padding.left = left;
-        self.padding.right = right;
-        self.padding.top = top;
-        self.padding.bottom = bottom;
+        self.padding.left = scale::px(left);
+        self.padding.right = scale::px(right);
+        self.padding.top = scale::px(top);
+        self.padding.bottom = scale::px(bottom);
         self
     }
 
@@ -284,39 +367,15 @@ impl<T> IntoViewComponent<T> for HStack<T> where T: 'static {
     }
 }
 
+/// Horizontally stacked view. See `__view_components!` for supported items.
 #[macro_export]
 macro_rules! HStack {
-    ( $($x:expr), + ) => {
+    ( $($items:tt)+ ) => {
         {
             macro_imports!();
 
             let mut components = Vec::new();
-
-            let default_padding = 10;
-
-            let mut current_x = default_padding;
-            
-            $(
-                let mut component = $x.as_component();
-
-                match &mut component {
-                    // FIXME: Placement needs to occur in the init function
-                    ViewComponent::Widget(widget) => {
-                        widget.place(current_x, 0);
-
-                        current_x += widget.draw_width() as i32 + default_padding;
-                    }
-
-                    ViewComponent::View(subview) => {
-                        subview.translate(current_x, 0);
-                        
-                        current_x += subview.draw_width() as i32 + default_padding;
-                    }
-                    _ => {}
-                }
-
-                components.push(component);                
-            )+
+            $crate::__view_components!(@push components; $($items)+);
 
             HStack::new(components)
         }
@@ -3,10 +3,14 @@
 pub mod vstack;
 pub mod hstack;
 pub mod overlay;
+pub mod split;
+pub mod tabs;
 
 pub use vstack::VStack;
 pub use hstack::HStack;
 pub use overlay::Overlay;
+pub use split::SplitView;
+pub use tabs::TabView;
 
 /*
     View functionality
@@ -18,6 +22,7 @@ pub use overlay::Overlay;
 
 extern crate sdl2;
 use sdl2::ttf;
+use sdl2::rect::Rect;
 
 use std::collections::HashMap;
 
@@ -33,13 +38,27 @@ use crate::view_components::components::Component;
 /// * `Left` - Align each widget to the left within its view (default)
 /// * `Center` - Center each widget within its view
 /// * `Right` - TODO: This
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Alignment {
     Center,
     Left,
     Right,
 }
 
+/// Vertical placement of content within its bounds
+#[derive(Clone, Copy)]
+pub enum VerticalAlignment {
+    Top,
+    Center,
+    Bottom,
+}
+
+
+/// Areas that widgets are clipped to, by widget id (see `View::widget_clips`)
+/// - `None` when a widget is clipped entirely
+/// - Widgets without an entry are drawn unclipped
+pub type WidgetClips = HashMap<&'static str, Option<Rect>>;
+
 
 // ========================== View Trait ========================== //
 
@@ -69,7 +88,37 @@ pub trait View<T> {
 
     /// Obtain mutable references to *all* nested widgets
     fn child_widgets_mut(&mut self) -> Vec<&mut Box<dyn Widget<T>>>;
+
+    /// Obtain mutable references to the view's direct children (including hidden ones)
+    fn components_mut(&mut self) -> Vec<&mut ViewComponent<T>>;
+
+    /// Name of the view's type (such as `"VStack"`) for diagnostics & matching
+    fn kind(&self) -> &'static str {
+        crate::view_components::ids::short_type_name(std::any::type_name::<Self>())
+    }
     
+    /// Hash of everything other than its children's sizes that determines the view's layout
+    /// (such as padding & fixed dimensions), equal before & after `init`
+    /// - Used to keep the layout of unchanged subtrees when views are regenerated (see `reconcile::patch`)
+    /// - `None` (default) when the view is always laid out again
+    fn layout_hash(&self) -> Option<u64> {
+        None
+    }
+
+    /// Clip areas of nested widgets (such as the panes of a `SplitView`)
+    /// - Call after `init`, once widget ids are assigned
+    fn widget_clips(&mut self) -> WidgetClips {
+        let mut clips = WidgetClips::new();
+
+        for component in self.components_mut() {
+            if let ViewComponent::View(subview) = component {
+                clips.extend(subview.widget_clips());
+            }
+        }
+
+        clips
+    }
+
     // TODO: rename/fix this
     fn child_comps(&self) -> Vec<&Box<dyn Component<T>>>;
     
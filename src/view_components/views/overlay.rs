@@ -11,6 +11,8 @@ use crate::Alignment;
 use crate::Component;
 
 use crate::font::Fonts;
+use crate::view_components::theme;
+use crate::scale;
 
 use crate::widgets::Button;
 
@@ -39,6 +41,7 @@ pub struct Overlay<T> {
 }
 
 impl<T> Overlay<T> {
+    /// Stacks the components vertically above the rest of the window
     pub fn new(mut components: Vec<ViewComponent<T>>) -> Self 
     where T: 'static {
         // components.push(Button::new("__overlayButton")
@@ -47,8 +50,39 @@ impl<T> Overlay<T> {
         //                     .as_component()
         // );
 
+        let default_padding = scale::px(theme::with_current(|theme| theme.spacing)) as i32;
+        let mut current_y = default_padding;
+
+        // Absent optional items take up no space
+        components.retain(|component| !matches!(component, ViewComponent::Empty));
+
+        for component in &mut components {
+            match component {
+                // FIXME: Placement needs to occur in the init function
+                ViewComponent::Widget(widget) => {
+                    // TODO: Account for padding here?
+                    widget.place(0, current_y);
+
+                    current_y += widget.draw_height() as i32 + default_padding;
+                }
+
+                ViewComponent::View(subview) => {
+                    subview.translate(0, current_y);
+
+                    current_y += subview.draw_height() as i32 + default_padding;
+                }
+
+                ViewComponent::Component(comp) => {
+                    comp.place(0, current_y);
+                    current_y += comp.draw_height() as i32 + default_padding;
+                }
+
+                ViewComponent::Empty => {}
+            }
+        }
+
         Overlay {
-            overlay_color: Color::RGBA(0, 0, 0, 180),
+            overlay_color: theme::with_current(|theme| theme.palette.overlay),
             data: ViewData {
                 component_map: std::collections::HashMap::new(),
                 components: components,
@@ -91,10 +125,17 @@ impl<T> View<T> for Overlay<T> {
         for item in &mut self.data.components {
             match item {
                 ViewComponent::Widget(widget) => {
+                    // Size font-dependent content (such as `RichText`)
+                    widget.measure(ttf_context);
                     // If the widget has a text component, obtain its surface size
                     if let Some(text_component) = widget.text_component() {
+                        // Unchanged text was already measured during reconciliation
+                        if text_component.is_measured() {
+                            continue;
+                        }
+
                         font_manager.load_font(ttf_context, &text_component.font);
-                        let text_surface_size = font_manager.size_surface(&text_component.font, &text_component.text);
+                        let text_surface_size = font_manager.size_text(&text_component.font, &text_component.text, &text_component.wrap);
                         widget.assign_text_dimensions(text_surface_size);
                     }
                 }
@@ -253,6 +294,10 @@ impl<T> View<T> for Overlay<T> {
         widgets
     }
 
+    fn components_mut(&mut self) -> Vec<&mut ViewComponent<T>> {
+        self.data.components.iter_mut().collect()
+    }
+
     fn child_comps(&self) -> Vec<&Box<dyn Component<T>>> {
         let mut comps = Vec::new();
 
@@ -302,43 +347,15 @@ impl<T> IntoViewComponent<T> for Overlay<T> where T: 'static {
     }
 }
 
+/// Vertically stacked overlay. See `__view_components!` for supported items.
 #[macro_export]
 macro_rules! VOverlay {
-    ( $($x:expr), + ) => {
+    ( $($items:tt)+ ) => {
         {
             macro_imports!();
 
             let mut components = Vec::new();
-            // let mut vstack = VStack::new(components);
-            let default_padding = 10;
-
-            let mut current_y = default_padding;
-            $(
-                let mut component = $x.as_component();
-
-                match &mut component {
-                    // FIXME: Placement needs to occur in the init function
-                    ViewComponent::Widget(widget) => {
-                        // TODO: Account for padding here?
-                        widget.place(0, current_y);
-
-                        current_y += widget.draw_height() as i32 + default_padding;
-                    }
-
-                    ViewComponent::View(subview) => {
-                        subview.translate(0, current_y);
-                        
-                        current_y += subview.draw_height() as i32 + default_padding;
-                    }
-
-                    ViewComponent::Component(comp) => {
-                        comp.place(0, current_y);
-                        current_y += comp.draw_height() as i32 + default_padding;
-                    }
-                }
-
-                components.push(component);
-            )+
+            $crate::__view_components!(@push components; $($items)+);
 
             Overlay::new(components)
         }
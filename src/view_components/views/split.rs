@@ -0,0 +1,683 @@
+extern crate sdl2;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::event::Event;
+use sdl2::mouse::MouseButton;
+use sdl2::ttf;
+
+use crate::view_components::{ViewComponent, IntoViewComponent, Orientation};
+use crate::view_components::widgets::{Widget, WidgetState, Text, GenerateView, Update};
+use crate::view_components::components::Component;
+use crate::view_components::views::{View, ViewData, Alignment, Overlay, WidgetClips};
+use crate::backend::system::window::Window;
+use crate::font::Fonts;
+use crate::view_components::theme;
+use crate::scale;
+
+/*
+    SplitView hosts exactly two panes separated by a draggable divider bar.
+
+    Like `ScrollBar`, the split ratio is owned by the user's state:
+    dragging the bar reports a new ratio through `on_ratio_changed`,
+    and the regenerated view is built with that ratio via `with_ratio`.
+
+    Each pane is clipped to its length (see `View::widget_clips`),
+    so content larger than its pane does not overlap the divider or the other pane.
+*/
+
+/// Index of the first pane within `ViewData::components`
+const FIRST_PANE: usize = 0;
+/// Index of the second pane within `ViewData::components`
+const SECOND_PANE: usize = 1;
+
+/// Called with the new split ratio
+type RatioCallback<T> = Box<dyn Fn(&mut T, f32)>;
+
+/// Two views side-by-side (`Orientation::Horizontal`) or stacked (`Orientation::Vertical`)
+/// separated by a draggable divider
+pub struct SplitView<T> {
+    id: &'static str,
+    x: i32,
+    y: i32,
+
+    orientation: Orientation,
+    /// Fraction of the available length given to the first pane
+    ratio: f32,
+    /// Ratio restored when expanding a collapsed pane
+    default_ratio: f32,
+    /// Minimum pixel length of (first pane, second pane)
+    min_sizes: (u32, u32),
+    /// Whether double-clicking the divider collapses the first pane
+    collapsible: bool,
+    bar_thickness: u32,
+
+    bar_color: Color,
+    bar_hover_color: Color,
+    bar_drag_color: Color,
+
+    on_ratio_changed: Option<RatioCallback<T>>,
+
+    /// Panes followed by the divider (after init) and any overlays
+    data: ViewData<T>,
+}
+
+impl<T> SplitView<T> {
+    /// - `id`: Id of the divider bar (the only widget owned by the view itself)
+    /// - `first`: Left or top pane
+    /// - `second`: Right or bottom pane
+    pub fn new<A, B>(id: &'static str, orientation: Orientation, first: A, second: B) -> Self
+    where A: IntoViewComponent<T>, B: IntoViewComponent<T> {
+        let style = theme::with_current(|theme| theme.styled(&theme.split, "SplitView", id));
+
+        SplitView {
+            id,
+            x: 0,
+            y: 0,
+
+            orientation,
+            ratio: 0.5,
+            default_ratio: 0.5,
+            min_sizes: (0, 0),
+            collapsible: false,
+            bar_thickness: scale::px(6),
+
+            bar_color: style.bar,
+            bar_hover_color: style.bar_hover,
+            bar_drag_color: style.bar_drag,
+
+            on_ratio_changed: None,
+
+            data: ViewData {
+                component_map: std::collections::HashMap::new(),
+                components: vec![first.as_component(), second.as_component()],
+                view_width: scale::px(400),
+                view_height: scale::px(300),
+                fixed_size: true,
+                alignment: Alignment::Left,
+            },
+        }
+    }
+
+    /// Fraction (0.0 - 1.0) of the view given to the first pane
+    pub fn with_ratio(mut self, ratio: f32) -> Self {
+        self.ratio = ratio.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Minimum pixel length of each pane along the split direction
+    pub fn with_min_sizes(mut self, first: u32, second: u32) -> Self {
+        self.min_sizes = (scale::px(first), scale::px(second));
+        self
+    }
+
+    /// Double-clicking the divider collapses the first pane.
+    /// Double-clicking again restores `default_ratio`.
+    pub fn collapsible(mut self, default_ratio: f32) -> Self {
+        self.collapsible = true;
+        self.default_ratio = default_ratio.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Width (or height) of the divider bar
+    pub fn with_bar_thickness(mut self, thickness: u32) -> Self {
+        self.bar_thickness = scale::px(thickness);
+        self
+    }
+
+    /// Assign divider bar colors (base, hover, dragging)
+    pub fn with_bar_colors(mut self, base: Color, hover: Color, drag: Color) -> Self {
+        self.bar_color = base;
+        self.bar_hover_color = hover;
+        self.bar_drag_color = drag;
+        self
+    }
+
+    /// Called with the new ratio whenever the divider is dragged or collapsed
+    pub fn with_on_ratio_changed<F: 'static + Fn(&mut T, f32)>
+    (mut self, callback: F) -> Self {
+        self.on_ratio_changed = Some(Box::new(callback));
+        self
+    }
+
+    /// Send the message created by `to_message` to the state's `update` function when the ratio changes
+    pub fn with_on_ratio_changed_message<F: 'static + Fn(f32) -> T::Message>
+    (self, to_message: F) -> Self
+    where T: Update {
+        self.with_on_ratio_changed(move |state: &mut T, ratio| state.update(to_message(ratio)))
+    }
+
+    /// Length of the view along the split direction
+    fn split_length(&self) -> u32 {
+        match self.orientation {
+            Orientation::Horizontal => self.data.view_width,
+            Orientation::Vertical => self.data.view_height,
+        }
+    }
+
+    /// Whether the first pane is currently hidden
+    fn is_collapsed(&self) -> bool {
+        self.collapsible && self.ratio == 0.0
+    }
+
+    /// Pixel length of the first pane
+    fn first_length(&self) -> u32 {
+        if self.is_collapsed() {
+            return 0;
+        }
+
+        pane_length(self.ratio, self.split_length(), self.bar_thickness, self.min_sizes)
+    }
+
+    /// Areas of the (first, second) panes (`None` when a pane has no length)
+    fn pane_rects(&self) -> [Option<Rect>; 2] {
+        let first_length = self.first_length();
+        let second_start = first_length + self.bar_thickness;
+        let second_length = self.split_length().saturating_sub(second_start);
+
+        let pane = |start: u32, length: u32| {
+            if length == 0 {
+                return None;
+            }
+
+            Some(match self.orientation {
+                Orientation::Horizontal => Rect::new(self.x + start as i32, self.y, length, self.data.view_height),
+                Orientation::Vertical => Rect::new(self.x, self.y + start as i32, self.data.view_width, length),
+            })
+        };
+
+        [pane(0, first_length), pane(second_start, second_length)]
+    }
+
+    /// Whether the component at `index` should be drawn and receive events
+    fn is_visible(&self, index: usize) -> bool {
+        !(index == FIRST_PANE && self.is_collapsed())
+    }
+}
+
+/// Converts a ratio into the first pane's length, respecting both minimum sizes
+fn pane_length(ratio: f32, total: u32, bar: u32, min_sizes: (u32, u32)) -> u32 {
+    let available = total.saturating_sub(bar);
+    let length = (available as f32 * ratio) as u32;
+
+    let max_length = available.saturating_sub(min_sizes.1);
+    length.max(min_sizes.0).min(max_length)
+}
+
+impl<T> View<T> for SplitView<T> where T: 'static {
+    fn init(&mut self, ttf_context: &ttf::Sdl2TtfContext) {
+        let mut font_manager = Fonts::new();
+
+        for item in &mut self.data.components {
+            match item {
+                ViewComponent::View(nested_view) => {
+                    nested_view.init(ttf_context);
+                }
+                ViewComponent::Widget(widget) => {
+                    // Panes may be single widgets
+                    // Size font-dependent content (such as `RichText`)
+                    widget.measure(ttf_context);
+                    if let Some(text_component) = widget.text_component() {
+                        // Unchanged text was already measured during reconciliation
+                        if text_component.is_measured() {
+                            continue;
+                        }
+
+                        font_manager.load_font(ttf_context, &text_component.font);
+                        let text_surface_size = font_manager.size_text(&text_component.font, &text_component.text, &text_component.wrap);
+                        widget.assign_text_dimensions(text_surface_size);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Shift the second pane past the first pane and divider
+        let first_length = self.first_length();
+        let offset = (first_length + self.bar_thickness) as i32;
+
+        let bar = match self.orientation {
+            Orientation::Horizontal => {
+                translate_component(&mut self.data.components[SECOND_PANE], offset, 0);
+                Rect::new(self.x + first_length as i32, self.y, self.bar_thickness, self.data.view_height)
+            }
+            Orientation::Vertical => {
+                translate_component(&mut self.data.components[SECOND_PANE], 0, offset);
+                Rect::new(self.x, self.y + first_length as i32, self.data.view_width, self.bar_thickness)
+            }
+        };
+
+        let divider = SplitDivider {
+            id: self.id,
+            bar,
+            orientation: self.orientation,
+            origin: match self.orientation {
+                Orientation::Horizontal => self.x,
+                Orientation::Vertical => self.y,
+            },
+            total_length: self.split_length(),
+            min_sizes: self.min_sizes,
+            collapsible: self.collapsible,
+            collapsed: self.is_collapsed(),
+            default_ratio: self.default_ratio,
+            color: self.bar_color,
+            hover_color: self.bar_hover_color,
+            drag_color: self.bar_drag_color,
+            on_ratio_changed: self.on_ratio_changed.take(),
+        };
+
+        self.data.components.push(divider.as_component());
+    }
+
+    fn translate(&mut self, dx: i32, dy: i32) {
+        self.x += dx;
+        self.y += dy;
+
+        for component in &mut self.data.components {
+            translate_component(component, dx, dy);
+        }
+    }
+
+    fn align(&mut self) {
+        // Panes are laid out by `init`
+    }
+
+    fn draw_width(&self) -> u32 {
+        self.data.view_width
+    }
+
+    fn draw_height(&self) -> u32 {
+        self.data.view_height
+    }
+
+    fn view_size(&self) -> (u32, u32) {
+        (self.data.view_width, self.data.view_height)
+    }
+
+    fn widgets_mut(&mut self) -> Vec<&mut Box<dyn Widget<T>>> {
+        let mut widgets = Vec::new();
+
+        for component in &mut self.data.components {
+            if let ViewComponent::Widget(widget) = component {
+                widgets.push(widget);
+            }
+        }
+
+        widgets
+    }
+
+    fn widgets(&self) -> Vec<&Box<dyn Widget<T>>> {
+        let mut widgets = Vec::new();
+
+        for component in &self.data.components {
+            if let ViewComponent::Widget(widget) = component {
+                widgets.push(widget);
+            }
+        }
+
+        widgets
+    }
+
+    fn child_widgets_mut(&mut self) -> Vec<&mut Box<dyn Widget<T>>> {
+        let mut widgets = Vec::new();
+        let hide_first = !self.is_visible(FIRST_PANE);
+
+        for (index, component) in self.data.components.iter_mut().enumerate() {
+            if index == FIRST_PANE && hide_first {
+                continue;
+            }
+
+            match component {
+                ViewComponent::Widget(widget) => {
+                    widgets.push(widget);
+                }
+                ViewComponent::View(subview) => {
+                    widgets.append(&mut subview.child_widgets_mut());
+                }
+                _ => {}
+            }
+        }
+
+        widgets
+    }
+
+    fn components_mut(&mut self) -> Vec<&mut ViewComponent<T>> {
+        self.data.components.iter_mut().collect()
+    }
+
+    /// Each pane's widgets are clipped to the pane (& to any clip within it, such as a nested split)
+    fn widget_clips(&mut self) -> WidgetClips {
+        let panes = self.pane_rects();
+        let mut clips = WidgetClips::new();
+
+        for (index, pane) in [FIRST_PANE, SECOND_PANE].iter().zip(panes.iter()) {
+            match &mut self.data.components[*index] {
+                ViewComponent::Widget(widget) => {
+                    clips.insert(widget.id(), *pane);
+                }
+                ViewComponent::View(subview) => {
+                    let nested = subview.widget_clips();
+
+                    for widget in subview.child_widgets_mut() {
+                        let clip = match nested.get(widget.id()) {
+                            Some(inner) => inner.and_then(|inner| pane.and_then(|pane| inner.intersection(pane))),
+                            None => *pane,
+                        };
+                        clips.insert(widget.id(), clip);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        clips
+    }
+
+    fn child_comps(&self) -> Vec<&Box<dyn Component<T>>> {
+        let mut comps = Vec::new();
+
+        for (index, component) in self.data.components.iter().enumerate() {
+            if !self.is_visible(index) {
+                continue;
+            }
+
+            match component {
+                ViewComponent::Component(comp) => {
+                    comps.push(comp);
+                }
+                ViewComponent::View(subview) => {
+                    comps.append(&mut subview.child_comps());
+                }
+                _ => {}
+            }
+        }
+
+        comps
+    }
+
+    fn overlay(&mut self, overlay: Overlay<T>) where T: 'static {
+        self.data.components.push(overlay.as_component());
+    }
+
+    fn alignment(mut self, alignment: Alignment) -> Self {
+        self.data.alignment = alignment;
+        self
+    }
+
+    fn fixed_width(mut self, width: u32) -> Self {
+        self.data.view_width = scale::px(width);
+        self
+    }
+
+    fn fixed_height(mut self, height: u32) -> Self {
+        self.data.view_height = scale::px(height);
+        self
+    }
+
+    fn fixed_size(mut self, width: u32, height: u32) -> Self {
+        self.data.view_width = scale::px(width);
+        self.data.view_height = scale::px(height);
+        self
+    }
+
+    fn padding(self, _left: u32, _right: u32, _top: u32, _bottom: u32) -> Self {
+        // Panes fill the split view. Padding belongs to the panes themselves.
+        self
+    }
+}
+
+impl<T> IntoViewComponent<T> for SplitView<T> where T: 'static {
+    fn as_component(self) -> ViewComponent<T> {
+        ViewComponent::View(Box::new(self))
+    }
+}
+
+/// Translates any kind of view component
+fn translate_component<T>(component: &mut ViewComponent<T>, dx: i32, dy: i32) {
+    match component {
+        ViewComponent::Widget(widget) => {
+            widget.translate(dx, dy);
+        }
+        ViewComponent::View(view) => {
+            view.translate(dx, dy);
+        }
+        _ => {}
+    }
+}
+
+
+// ========================== SplitDivider Widget ========================== //
+
+/// The draggable bar created by `SplitView::init`
+pub struct SplitDivider<T> {
+    id: &'static str,
+    bar: Rect,
+    orientation: Orientation,
+
+    /// Start of the split view along the split direction
+    origin: i32,
+    total_length: u32,
+    min_sizes: (u32, u32),
+
+    collapsible: bool,
+    collapsed: bool,
+    default_ratio: f32,
+
+    color: Color,
+    hover_color: Color,
+    drag_color: Color,
+
+    on_ratio_changed: Option<RatioCallback<T>>,
+}
+
+impl<T> SplitDivider<T> {
+    /// Maps a mouse position along the split direction to a split ratio
+    fn position_to_ratio(&self, position: i32) -> f32 {
+        let thickness = self.bar_thickness();
+        let available = self.total_length.saturating_sub(thickness);
+        if available == 0 {
+            return 0.0;
+        }
+
+        // Center the bar on the cursor
+        let first_length = (position - self.origin - thickness as i32 / 2).max(0) as u32;
+        let first_length = pane_length(first_length as f32 / available as f32, self.total_length, thickness, self.min_sizes);
+
+        first_length as f32 / available as f32
+    }
+
+    fn bar_thickness(&self) -> u32 {
+        match self.orientation {
+            Orientation::Horizontal => self.bar.width(),
+            Orientation::Vertical => self.bar.height(),
+        }
+    }
+
+    fn notify(&self, state: &mut T, ratio: f32) {
+        if let Some(on_ratio_changed) = &self.on_ratio_changed {
+            (on_ratio_changed)(state, ratio);
+        }
+    }
+}
+
+impl<T> Widget<T> for SplitDivider<T> {
+    fn rect(&self) -> Rect {
+        self.bar
+    }
+
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn set_id(&mut self, id: &'static str) {
+        self.id = id;
+    }
+
+    fn text_component(&mut self) -> Option<&mut Text<T>> {
+        None
+    }
+
+    fn place(&mut self, x: i32, y: i32) {
+        self.bar.set_x(x);
+        self.bar.set_y(y);
+    }
+
+    fn update(&mut self, state: &mut T, event: &Event) {
+        match event {
+            Event::MouseButtonDown { mouse_btn: MouseButton::Left, clicks: 2, .. } if self.collapsible => {
+                let ratio = if self.collapsed { self.default_ratio } else { 0.0 };
+                self.notify(state, ratio);
+            }
+            Event::MouseMotion { x, y, .. } => {
+                let position = match self.orientation {
+                    Orientation::Horizontal => *x,
+                    Orientation::Vertical => *y,
+                };
+
+                let ratio = self.position_to_ratio(position);
+                self.notify(state, ratio);
+            }
+            _ => {}
+        }
+    }
+
+    fn render(&self, window: &mut Window<T>, widget_state: WidgetState)
+    where T: GenerateView<T> {
+        match widget_state {
+            WidgetState::Hovering => window.canvas.set_draw_color(self.hover_color),
+            WidgetState::Active => window.canvas.set_draw_color(self.drag_color),
+            _ => window.canvas.set_draw_color(self.color),
+        }
+
+        window.canvas.fill_rect(self.bar).unwrap();
+    }
+
+    fn translate(&mut self, dx: i32, dy: i32) {
+        self.bar.set_x(self.bar.x() + dx);
+        self.bar.set_y(self.bar.y() + dy);
+
+        self.origin += match self.orientation {
+            Orientation::Horizontal => dx,
+            Orientation::Vertical => dy,
+        };
+    }
+
+    fn draw_width(&self) -> u32 {
+        self.bar.width()
+    }
+
+    fn draw_height(&self) -> u32 {
+        self.bar.height()
+    }
+}
+
+impl<T> IntoViewComponent<T> for SplitDivider<T> where T: 'static {
+    fn as_component(self) -> ViewComponent<T> {
+        ViewComponent::Widget(Box::new(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view_components::widgets::Canvas;
+
+    /// 400x300 split of two oversized canvases with a 10px bar
+    fn split(orientation: Orientation) -> SplitView<f32> {
+        SplitView::new("divider", orientation, Canvas::new("first", (1000, 1000)), Canvas::new("second", (1000, 1000)))
+            .with_bar_thickness(10)
+            .with_on_ratio_changed(|state: &mut f32, ratio| *state = ratio)
+            .fixed_size(400, 300)
+    }
+
+    fn widget_rect(view: &mut SplitView<f32>, id: &str) -> Rect {
+        view.child_widgets_mut().into_iter().find(|widget| widget.id() == id).unwrap().rect()
+    }
+
+    fn divider(view: &mut SplitView<f32>) -> &mut Box<dyn Widget<f32>> {
+        match view.data.components.last_mut() {
+            Some(ViewComponent::Widget(widget)) => widget,
+            _ => panic!("Expected the divider after init"),
+        }
+    }
+
+    #[test]
+    fn pane_length_respects_min_sizes() {
+        assert_eq!(pane_length(0.5, 410, 10, (0, 0)), 200);
+        assert_eq!(pane_length(0.1, 410, 10, (100, 0)), 100);
+        assert_eq!(pane_length(0.9, 410, 10, (0, 100)), 300);
+        assert_eq!(pane_length(0.5, 5, 10, (0, 0)), 0);
+    }
+
+    #[test]
+    fn init_places_second_pane_and_divider() {
+        let ttf_context = ttf::init().unwrap();
+        let mut view = split(Orientation::Horizontal).with_ratio(0.25);
+        view.init(&ttf_context);
+
+        // (400 - 10) * 0.25
+        assert_eq!(widget_rect(&mut view, "second").x(), 107);
+        assert_eq!(widget_rect(&mut view, "divider"), Rect::new(97, 0, 10, 300));
+    }
+
+    #[test]
+    fn panes_are_clipped_to_their_lengths() {
+        let ttf_context = ttf::init().unwrap();
+        let mut view = split(Orientation::Vertical).with_min_sizes(0, 200);
+        view.init(&ttf_context);
+        view.translate(5, 5);
+
+        let clips = view.widget_clips();
+        // The second pane's minimum size limits the first pane
+        assert_eq!(clips["first"], Some(Rect::new(5, 5, 400, 90)));
+        assert_eq!(clips["second"], Some(Rect::new(5, 105, 400, 200)));
+        assert!(!clips.contains_key("divider"));
+    }
+
+    #[test]
+    fn nested_panes_are_clipped_to_both_splits() {
+        let ttf_context = ttf::init().unwrap();
+        let inner = split(Orientation::Vertical).fixed_size(1000, 300);
+        let mut view = SplitView::new("outer", Orientation::Horizontal, inner, Canvas::new("right", (10, 10)))
+            .with_bar_thickness(10)
+            .fixed_size(400, 300);
+        view.init(&ttf_context);
+
+        let clips = view.widget_clips();
+        assert_eq!(clips["first"], Some(Rect::new(0, 0, 195, 145)));
+        assert_eq!(clips["second"], Some(Rect::new(0, 155, 195, 145)));
+        // The inner divider is only clipped to the outer pane
+        assert_eq!(clips["divider"], Some(Rect::new(0, 0, 195, 300)));
+    }
+
+    #[test]
+    fn collapsed_pane_is_clipped_entirely() {
+        let ttf_context = ttf::init().unwrap();
+        let mut view = split(Orientation::Horizontal).collapsible(0.4).with_ratio(0.0);
+        view.init(&ttf_context);
+
+        let clips = view.widget_clips();
+        assert_eq!(clips["first"], None);
+        assert_eq!(clips["second"], Some(Rect::new(10, 0, 390, 300)));
+    }
+
+    #[test]
+    fn divider_reports_ratios() {
+        let ttf_context = ttf::init().unwrap();
+        let mut view = split(Orientation::Horizontal).collapsible(0.4).with_min_sizes(50, 0);
+        view.init(&ttf_context);
+
+        let mut ratio = 0.5;
+        let motion = |x| Event::MouseMotion { timestamp: 0, window_id: 0, which: 0, mousestate: sdl2::mouse::MouseState::from_sdl_state(0), x, y: 0, xrel: 0, yrel: 0 };
+
+        // The bar is centered on the cursor
+        divider(&mut view).update(&mut ratio, &motion(200));
+        assert_eq!(ratio, 195.0 / 390.0);
+        // Clamped to the first pane's minimum size
+        divider(&mut view).update(&mut ratio, &motion(0));
+        assert_eq!(ratio, 50.0 / 390.0);
+
+        let double_click = Event::MouseButtonDown { timestamp: 0, window_id: 0, which: 0, mouse_btn: MouseButton::Left, clicks: 2, x: 200, y: 0 };
+        divider(&mut view).update(&mut ratio, &double_click);
+        assert_eq!(ratio, 0.0);
+    }
+}
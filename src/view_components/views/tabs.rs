@@ -0,0 +1,684 @@
+extern crate sdl2;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::MouseButton;
+use sdl2::ttf;
+
+use crate::view_components::{ViewComponent, IntoViewComponent};
+use crate::view_components::widgets::{Widget, WidgetState, Text, GenerateView, Update};
+use crate::view_components::components::Component;
+use crate::view_components::views::{View, ViewData, Alignment, Overlay};
+use crate::backend::system::window::Window;
+use crate::font::Fonts;
+use crate::view_components::theme;
+use crate::scale;
+
+/*
+    TabView shows one of several labelled pages beneath a clickable tab strip.
+
+    The selected page is owned by the user's state (see `with_selected`) and
+    changes are reported through `on_tab_changed`, just like `ScrollBar` values.
+*/
+
+/// Called with the index of the newly selected tab
+type TabCallback<T> = Box<dyn Fn(&mut T, usize)>;
+
+/// Container displaying one page at a time, selected via a tab strip
+pub struct TabView<T> {
+    id: &'static str,
+    x: i32,
+    y: i32,
+
+    labels: Vec<String>,
+    selected: usize,
+
+    strip_height: u32,
+    /// Horizontal space between a tab's label and its edges
+    tab_padding: u32,
+
+    strip_color: Color,
+    tab_color: Color,
+    tab_hover_color: Color,
+    selected_color: Color,
+    label_color: Color,
+
+    on_tab_changed: Option<TabCallback<T>>,
+
+    /// Created during `init` once labels are sized
+    strip: Option<ViewComponent<T>>,
+
+    /// Pages (one per label) followed by any overlays
+    data: ViewData<T>,
+}
+
+impl<T> TabView<T> {
+    /// - `id`: Id of the tab strip widget
+    pub fn new(id: &'static str) -> Self {
+        let style = theme::with_current(|theme| theme.styled(&theme.tabs, "TabView", id));
+
+        TabView {
+            id,
+            x: 0,
+            y: 0,
+
+            labels: Vec::new(),
+            selected: 0,
+
+            strip_height: scale::px(36),
+            tab_padding: scale::px(12),
+
+            strip_color: style.strip,
+            tab_color: style.tab,
+            tab_hover_color: style.tab_hover,
+            selected_color: style.selected,
+            label_color: style.label,
+
+            on_tab_changed: None,
+
+            strip: None,
+
+            data: ViewData {
+                component_map: std::collections::HashMap::new(),
+                components: Vec::new(),
+                view_width: 0,
+                view_height: 0,
+                fixed_size: false,
+                alignment: Alignment::Left,
+            },
+        }
+    }
+
+    /// Append a page with the given tab label
+    pub fn with_tab<V: IntoViewComponent<T>>(mut self, label: &str, page: V) -> Self {
+        // Pages must precede overlays
+        let index = self.labels.len();
+        self.data.components.insert(index, page.as_component());
+        self.labels.push(String::from(label));
+        self
+    }
+
+    /// Index of the page to display
+    pub fn with_selected(mut self, index: usize) -> Self {
+        self.selected = index;
+        self
+    }
+
+    /// Called with the new index when a tab is clicked or Ctrl+Tab is pressed
+    /// (while the tab strip is focused or hovered)
+    pub fn with_on_tab_changed<F: 'static + Fn(&mut T, usize)>
+    (mut self, callback: F) -> Self {
+        self.on_tab_changed = Some(Box::new(callback));
+        self
+    }
+
+    /// Send the message created by `to_message` to the state's `update` function when the tab changes
+    pub fn with_on_tab_changed_message<F: 'static + Fn(usize) -> T::Message>
+    (self, to_message: F) -> Self
+    where T: Update {
+        self.with_on_tab_changed(move |state: &mut T, index| state.update(to_message(index)))
+    }
+
+    /// Height of the tab strip
+    pub fn with_strip_height(mut self, height: u32) -> Self {
+        self.strip_height = scale::px(height);
+        self
+    }
+
+    /// Assign tab colors (strip background, tab, hovered tab, selected tab)
+    pub fn with_tab_colors(mut self, strip: Color, tab: Color, hover: Color, selected: Color) -> Self {
+        self.strip_color = strip;
+        self.tab_color = tab;
+        self.tab_hover_color = hover;
+        self.selected_color = selected;
+        self
+    }
+
+    /// Assign the color of tab labels
+    pub fn with_label_color(mut self, color: Color) -> Self {
+        self.label_color = color;
+        self
+    }
+
+    /// The selected page's index, clamped to existing pages
+    fn selected_page(&self) -> usize {
+        self.selected.min(self.labels.len().saturating_sub(1))
+    }
+
+    /// Whether the component at `index` should be drawn and receive events
+    fn is_visible(&self, index: usize) -> bool {
+        index == self.selected_page() || index >= self.labels.len()
+    }
+
+    fn pages_width(&self) -> u32 {
+        let mut max_width = 0u32;
+
+        for component in &self.data.components[..self.labels.len()] {
+            let width = match component {
+                ViewComponent::Widget(widget) => widget.draw_width(),
+                ViewComponent::View(subview) => subview.draw_width(),
+                ViewComponent::Component(comp) => comp.draw_width(),
+                ViewComponent::Empty => 0,
+            };
+
+            max_width = max_width.max(width);
+        }
+
+        max_width
+    }
+
+    fn pages_height(&self) -> u32 {
+        let mut max_height = 0u32;
+
+        for component in &self.data.components[..self.labels.len()] {
+            let height = match component {
+                ViewComponent::Widget(widget) => widget.draw_height(),
+                ViewComponent::View(subview) => subview.draw_height(),
+                ViewComponent::Component(comp) => comp.draw_height(),
+                ViewComponent::Empty => 0,
+            };
+
+            max_height = max_height.max(height);
+        }
+
+        max_height
+    }
+}
+
+impl<T> View<T> for TabView<T> where T: 'static {
+    fn init(&mut self, ttf_context: &ttf::Sdl2TtfContext) {
+        let mut font_manager = Fonts::new();
+
+        for item in &mut self.data.components {
+            match item {
+                ViewComponent::Widget(widget) => {
+                    // Size font-dependent content (such as `RichText`)
+                    widget.measure(ttf_context);
+                    if let Some(text_component) = widget.text_component() {
+                        // Unchanged text was already measured during reconciliation
+                        if text_component.is_measured() {
+                            continue;
+                        }
+
+                        font_manager.load_font(ttf_context, &text_component.font);
+                        let text_surface_size = font_manager.size_text(&text_component.font, &text_component.text, &text_component.wrap);
+                        widget.assign_text_dimensions(text_surface_size);
+                    }
+                }
+
+                ViewComponent::View(nested_view) => {
+                    nested_view.init(ttf_context);
+                }
+
+                _ => {}
+            }
+        }
+
+        // Size each tab according to its label
+        let mut tabs = Vec::new();
+        let mut labels = Vec::new();
+        let mut tab_x = self.x;
+
+        for label in &self.labels {
+            let mut text = Text::new("", label).with_color(self.label_color);
+            font_manager.load_font(ttf_context, &text.font);
+            let (text_width, text_height) = font_manager.size_text(&text.font, &text.text, &text.wrap);
+            text.assign_text_dimensions((text_width, text_height));
+
+            let tab = Rect::new(tab_x, self.y, text_width + self.tab_padding * 2, self.strip_height);
+            text.container_rect = Rect::new(tab.x() + self.tab_padding as i32, tab.y(), text_width, tab.height());
+
+            tab_x += tab.width() as i32;
+            tabs.push(tab);
+            labels.push(text);
+        }
+
+        // Pages are drawn beneath the strip
+        let strip_height = self.strip_height as i32;
+        for component in &mut self.data.components[..self.labels.len()] {
+            match component {
+                ViewComponent::Widget(widget) => {
+                    widget.translate(0, strip_height);
+                }
+                ViewComponent::View(view) => {
+                    view.translate(0, strip_height);
+                }
+                _ => {}
+            }
+        }
+
+        // Assign view dimensions if not defined
+        let strip_width = (tab_x - self.x) as u32;
+        if self.data.view_width == 0 {
+            self.data.view_width = self.pages_width().max(strip_width);
+        }
+        if self.data.view_height == 0 {
+            self.data.view_height = self.pages_height() + self.strip_height;
+        }
+
+        let strip = TabStrip {
+            id: self.id,
+            rect: Rect::new(self.x, self.y, self.data.view_width, self.strip_height),
+            tabs,
+            labels,
+            selected: self.selected_page(),
+            hovered: None,
+            strip_color: self.strip_color,
+            tab_color: self.tab_color,
+            tab_hover_color: self.tab_hover_color,
+            selected_color: self.selected_color,
+            on_tab_changed: self.on_tab_changed.take(),
+        };
+
+        self.strip = Some(strip.as_component());
+    }
+
+    fn translate(&mut self, dx: i32, dy: i32) {
+        self.x += dx;
+        self.y += dy;
+
+        if let Some(ViewComponent::Widget(strip)) = &mut self.strip {
+            strip.translate(dx, dy);
+        }
+
+        for component in &mut self.data.components {
+            match component {
+                ViewComponent::Widget(widget) => {
+                    widget.translate(dx, dy);
+                }
+                ViewComponent::View(view) => {
+                    view.translate(dx, dy);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn align(&mut self) {
+        // Pages are aligned by their own views
+    }
+
+    fn draw_width(&self) -> u32 {
+        if self.data.view_width != 0 {
+            return self.data.view_width;
+        }
+
+        self.pages_width()
+    }
+
+    fn draw_height(&self) -> u32 {
+        if self.data.view_height != 0 {
+            return self.data.view_height;
+        }
+
+        self.pages_height() + self.strip_height
+    }
+
+    fn view_size(&self) -> (u32, u32) {
+        (self.data.view_width, self.data.view_height)
+    }
+
+    fn widgets_mut(&mut self) -> Vec<&mut Box<dyn Widget<T>>> {
+        let mut widgets = Vec::new();
+
+        if let Some(ViewComponent::Widget(strip)) = &mut self.strip {
+            widgets.push(strip);
+        }
+
+        for component in &mut self.data.components {
+            if let ViewComponent::Widget(widget) = component {
+                widgets.push(widget);
+            }
+        }
+
+        widgets
+    }
+
+    fn widgets(&self) -> Vec<&Box<dyn Widget<T>>> {
+        let mut widgets = Vec::new();
+
+        if let Some(ViewComponent::Widget(strip)) = &self.strip {
+            widgets.push(strip);
+        }
+
+        for component in &self.data.components {
+            if let ViewComponent::Widget(widget) = component {
+                widgets.push(widget);
+            }
+        }
+
+        widgets
+    }
+
+    fn child_widgets_mut(&mut self) -> Vec<&mut Box<dyn Widget<T>>> {
+        let mut widgets = Vec::new();
+        let selected = self.selected_page();
+        let page_count = self.labels.len();
+
+        if let Some(ViewComponent::Widget(strip)) = &mut self.strip {
+            widgets.push(strip);
+        }
+
+        for (index, component) in self.data.components.iter_mut().enumerate() {
+            // Only the selected page (and overlays) receive events
+            if index != selected && index < page_count {
+                continue;
+            }
+
+            match component {
+                ViewComponent::Widget(widget) => {
+                    widgets.push(widget);
+                }
+                ViewComponent::View(subview) => {
+                    widgets.append(&mut subview.child_widgets_mut());
+                }
+                _ => {}
+            }
+        }
+
+        widgets
+    }
+
+    /// The strip comes last, as it only exists once initialized
+    /// (reconciliation matches components by position)
+    fn components_mut(&mut self) -> Vec<&mut ViewComponent<T>> {
+        let mut components: Vec<&mut ViewComponent<T>> = self.data.components.iter_mut().collect();
+        components.extend(self.strip.iter_mut());
+        components
+    }
+
+    fn child_comps(&self) -> Vec<&Box<dyn Component<T>>> {
+        let mut comps = Vec::new();
+
+        for (index, component) in self.data.components.iter().enumerate() {
+            if !self.is_visible(index) {
+                continue;
+            }
+
+            match component {
+                ViewComponent::Component(comp) => {
+                    comps.push(comp);
+                }
+                ViewComponent::View(subview) => {
+                    comps.append(&mut subview.child_comps());
+                }
+                _ => {}
+            }
+        }
+
+        comps
+    }
+
+    fn overlay(&mut self, overlay: Overlay<T>) where T: 'static {
+        self.data.components.push(overlay.as_component());
+    }
+
+    fn alignment(mut self, alignment: Alignment) -> Self {
+        self.data.alignment = alignment;
+        self
+    }
+
+    fn fixed_width(mut self, width: u32) -> Self {
+        self.data.view_width = scale::px(width);
+        self
+    }
+
+    fn fixed_height(mut self, height: u32) -> Self {
+        self.data.view_height = scale::px(height);
+        self
+    }
+
+    fn fixed_size(mut self, width: u32, height: u32) -> Self {
+        self.data.view_width = scale::px(width);
+        self.data.view_height = scale::px(height);
+        self
+    }
+
+    fn padding(self, _left: u32, _right: u32, _top: u32, _bottom: u32) -> Self {
+        // Padding belongs to the pages themselves
+        self
+    }
+}
+
+impl<T> IntoViewComponent<T> for TabView<T> where T: 'static {
+    fn as_component(self) -> ViewComponent<T> {
+        ViewComponent::View(Box::new(self))
+    }
+}
+
+
+// ========================== TabStrip Widget ========================== //
+
+/// The row of tabs created by `TabView::init`
+pub struct TabStrip<T> {
+    id: &'static str,
+    rect: Rect,
+    tabs: Vec<Rect>,
+    labels: Vec<Text<T>>,
+    selected: usize,
+    /// Tab under the mouse (only highlighted while the strip is hovered)
+    hovered: Option<usize>,
+
+    strip_color: Color,
+    tab_color: Color,
+    tab_hover_color: Color,
+    selected_color: Color,
+
+    on_tab_changed: Option<TabCallback<T>>,
+}
+
+impl<T> TabStrip<T> {
+    fn select(&self, state: &mut T, index: usize) {
+        if index == self.selected {
+            return;
+        }
+
+        if let Some(on_tab_changed) = &self.on_tab_changed {
+            (on_tab_changed)(state, index);
+        }
+    }
+}
+
+impl<T> Widget<T> for TabStrip<T> {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn set_id(&mut self, id: &'static str) {
+        self.id = id;
+    }
+
+    fn text_component(&mut self) -> Option<&mut Text<T>> {
+        // Labels are sized by `TabView::init`
+        None
+    }
+
+    fn place(&mut self, x: i32, y: i32) {
+        let dx = x - self.rect.x();
+        let dy = y - self.rect.y();
+        self.translate(dx, dy);
+    }
+
+    /// Clicking a tab makes this strip the target of Ctrl+Tab
+    fn can_focus(&self) -> bool {
+        true
+    }
+
+    fn on_mouse_event(&mut self, _state: &mut T, event: &Event) {
+        if let Event::MouseMotion { x, y, .. } = event {
+            self.hovered = self.tabs.iter().position(|tab| tab.contains_point((*x, *y)));
+        }
+    }
+
+    fn update(&mut self, state: &mut T, event: &Event) {
+        if let Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } = event {
+            let clicked = self.tabs.iter().position(|tab| tab.contains_point((*x, *y)));
+
+            if let Some(index) = clicked {
+                self.select(state, index);
+            }
+        }
+    }
+
+    fn on_key_event(&mut self, state: &mut T, event: &Event, widget_state: &WidgetState) {
+        // Other tab strips in the window must not switch as well
+        match widget_state {
+            WidgetState::Focused | WidgetState::Hovering => {}
+            _ => return,
+        }
+
+        if let Event::KeyDown { keycode: Some(Keycode::Tab), keymod, .. } = event {
+            if !keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) || self.tabs.is_empty() {
+                return;
+            }
+
+            // Ctrl+Shift+Tab cycles backwards
+            let count = self.tabs.len();
+            let index = if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) {
+                (self.selected + count - 1) % count
+            } else {
+                (self.selected + 1) % count
+            };
+
+            self.select(state, index);
+        }
+    }
+
+    fn render(&self, window: &mut Window<T>, _widget_state: WidgetState)
+    where T: GenerateView<T> {
+        window.canvas.set_draw_color(self.strip_color);
+        window.canvas.fill_rect(self.rect).unwrap();
+
+        // A focused strip may also be hovered
+        let hovered = if window.window_state.hovering == Some(self.id) { self.hovered } else { None };
+
+        for (index, tab) in self.tabs.iter().enumerate() {
+            if index == self.selected {
+                window.canvas.set_draw_color(self.selected_color);
+            } else if hovered == Some(index) {
+                window.canvas.set_draw_color(self.tab_hover_color);
+            } else {
+                window.canvas.set_draw_color(self.tab_color);
+            }
+
+            // Leave a gap between neighboring tabs
+            let inner = Rect::new(tab.x() + 1, tab.y() + 2, tab.width().saturating_sub(2), tab.height().saturating_sub(2));
+            window.canvas.fill_rect(inner).unwrap();
+
+            self.labels[index].render(window, WidgetState::Base);
+        }
+    }
+
+    fn translate(&mut self, dx: i32, dy: i32) {
+        self.rect.offset(dx, dy);
+
+        for tab in &mut self.tabs {
+            tab.offset(dx, dy);
+        }
+
+        for label in &mut self.labels {
+            label.translate(dx, dy);
+        }
+    }
+
+    fn draw_width(&self) -> u32 {
+        self.rect.width()
+    }
+
+    fn draw_height(&self) -> u32 {
+        self.rect.height()
+    }
+}
+
+impl<T> IntoViewComponent<T> for TabStrip<T> where T: 'static {
+    fn as_component(self) -> ViewComponent<T> {
+        ViewComponent::Widget(Box::new(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view_components::widgets::Canvas;
+
+    /// Three tabs of 50px each, the second one selected
+    fn strip() -> TabStrip<usize> {
+        TabStrip {
+            id: "tabs",
+            rect: Rect::new(0, 0, 150, 30),
+            tabs: (0..3).map(|index| Rect::new(index * 50, 0, 50, 30)).collect(),
+            labels: Vec::new(),
+            selected: 1,
+            hovered: None,
+            strip_color: Color::RGB(0, 0, 0),
+            tab_color: Color::RGB(0, 0, 0),
+            tab_hover_color: Color::RGB(0, 0, 0),
+            selected_color: Color::RGB(0, 0, 0),
+            on_tab_changed: Some(Box::new(|state: &mut usize, index| *state = index)),
+        }
+    }
+
+    fn ctrl_tab(keymod: Mod) -> Event {
+        Event::KeyDown { timestamp: 0, window_id: 0, keycode: Some(Keycode::Tab), scancode: None, keymod, repeat: false }
+    }
+
+    #[test]
+    fn strip_follows_pages() {
+        let mut view = TabView::new("tabs")
+            .with_tab("First", Canvas::new("first", (10, 10)))
+            .with_tab("Second", Canvas::new("second", (10, 10)));
+        // Set by `init`
+        view.strip = Some(strip().as_component());
+
+        let ids: Vec<&str> = view.components_mut().into_iter().map(|component| match component {
+            ViewComponent::Widget(widget) => widget.id(),
+            _ => "",
+        }).collect();
+        assert_eq!(ids, vec!["first", "second", "tabs"]);
+    }
+
+    #[test]
+    fn selected_page_is_clamped() {
+        let view: TabView<usize> = TabView::new("tabs")
+            .with_tab("First", Canvas::new("first", (10, 10)))
+            .with_tab("Second", Canvas::new("second", (10, 10)))
+            .with_selected(5);
+
+        assert_eq!(view.selected_page(), 1);
+        assert!(view.is_visible(1));
+        assert!(!view.is_visible(0));
+    }
+
+    #[test]
+    fn clicking_a_tab_selects_it() {
+        let mut strip = strip();
+        let mut selected = 1;
+        let click = |x| Event::MouseButtonDown { timestamp: 0, window_id: 0, which: 0, mouse_btn: MouseButton::Left, clicks: 1, x, y: 10 };
+
+        strip.update(&mut selected, &click(120));
+        assert_eq!(selected, 2);
+        strip.update(&mut selected, &click(500));
+        assert_eq!(selected, 2);
+    }
+
+    #[test]
+    fn ctrl_tab_cycles_while_focused_or_hovered() {
+        let mut strip = strip();
+        let mut selected = 1;
+
+        strip.on_key_event(&mut selected, &ctrl_tab(Mod::LCTRLMOD), &WidgetState::Base);
+        assert_eq!(selected, 1);
+        strip.on_key_event(&mut selected, &ctrl_tab(Mod::NOMOD), &WidgetState::Focused);
+        assert_eq!(selected, 1);
+
+        strip.on_key_event(&mut selected, &ctrl_tab(Mod::LCTRLMOD), &WidgetState::Focused);
+        assert_eq!(selected, 2);
+        strip.on_key_event(&mut selected, &ctrl_tab(Mod::LCTRLMOD | Mod::LSHIFTMOD), &WidgetState::Hovering);
+        assert_eq!(selected, 0);
+    }
+}
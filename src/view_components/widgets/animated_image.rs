@@ -0,0 +1,186 @@
+extern crate sdl2;
+use sdl2::rect::Rect;
+
+use crate::Window;
+use crate::scale;
+
+use super::{Widget, WidgetState};
+use crate::view_components::{IntoViewComponent, ViewComponent};
+use crate::images::SpriteSheet;
+
+use std::any::Any;
+use std::time::Duration;
+
+
+/// Playback position, carried over when the view is regenerated
+struct Playback {
+    resource_path: &'static str,
+    elapsed: Duration,
+}
+
+type ClickCallback<T> = Box<dyn Fn(&mut T)>;
+
+/// AnimatedImage Widget
+/// - Plays the frames of a `SpriteSheet` (such as a loading indicator)
+/// - Playback continues across view regenerations when the sprite sheet is unchanged
+pub struct AnimatedImage<T> {
+    id: &'static str,
+    rect: Rect,
+    sheet: SpriteSheet,
+
+    playing: bool,
+    looping: bool,
+    /// Time played since the first frame
+    elapsed: Duration,
+
+    on_click: Option<ClickCallback<T>>,
+}
+
+impl<T> AnimatedImage<T> {
+    /// Displayed at the size of a single frame (see `with_size`)
+    pub fn new(id: &'static str, sheet: SpriteSheet) -> Self {
+        let (width, height) = sheet.frame_size();
+
+        AnimatedImage {
+            id,
+            rect: Rect::new(0, 0, scale::px(width).max(1), scale::px(height).max(1)),
+            looping: sheet.looping,
+            sheet,
+            playing: true,
+            elapsed: Duration::from_secs(0),
+            on_click: None,
+        }
+    }
+
+    /// Load the sprite sheet from a TOML descriptor (see `SpriteSheet::from_file`)
+    /// - Each descriptor is only read once (see `SpriteSheet::load_cached`)
+    pub fn from_file(id: &'static str, descriptor_path: &str) -> Result<Self, String> {
+        SpriteSheet::load_cached(descriptor_path).map(|sheet| Self::new(id, sheet))
+    }
+
+    /// Scale frames to `width` x `height`
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.rect.set_width(scale::px(width));
+        self.rect.set_height(scale::px(height));
+        self
+    }
+
+    /// Play or pause the animation (playing by default)
+    pub fn with_playing(mut self, playing: bool) -> Self {
+        self.playing = playing;
+        self
+    }
+
+    /// Start paused
+    pub fn paused(self) -> Self {
+        self.with_playing(false)
+    }
+
+    /// Restart after the last frame, or hold it (defaults to the sprite sheet's `looping`)
+    pub fn with_loop(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    pub fn with_on_click<F: Fn(&mut T) + 'static>(mut self, callback: F) -> Self {
+        self.on_click = Some(Box::new(callback));
+        self
+    }
+
+    /// Send `message` to the state's `update` function when clicked
+    pub fn with_message(self, message: T::Message) -> Self
+    where T: super::Update {
+        self.with_on_click(move |state: &mut T| state.update(message.clone()))
+    }
+
+    /// Index of the displayed frame
+    pub fn current_frame(&self) -> usize {
+        self.sheet.frame_at(self.elapsed, self.looping)
+    }
+
+    /// Whether a non-looping animation has shown its last frame
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.elapsed >= self.sheet.total_duration()
+    }
+}
+
+impl<T> Widget<T> for AnimatedImage<T> {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn set_id(&mut self, id: &'static str) {
+        self.id = id;
+    }
+
+    fn text_component(&mut self) -> Option<&mut super::Text<T>> {
+        None
+    }
+
+    fn place(&mut self, x: i32, y: i32) {
+        self.rect.set_x(x);
+        self.rect.set_y(y);
+    }
+
+    fn on_click(&mut self, state: &mut T) {
+        if let Some(on_click) = &self.on_click {
+            (on_click)(state);
+        }
+    }
+
+    fn render(&self, window: &mut Window<T>, _widget_state: WidgetState)
+    where T: super::GenerateView<T> {
+        let frame = match self.sheet.frames.get(self.current_frame()) {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        let texture = window.image_cache.texture(self.sheet.resource_path).expect("Failed to create image texture");
+        window.canvas.copy(texture, Some(frame.source), Some(self.rect)).expect("Failed to copy texture to target");
+    }
+
+    fn animate(&mut self, delta: f32, _widget_state: &WidgetState) {
+        // Stop counting once a non-looping animation is finished
+        if self.playing && !self.is_finished() {
+            self.elapsed += Duration::from_secs_f32(delta.max(0.0));
+        }
+    }
+
+    fn persistent_state(&self) -> Option<Box<dyn Any>> {
+        Some(Box::new(Playback {
+            resource_path: self.sheet.resource_path,
+            elapsed: self.elapsed,
+        }))
+    }
+
+    fn restore_state(&mut self, state: Box<dyn Any>) {
+        if let Ok(playback) = state.downcast::<Playback>() {
+            // A different animation starts from its first frame
+            if playback.resource_path == self.sheet.resource_path {
+                self.elapsed = playback.elapsed;
+            }
+        }
+    }
+
+    fn translate(&mut self, dx: i32, dy: i32) {
+        self.rect.offset(dx, dy);
+    }
+
+    fn draw_width(&self) -> u32 {
+        self.rect.width()
+    }
+
+    fn draw_height(&self) -> u32 {
+        self.rect.height()
+    }
+}
+
+impl<T> IntoViewComponent<T> for AnimatedImage<T> where T: 'static {
+    fn as_component(self) -> ViewComponent<T> {
+        ViewComponent::Widget(Box::new(self))
+    }
+}
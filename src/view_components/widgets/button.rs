@@ -1,13 +1,22 @@
 extern crate sdl2;
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
+use sdl2::surface::Surface;
 
 use crate::view_components::{ViewComponent, IntoViewComponent};
 use crate::backend::system::window::Window;
-use crate::colors;
+use crate::view_components::theme;
+use crate::scale;
+use crate::animation::{Animated, Easing};
+use crate::images;
 
 use super::{Widget, WidgetState};
 use super::text::Text;
+use super::image::{self, NineSlice};
+
+use std::any::Any;
+use std::cell::Cell;
+use std::rc::Rc;
 
 pub struct Button<T> {
     pub id: &'static str,
@@ -17,18 +26,56 @@ pub struct Button<T> {
     pub hover_color: Color,
     pub text: Option<Text<T>>,
     pub on_click: Option<Box<dyn Fn(&mut T)>>,
+    pub corner_radius: u32,
+    /// Nine-slice background image (resource path, image size & borders), tinted by the button's colors
+    skin: Option<(&'static str, (u32, u32), NineSlice)>,
+    /// Drawn left of the text or centered without text
+    icon: Option<ButtonIcon>,
+    /// Icon raster of the previous view at another size, released when next drawn
+    stale_icon: Cell<Option<&'static str>>,
+    /// Displayed color, tweened between the above colors
+    color: Animated<Color>,
+    /// Displayed (width, height), tweened towards the laid out size
+    size: Animated<(u32, u32)>,
+}
+
+/// An icon image loaded at the size it is drawn
+struct ButtonIcon {
+    resource_path: &'static str,
+    /// Cache key of the loaded size (see `images::load_sized`)
+    key: &'static str,
+    size: (u32, u32),
+    surface: Rc<Surface<'static>>,
+}
+
+/// Transitions & icon carried over to the button of a regenerated view
+struct ButtonState {
+    color: Animated<Color>,
+    size: Animated<(u32, u32)>,
+    /// Icon resource path & cache key
+    icon: Option<(&'static str, &'static str)>,
 }
 
 impl<T> Button<T> {
     pub fn new(id: &'static str) -> Self {
+        let (style, corner_radius) = theme::with_current(|theme| {
+            (theme.styled(&theme.button, "Button", id), theme.corner_radius)
+        });
+
         Button {
             id: id,
-            rect: Rect::new(0, 0, 100, 40),
-            passive_color: colors::MANILLA,
-            clicking_color: Color::RGB(100, 100, 100),
-            hover_color: Color::RGB(200, 200, 200),
+            rect: Rect::new(0, 0, scale::px(100), scale::px(40)),
+            passive_color: style.base,
+            clicking_color: style.click,
+            hover_color: style.hover,
             text: None,
             on_click: None,
+            corner_radius: scale::px(corner_radius),
+            skin: None,
+            icon: None,
+            stale_icon: Cell::new(None),
+            color: Animated::new(style.base),
+            size: Animated::new((0, 0)),
         }
     }
 
@@ -49,22 +96,28 @@ impl<T> Button<T> {
         self
     }
 
+    /// Send `message` to the state's `update` function when clicked
+    pub fn with_message(self, message: T::Message) -> Self
+    where T: super::Update {
+        self.with_on_click(move |state: &mut T| state.update(message.clone()))
+    }
+
     /// Assign specific width to button
     pub fn with_width(mut self, width: u32) -> Self {
-        self.rect.set_width(width);
+        self.rect.set_width(scale::px(width));
         self
     }
 
     /// Assign specific height to button
     pub fn with_height(mut self, height: u32) -> Self {
-        self.rect.set_height(height);
+        self.rect.set_height(scale::px(height));
         self
     }
 
     /// Assign width and height to button
     pub fn with_dimensions(mut self, width: u32, height: u32) -> Self {
-        self.rect.set_width(width);
-        self.rect.set_height(height);
+        self.rect.set_width(scale::px(width));
+        self.rect.set_height(scale::px(height));
         self
     }
 
@@ -85,6 +138,76 @@ impl<T> Button<T> {
         self.clicking_color = color;
         self
     }
+
+    /// Round the button's corners (`0` for square corners)
+    pub fn with_corner_radius(mut self, radius: u32) -> Self {
+        self.corner_radius = scale::px(radius);
+        self
+    }
+
+    /// Draw the button using a background image whose borders are not stretched (see `NineSlice`)
+    /// - The image is tinted by the button's colors (use a white base color for an untinted image)
+    pub fn with_skin(mut self, resource_path: &'static str, left: u32, right: u32, top: u32, bottom: u32) -> Self {
+        let size = images::load_cached(resource_path).expect("Failed to load resource").size();
+        self.skin = Some((resource_path, size, NineSlice::new(left, right, top, bottom)));
+        self
+    }
+
+    /// Draw an icon of `width` x `height` (left of the text, or centered if there is none)
+    /// - Vector images (`.svg`) are rasterized at the icon's size, so they stay sharp at any scale
+    pub fn with_icon(mut self, resource_path: &'static str, width: u32, height: u32) -> Self {
+        let size = (scale::px(width), scale::px(height));
+        let (key, surface) = images::load_sized(resource_path, size, &images::ImageEffects::default())
+            .expect("Failed to load resource");
+        self.icon = Some(ButtonIcon { resource_path, key, size, surface });
+        self
+    }
+
+    /// Where the icon is drawn
+    fn icon_rect(&self, (width, height): (u32, u32)) -> Rect {
+        let y = self.rect.y() + (self.rect.height() as i32 - height as i32) / 2;
+
+        if self.text.is_some() {
+            // Inset from the left edge as far as from the top
+            let inset = (self.rect.height() as i32 - height as i32).max(0) / 2;
+            Rect::new(self.rect.x() + inset, y, width, height)
+        } else {
+            Rect::new(self.rect.x() + (self.rect.width() as i32 - width as i32) / 2, y, width, height)
+        }
+    }
+
+    /// Fade between base, hover & click colors over `duration` seconds
+    pub fn with_transition(mut self, duration: f32, easing: Easing) -> Self {
+        self.color = self.color.with_transition(duration, easing);
+        self
+    }
+
+    /// Grow or shrink to a new size over `duration` seconds (such as when the view is regenerated
+    /// with different dimensions), rather than jumping to it
+    /// - The button is drawn from its top-left corner, while events use its new size
+    pub fn with_size_transition(mut self, duration: f32, easing: Easing) -> Self {
+        self.size = self.size.with_transition(duration, easing);
+        self
+    }
+
+    /// Where the button's background is drawn (its tweened size)
+    fn drawn_rect(&self) -> Rect {
+        if !self.size.has_target() {
+            return self.rect;
+        }
+
+        let (width, height) = self.size.value();
+        Rect::new(self.rect.x(), self.rect.y(), width.max(1), height.max(1))
+    }
+
+    /// Color for the given state, disregarding transitions
+    fn state_color(&self, widget_state: &WidgetState) -> Color {
+        match widget_state {
+            WidgetState::Hovering => self.hover_color,
+            WidgetState::Active => self.clicking_color,
+            _ => self.passive_color,
+        }
+    }
 }
 
 impl<T> Widget<T> for Button<T> {
@@ -108,19 +231,75 @@ impl<T> Widget<T> for Button<T> {
 
     fn render(&self, window: &mut Window<T>, widget_state: WidgetState)
     where T: super::GenerateView<T> {
-        match widget_state {
-            WidgetState::Hovering => window.canvas.set_draw_color(self.hover_color),
-            WidgetState::Active => window.canvas.set_draw_color(self.clicking_color),
-            _ => window.canvas.set_draw_color(self.passive_color),
+        let color = if self.color.has_target() {
+            self.color.value()
+        } else {
+            self.state_color(&widget_state)
+        };
+
+        let rect = self.drawn_rect();
+
+        if let Some((resource_path, size, slice)) = &self.skin {
+            let texture = window.image_cache.texture(resource_path).expect("Failed to create skin texture");
+
+            // The texture is shared, so its tint is reset afterwards
+            texture.set_color_mod(color.r, color.g, color.b);
+            image::draw_nine_slice(&mut window.canvas, texture, *size, slice, rect);
+            texture.set_color_mod(255, 255, 255);
+        } else if self.corner_radius > 0 {
+            window.shape_cache.fill_rounded_rect(&mut window.canvas, rect, self.corner_radius, color);
+        } else {
+            window.canvas.set_draw_color(color);
+            window.canvas.fill_rect(rect).unwrap();
+        }
+
+        if let Some(stale_key) = self.stale_icon.take() {
+            window.image_cache.release(stale_key);
+        }
+
+        if let Some(icon) = &self.icon {
+            let texture = window.image_cache.texture_of(icon.key, &icon.surface).expect("Failed to create icon texture");
+            window.canvas.copy(texture, None, Some(self.icon_rect(icon.size))).expect("Failed to copy texture to target");
         }
 
-        window.canvas.fill_rect(self.rect).unwrap();
         // pay attention to draw order
         if let Some(button_text) = &self.text {
             button_text.render(window, widget_state);
         }
     }
 
+    fn animate(&mut self, delta: f32, widget_state: &WidgetState) {
+        let target = self.state_color(widget_state);
+        self.color.set_target(target);
+        self.color.advance(delta);
+
+        self.size.set_target((self.rect.width(), self.rect.height()));
+        self.size.advance(delta);
+    }
+
+    fn persistent_state(&self) -> Option<Box<dyn Any>> {
+        Some(Box::new(ButtonState {
+            color: self.color.clone(),
+            size: self.size.clone(),
+            icon: self.icon.as_ref().map(|icon| (icon.resource_path, icon.key)),
+        }))
+    }
+
+    /// Hover, press & size transitions continue across view regenerations
+    /// - The previous size of a resized icon (such as after a scale factor change) is released
+    fn restore_state(&mut self, state: Box<dyn Any>) {
+        if let Ok(state) = state.downcast::<ButtonState>() {
+            self.color.resume(&state.color);
+            self.size.resume(&state.size);
+
+            if let (Some((previous_path, previous_key)), Some(icon)) = (state.icon, &self.icon) {
+                if previous_path == icon.resource_path && previous_key != icon.key {
+                    self.stale_icon.set(Some(previous_key));
+                }
+            }
+        }
+    }
+
     fn translate(&mut self, dx: i32, dy: i32) {
         self.rect = Rect::new(
             self.rect().x() + dx,
@@ -142,6 +321,10 @@ impl<T> Widget<T> for Button<T> {
         self.id
     }
 
+    fn set_id(&mut self, id: &'static str) {
+        self.id = id;
+    }
+
     fn on_click(&mut self, state: &mut T) {
         if let Some(ref on_click_function) = self.on_click {
             (on_click_function)(state);
@@ -161,4 +344,36 @@ impl<T> IntoViewComponent<T> for Button<T> where T: 'static {
     fn as_component(self) -> ViewComponent<T> {
         ViewComponent::Widget(Box::new(self))
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_transitions_continue_from_the_previous_view() {
+        let mut previous: Button<()> = Button::new("grow").with_dimensions(100, 40).with_size_transition(1.0, Easing::Linear);
+        previous.animate(0.0, &WidgetState::Base);
+        assert_eq!(previous.drawn_rect(), previous.rect);
+
+        let mut next: Button<()> = Button::new("grow").with_dimensions(200, 40).with_size_transition(1.0, Easing::Linear);
+        next.restore_state(previous.persistent_state().unwrap());
+
+        next.animate(0.0, &WidgetState::Base);
+        assert_eq!(next.drawn_rect().size(), (scale::px(100), scale::px(40)));
+        next.animate(0.5, &WidgetState::Base);
+        assert_eq!(next.drawn_rect().size(), (scale::px(150), scale::px(40)));
+        next.animate(0.5, &WidgetState::Base);
+        assert_eq!(next.drawn_rect(), next.rect);
+    }
+
+    #[test]
+    fn sizes_jump_without_transition() {
+        let mut previous: Button<()> = Button::new("jump").with_width(100);
+        previous.animate(0.0, &WidgetState::Base);
+
+        let mut next: Button<()> = Button::new("jump").with_width(200);
+        next.restore_state(previous.persistent_state().unwrap());
+        next.animate(0.0, &WidgetState::Base);
+        assert_eq!(next.drawn_rect(), next.rect);
+    }
+}
@@ -0,0 +1,433 @@
+extern crate sdl2;
+use sdl2::rect::{Point, Rect};
+use sdl2::pixels::Color;
+use sdl2::event::Event;
+use sdl2::mouse::MouseButton;
+use sdl2::render::WindowCanvas;
+use sdl2::ttf::{FontStyle, Sdl2TtfContext};
+
+use crate::Window;
+use crate::view_components::theme;
+use crate::scale;
+use crate::view_components::{IntoViewComponent, ViewComponent};
+use crate::font::{FontParams, GlyphAtlas};
+use crate::images::ImageCache;
+use crate::draw::ShapeCache;
+
+use super::{Widget, WidgetState};
+use super::text::Text;
+
+use std::any::Any;
+
+
+// ========================== Painter ========================== //
+
+/// Immediate-mode drawing API passed to a `Canvas`'s draw callback
+/// - All coordinates are relative to the canvas' top-left corner
+/// - Coordinates are physical pixels (see `scale_factor`)
+/// - Drawing is clipped to the canvas
+pub struct Painter<'p> {
+    canvas: &'p mut WindowCanvas,
+    ttf_context: &'p Sdl2TtfContext,
+    /// The window's image cache
+    images: &'p mut ImageCache,
+    /// The window's glyph cache
+    atlas: &'p mut GlyphAtlas,
+    /// The window's shape cache
+    shapes: &'p mut ShapeCache,
+    font: FontParams,
+    origin: Point,
+    width: u32,
+    height: u32,
+}
+
+impl<'p> Painter<'p> {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Physical pixels per logical pixel (see `scale`)
+    pub fn scale_factor(&self) -> f32 {
+        scale::factor()
+    }
+
+    /// Font used by `text` & `text_size` (defaults to the theme's font)
+    pub fn set_font(&mut self, font: FontParams) {
+        self.font = font;
+    }
+
+    /// Fill the entire canvas
+    pub fn clear(&mut self, color: Color) {
+        let (width, height) = (self.width, self.height);
+        self.fill_rect(Rect::new(0, 0, width, height), color);
+    }
+
+    pub fn fill_rect(&mut self, rect: Rect, color: Color) {
+        let rect = self.to_window_rect(rect);
+        self.canvas.set_draw_color(color);
+        self.canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
+        self.canvas.fill_rect(rect).unwrap();
+        self.canvas.set_blend_mode(sdl2::render::BlendMode::None);
+    }
+
+    /// Outline a rectangle. The border is drawn inside `rect`.
+    pub fn stroke_rect(&mut self, rect: Rect, thickness: u32, color: Color) {
+        self.stroke_rounded_rect(rect, 0, thickness, color);
+    }
+
+    pub fn fill_rounded_rect(&mut self, rect: Rect, radius: u32, color: Color) {
+        let rect = self.to_window_rect(rect);
+        self.shapes.fill_rounded_rect(self.canvas, rect, radius, color);
+    }
+
+    /// Outline a rounded rectangle. The border is drawn inside `rect`.
+    pub fn stroke_rounded_rect(&mut self, rect: Rect, radius: u32, thickness: u32, color: Color) {
+        let rect = self.to_window_rect(rect);
+        self.shapes.draw_rounded_border(self.canvas, rect, radius, thickness, color);
+    }
+
+    pub fn line<P: Into<Point>>(&mut self, from: P, to: P, thickness: u32, color: Color) {
+        let (from, to) = (self.to_window_point(from.into()), self.to_window_point(to.into()));
+        self.shapes.draw_line(self.canvas, from, to, thickness, color);
+    }
+
+    pub fn polyline<P: Into<Point> + Copy>(&mut self, points: &[P], thickness: u32, color: Color) {
+        let points = self.to_window_points(points);
+        self.shapes.draw_polyline(self.canvas, &points, thickness, color);
+    }
+
+    pub fn fill_polygon<P: Into<Point> + Copy>(&mut self, points: &[P], color: Color) {
+        let points = self.to_window_points(points);
+        self.shapes.fill_polygon(self.canvas, &points, color);
+    }
+
+    pub fn fill_circle<P: Into<Point>>(&mut self, center: P, radius: u32, color: Color) {
+        let center = self.to_window_point(center.into());
+        self.shapes.fill_circle(self.canvas, center, radius, color);
+    }
+
+    /// Outline a circle. The border is drawn inside `radius`.
+    pub fn stroke_circle<P: Into<Point>>(&mut self, center: P, radius: u32, thickness: u32, color: Color) {
+        let center = self.to_window_point(center.into());
+        self.shapes.draw_circle(self.canvas, center, radius, thickness, color);
+    }
+
+    /// Draw `text` with its top-left corner at `position`
+    pub fn text<P: Into<Point>>(&mut self, text: &str, position: P, color: Color) {
+        let position = self.to_window_point(position.into());
+        self.atlas.draw_text(self.canvas, self.ttf_context, &self.font, text, color, (position.x(), position.y()));
+    }
+
+    /// Dimensions (width, height) of `text` drawn with the current font
+    pub fn text_size(&mut self, text: &str) -> (u32, u32) {
+        self.atlas.size_text(self.ttf_context, &self.font, FontStyle::NORMAL, text)
+    }
+
+    /// Draw the image at `resource_path`, scaled to `target`
+    /// - Images are loaded once & shared with other widgets (see `ImageCache`)
+    pub fn image(&mut self, resource_path: &'static str, target: Rect) -> Result<(), String> {
+        let target = self.to_window_rect(target);
+        let texture = self.images.texture(resource_path)?;
+
+        self.canvas.copy(texture, None, Some(target))
+    }
+
+    fn to_window_point(&self, point: Point) -> Point {
+        point.offset(self.origin.x(), self.origin.y())
+    }
+
+    fn to_window_points<P: Into<Point> + Copy>(&self, points: &[P]) -> Vec<Point> {
+        points.iter().map(|point| self.to_window_point((*point).into())).collect()
+    }
+
+    fn to_window_rect(&self, rect: Rect) -> Rect {
+        Rect::new(rect.x() + self.origin.x(), rect.y() + self.origin.y(), rect.width(), rect.height())
+    }
+}
+
+
+// ========================== Canvas Widget ========================== //
+
+/// Draws the canvas' content using the application state
+type DrawCallback<T> = Box<dyn Fn(&T, &mut Painter)>;
+/// Mouse callback accepting application state & canvas-relative (x, y)
+type MouseCallback<T> = Box<dyn Fn(&mut T, i32, i32)>;
+
+/// Canvas Widget
+/// - Draws custom content (charts, diagrams, etc.) using a `Painter`
+/// - Reports mouse events in canvas-relative coordinates
+pub struct Canvas<T> {
+    id: &'static str,
+    rect: Rect,
+    background: Option<Color>,
+    font: FontParams,
+
+    on_draw: Option<DrawCallback<T>>,
+    on_mouse_down: Option<MouseCallback<T>>,
+    on_mouse_up: Option<MouseCallback<T>>,
+    on_mouse_drag: Option<MouseCallback<T>>,
+    on_mouse_move: Option<MouseCallback<T>>,
+
+    /// Whether the left mouse button was pressed within the canvas (and not yet released)
+    /// - Carried over when the view is regenerated (see `persistent_state`)
+    pressed: bool,
+}
+
+impl<T> Canvas<T> {
+    /// - `dimensions`: (width, height) of the canvas
+    pub fn new(id: &'static str, dimensions: (u32, u32)) -> Self {
+        let font = theme::with_current(|theme| theme.font);
+
+        Canvas {
+            id,
+            rect: Rect::new(0, 0, scale::px(dimensions.0), scale::px(dimensions.1)),
+            background: None,
+            font,
+            on_draw: None,
+            on_mouse_down: None,
+            on_mouse_up: None,
+            on_mouse_drag: None,
+            on_mouse_move: None,
+            pressed: false,
+        }
+    }
+
+    /// Draw the canvas' content each frame
+    pub fn with_on_draw<F: 'static + Fn(&T, &mut Painter)>(mut self, draw: F) -> Self {
+        self.on_draw = Some(Box::new(draw));
+        self
+    }
+
+    /// Fill the canvas before drawing
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Called when the left mouse button is pressed within the canvas
+    pub fn with_on_mouse_down<F: 'static + Fn(&mut T, i32, i32)>(mut self, callback: F) -> Self {
+        self.on_mouse_down = Some(Box::new(callback));
+        self
+    }
+
+    /// Called when the left mouse button is released after being pressed within the canvas
+    pub fn with_on_mouse_up<F: 'static + Fn(&mut T, i32, i32)>(mut self, callback: F) -> Self {
+        self.on_mouse_up = Some(Box::new(callback));
+        self
+    }
+
+    /// Called when the mouse moves while pressed (positions may lie outside the canvas)
+    pub fn with_on_mouse_drag<F: 'static + Fn(&mut T, i32, i32)>(mut self, callback: F) -> Self {
+        self.on_mouse_drag = Some(Box::new(callback));
+        self
+    }
+
+    /// Called when the mouse moves over the canvas without being pressed
+    pub fn with_on_mouse_move<F: 'static + Fn(&mut T, i32, i32)>(mut self, callback: F) -> Self {
+        self.on_mouse_move = Some(Box::new(callback));
+        self
+    }
+
+    fn call(callback: &Option<MouseCallback<T>>, state: &mut T, position: (i32, i32)) {
+        if let Some(callback) = callback {
+            (callback)(state, position.0, position.1);
+        }
+    }
+}
+
+impl<T> Widget<T> for Canvas<T> {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn set_id(&mut self, id: &'static str) {
+        self.id = id;
+    }
+
+    fn text_component(&mut self) -> Option<&mut Text<T>> {
+        None
+    }
+
+    fn place(&mut self, x: i32, y: i32) {
+        self.rect.set_x(x);
+        self.rect.set_y(y);
+    }
+
+    fn on_mouse_event(&mut self, state: &mut T, event: &Event) {
+        let origin = self.rect.top_left();
+        let local = |x: i32, y: i32| (x - origin.x(), y - origin.y());
+
+        // TODO: Report other mouse buttons & the scroll wheel
+        match *event {
+            Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } => {
+                self.pressed = true;
+                Self::call(&self.on_mouse_down, state, local(x, y));
+            }
+            Event::MouseButtonUp { mouse_btn: MouseButton::Left, x, y, .. } if self.pressed => {
+                self.pressed = false;
+                Self::call(&self.on_mouse_up, state, local(x, y));
+            }
+            Event::MouseMotion { x, y, .. } => {
+                if self.pressed {
+                    Self::call(&self.on_mouse_drag, state, local(x, y));
+                } else {
+                    Self::call(&self.on_mouse_move, state, local(x, y));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Mouse callbacks usually modify state, regenerating the view mid-press
+    fn persistent_state(&self) -> Option<Box<dyn Any>> {
+        Some(Box::new(self.pressed))
+    }
+
+    fn restore_state(&mut self, state: Box<dyn Any>) {
+        if let Ok(pressed) = state.downcast::<bool>() {
+            self.pressed = *pressed;
+        }
+    }
+
+    fn render(&self, window: &mut Window<T>, _widget_state: WidgetState)
+    where T: super::GenerateView<T> {
+        // Stay within any enclosing clip (such as a `SplitView` pane)
+        let previous_clip = window.canvas.clip_rect();
+        let clip = match clip_within(previous_clip, self.rect) {
+            Some(clip) => clip,
+            None => return,
+        };
+        window.canvas.set_clip_rect(Some(clip));
+
+        if let Some(color) = self.background {
+            window.canvas.set_draw_color(color);
+            window.canvas.fill_rect(self.rect).unwrap();
+        }
+
+        if let Some(on_draw) = &self.on_draw {
+            let mut painter = Painter {
+                canvas: &mut window.canvas,
+                ttf_context: &window.ttf_context,
+                images: &mut window.image_cache,
+                atlas: &mut window.glyph_atlas,
+                shapes: &mut window.shape_cache,
+                font: self.font,
+                origin: self.rect.top_left(),
+                width: self.rect.width(),
+                height: self.rect.height(),
+            };
+
+            (on_draw)(&*window.window_state.user_state, &mut painter);
+        }
+
+        window.canvas.set_clip_rect(previous_clip);
+    }
+
+    fn translate(&mut self, dx: i32, dy: i32) {
+        self.rect.offset(dx, dy);
+    }
+
+    fn draw_width(&self) -> u32 {
+        self.rect.width()
+    }
+
+    fn draw_height(&self) -> u32 {
+        self.rect.height()
+    }
+}
+
+/// Part of `rect` within the `enclosing` clip (if any), or `None` if nothing of `rect` is visible
+fn clip_within(enclosing: Option<Rect>, rect: Rect) -> Option<Rect> {
+    match enclosing {
+        Some(enclosing) => enclosing.intersection(rect),
+        None => Some(rect),
+    }
+}
+
+impl<T> IntoViewComponent<T> for Canvas<T> where T: 'static {
+    fn as_component(self) -> ViewComponent<T> {
+        ViewComponent::Widget(Box::new(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sdl2::mouse::MouseState;
+
+    fn button_event(down: bool, x: i32, y: i32) -> Event {
+        if down {
+            Event::MouseButtonDown { timestamp: 0, window_id: 0, which: 0, mouse_btn: MouseButton::Left, clicks: 1, x, y }
+        } else {
+            Event::MouseButtonUp { timestamp: 0, window_id: 0, which: 0, mouse_btn: MouseButton::Left, clicks: 1, x, y }
+        }
+    }
+
+    fn motion_event(x: i32, y: i32) -> Event {
+        Event::MouseMotion { timestamp: 0, window_id: 0, which: 0, mousestate: MouseState::from_sdl_state(0), x, y, xrel: 0, yrel: 0 }
+    }
+
+    /// A canvas at (100, 50) recording its callbacks as (name, x, y)
+    fn recording_canvas() -> Canvas<Vec<(&'static str, i32, i32)>> {
+        let mut canvas = Canvas::new("canvas", (40, 40))
+            .with_on_mouse_down(|events: &mut Vec<_>, x, y| events.push(("down", x, y)))
+            .with_on_mouse_up(|events: &mut Vec<_>, x, y| events.push(("up", x, y)))
+            .with_on_mouse_drag(|events: &mut Vec<_>, x, y| events.push(("drag", x, y)))
+            .with_on_mouse_move(|events: &mut Vec<_>, x, y| events.push(("move", x, y)));
+        canvas.place(100, 50);
+        canvas
+    }
+
+    #[test]
+    fn mouse_events_are_canvas_relative() {
+        let mut canvas = recording_canvas();
+        let mut events = Vec::new();
+
+        canvas.on_mouse_event(&mut events, &motion_event(101, 52));
+        canvas.on_mouse_event(&mut events, &button_event(true, 110, 60));
+        canvas.on_mouse_event(&mut events, &motion_event(90, 40));
+        canvas.on_mouse_event(&mut events, &button_event(false, 90, 40));
+
+        assert_eq!(events, [("move", 1, 2), ("down", 10, 10), ("drag", -10, -10), ("up", -10, -10)]);
+    }
+
+    #[test]
+    fn releases_require_a_press() {
+        let mut canvas = recording_canvas();
+        let mut events = Vec::new();
+
+        canvas.on_mouse_event(&mut events, &button_event(false, 110, 60));
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn presses_continue_in_regenerated_views() {
+        let mut canvas = recording_canvas();
+        let mut events = Vec::new();
+        canvas.on_mouse_event(&mut events, &button_event(true, 110, 60));
+
+        let mut regenerated = recording_canvas();
+        regenerated.restore_state(canvas.persistent_state().unwrap());
+        regenerated.on_mouse_event(&mut events, &motion_event(120, 60));
+        regenerated.on_mouse_event(&mut events, &button_event(false, 120, 60));
+
+        assert_eq!(events, [("down", 10, 10), ("drag", 20, 10), ("up", 20, 10)]);
+    }
+
+    #[test]
+    fn clips_stay_within_enclosing_clips() {
+        let rect = Rect::new(100, 50, 40, 40);
+
+        assert_eq!(clip_within(None, rect), Some(rect));
+        assert_eq!(clip_within(Some(Rect::new(0, 0, 120, 600)), rect), Some(Rect::new(100, 50, 20, 40)));
+        assert_eq!(clip_within(Some(Rect::new(0, 0, 50, 50)), rect), None);
+    }
+}
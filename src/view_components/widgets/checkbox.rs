@@ -1,10 +1,11 @@
 extern crate sdl2;
 use sdl2::pixels::Color;
-use sdl2::rect::Rect;
+use sdl2::rect::{Point, Rect};
 
 use crate::view_components::{ViewComponent, IntoViewComponent};
 use crate::backend::system::window::Window;
-use crate::colors;
+use crate::view_components::theme;
+use crate::scale;
 
 use super::{Widget, WidgetState};
 use super::Text;
@@ -19,6 +20,7 @@ pub struct CheckBox<T> {
     click_color: Color,
     hover_color: Color,
     check_color: Color,
+    label_color: Color,
     text: Option<Text<T>>,
     is_checked: bool,
     
@@ -28,24 +30,31 @@ pub struct CheckBox<T> {
 
     checkbox_width: u32,
     checkbox_height: u32,
+    corner_radius: u32,
 }
 
 impl<T> CheckBox<T> {
     pub fn new(id: &'static str, is_checked: bool) -> Self {
+        let (style, corner_radius) = theme::with_current(|theme| {
+            (theme.styled(&theme.checkbox, "CheckBox", id), theme.corner_radius)
+        });
+
         CheckBox {
             id: id,
-            rect: Rect::new(0, 0, 100, 40),
-            default_color: colors::MANILLA,
-            click_color: Color::RGB(140, 140, 140),
-            hover_color: Color::RGB(200, 200, 200),
-            check_color: Color::RGB(80, 80, 80),
+            rect: Rect::new(0, 0, scale::px(100), scale::px(40)),
+            default_color: style.base,
+            click_color: style.click,
+            hover_color: style.hover,
+            check_color: style.check,
+            label_color: style.label,
             text: None,
             is_checked: is_checked,
-            checkbox_padding_right: 10,
+            checkbox_padding_right: scale::px(style.padding),
             on_check: None,
 
-            checkbox_width: 20,
-            checkbox_height: 20,
+            checkbox_width: scale::px(20),
+            checkbox_height: scale::px(20),
+            corner_radius: scale::px(corner_radius),
         }
     }
 
@@ -53,7 +62,7 @@ impl<T> CheckBox<T> {
         // TODO: How to hanle the sub-widget's id?
         //       Note that the sub-widget is not actually part of the view
 
-        let mut attached_text = Text::new("", text).with_color(colors::WHITE);
+        let mut attached_text = Text::new("", text).with_color(self.label_color);
         attached_text.place(self.rect.x() + (self.checkbox_width + self.checkbox_padding_right) as i32, 
                        self.rect.y());
 
@@ -67,6 +76,13 @@ impl<T> CheckBox<T> {
         self
     }
 
+    /// Send the message created by `to_message` to the state's `update` function when checked
+    pub fn with_on_check_message<F: 'static + Fn(bool) -> T::Message>
+    (self, to_message: F) -> Self
+    where T: super::Update {
+        self.with_on_check(move |state: &mut T, is_checked| state.update(to_message(is_checked)))
+    }
+
     pub fn check(mut self) -> Self {
         self.is_checked = true;
         self
@@ -114,6 +130,10 @@ impl<T> Widget<T> for CheckBox<T> {
         self.id
     }
 
+    fn set_id(&mut self, id: &'static str) {
+        self.id = id;
+    }
+
     // TODO: Allow user to decide whether this should trigger when the widget is clicked
     //       or *only* when the checkbox itself is clicked (would need x/y coords)
     fn on_click(&mut self, state: &mut T) {
@@ -124,26 +144,28 @@ impl<T> Widget<T> for CheckBox<T> {
     fn render(&self, window: &mut Window<T>, widget_state: WidgetState)
     where T: super::GenerateView<T> {
         // First, draw the checkbox itself
-        match widget_state {
-            WidgetState::Hovering => window.canvas.set_draw_color(self.hover_color),
-            WidgetState::Active => window.canvas.set_draw_color(self.click_color),
-            WidgetState::Base => window.canvas.set_draw_color(self.default_color),
+        let box_color = match widget_state {
+            WidgetState::Hovering => self.hover_color,
+            WidgetState::Active => self.click_color,
+            WidgetState::Base => self.default_color,
             WidgetState::Focused => panic!("CheckBox cannot be focused"),
-        }
+        };
         let checkbox_x = self.rect.x();// + self.internal_padding as i32;
         let checkbox_y = self.rect.y() + (self.rect.height() as i32 - self.checkbox_height as i32) / 2;
+        let checkbox_rect = Rect::new(checkbox_x, checkbox_y, self.checkbox_width, self.checkbox_height);
 
-        window.canvas.fill_rect(Rect::new(checkbox_x, checkbox_y, self.checkbox_width, self.checkbox_height)).unwrap();
+        // Limit the radius so the box remains recognizable as a box
+        window.shape_cache.fill_rounded_rect(&mut window.canvas, checkbox_rect, self.corner_radius.min(self.checkbox_width / 4), box_color);
     
         // Second, draw the check if checked
         if self.is_checked {
-            window.canvas.set_draw_color(self.check_color);
-            window.canvas.fill_rect(Rect::new(
-                checkbox_x + 4, 
-                checkbox_y + 4, 
-                self.checkbox_width - 8, 
-                self.checkbox_height - 8)
-            ).unwrap();
+            let (width, height) = (self.checkbox_width as i32, self.checkbox_height as i32);
+            let checkmark = [
+                Point::new(checkbox_x + width / 4, checkbox_y + height / 2),
+                Point::new(checkbox_x + width * 2 / 5, checkbox_y + height * 7 / 10),
+                Point::new(checkbox_x + width * 3 / 4, checkbox_y + height * 3 / 10),
+            ];
+            window.shape_cache.draw_polyline(&mut window.canvas, &checkmark, (self.checkbox_width / 8).max(2), self.check_color);
         }
 
         // Finally, draw the text if present
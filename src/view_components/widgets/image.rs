@@ -2,24 +2,153 @@ extern crate sdl2;
 use sdl2::rect::Rect;
 use sdl2::pixels::Color;
 use sdl2::surface::Surface;
+use sdl2::render::{Texture, WindowCanvas};
 use sdl2::event::Event;
 
-use crate::{colors, Window};
+use crate::Window;
+use crate::view_components::theme;
+use crate::view_components::views::{Alignment, VerticalAlignment};
+use crate::scale;
 
 use super::{Widget, WidgetState};
 use crate::view_components::{IntoViewComponent, ViewComponent};
-use crate::images;
+use crate::images::{self, ImageCache, ImageEffects};
+use crate::animation::{Animated, Easing};
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+
+// ========================== Scaling ========================== //
+
+/// How an image is drawn within its bounds
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ImageFit {
+    /// Fill the bounds, ignoring the aspect ratio (default)
+    Stretch,
+    /// Largest size fitting within the bounds, preserving the aspect ratio
+    Contain,
+    /// Smallest size covering the bounds, preserving the aspect ratio (cropped)
+    Cover,
+    /// Natural size (cropped if larger than the bounds)
+    Center,
+    /// Natural size, repeated to fill the bounds
+    Tile,
+}
+
+/// Image borders (in image pixels) which keep their size when the image is stretched
+/// - Corners are never scaled, edges are stretched along one axis & the center along both
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct NineSlice {
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
+}
+
+impl NineSlice {
+    pub fn new(left: u32, right: u32, top: u32, bottom: u32) -> Self {
+        NineSlice {
+            left,
+            right,
+            top,
+            bottom,
+        }
+    }
+
+    /// (source, target) rects of each non-empty slice of an image of size `source` drawn to `target`
+    fn slices(&self, source: (u32, u32), target: Rect) -> Vec<(Rect, Rect)> {
+        let source_columns = slice_edges(0, source.0, self.left, self.right, 1.0);
+        let source_rows = slice_edges(0, source.1, self.top, self.bottom, 1.0);
+        let target_columns = slice_edges(target.x(), target.width(), self.left, self.right, scale::factor());
+        let target_rows = slice_edges(target.y(), target.height(), self.top, self.bottom, scale::factor());
+
+        let mut slices = Vec::with_capacity(9);
+        for row in 0..3 {
+            for column in 0..3 {
+                let source_rect = span_rect(&source_columns, &source_rows, column, row);
+                let target_rect = span_rect(&target_columns, &target_rows, column, row);
+
+                if let (Some(source_rect), Some(target_rect)) = (source_rect, target_rect) {
+                    slices.push((source_rect, target_rect));
+                }
+            }
+        }
+
+        slices
+    }
+}
+
+/// Edges of the three slices along one axis
+/// - Borders are scaled by `factor` & shrunk to fit within `length`
+fn slice_edges(start: i32, length: u32, before: u32, after: u32, factor: f32) -> [i32; 4] {
+    let mut before = (before as f32 * factor).round() as u32;
+    let mut after = (after as f32 * factor).round() as u32;
 
-use std::path::Path;
+    if before + after > length {
+        let shrink = length as f32 / (before + after) as f32;
+        before = (before as f32 * shrink) as u32;
+        after = length - before;
+    }
 
+    let end = start + length as i32;
+    [start, start + before as i32, end - after as i32, end]
+}
+
+fn span_rect(columns: &[i32; 4], rows: &[i32; 4], column: usize, row: usize) -> Option<Rect> {
+    let width = columns[column + 1] - columns[column];
+    let height = rows[row + 1] - rows[row];
+
+    if width > 0 && height > 0 {
+        Some(Rect::new(columns[column], rows[row], width as u32, height as u32))
+    } else {
+        None
+    }
+}
+
+/// Draw `texture` (of size `source`) stretched to `target` without scaling its borders
+pub fn draw_nine_slice(canvas: &mut WindowCanvas, texture: &Texture, source: (u32, u32), slice: &NineSlice, target: Rect) {
+    for (source_rect, target_rect) in slice.slices(source, target) {
+        canvas.copy(texture, Some(source_rect), Some(target_rect)).expect("Failed to copy texture to target");
+    }
+}
+
+/// Fraction of the free space placed before the image
+fn alignment_offset(free_space: i32, fraction: f32) -> i32 {
+    (free_space as f32 * fraction).round() as i32
+}
+
+
+// ========================== Image Widget ========================== //
+
+/// Vector image rasterized at a size: (size, cache key, image)
+type Raster = ((u32, u32), &'static str, Rc<Surface<'static>>);
 
 /// Image Widget
 /// - Displays an image
 pub struct Image<T> {
     id: &'static str,
     rect: Rect,
-    // resource_path: String,
-    image_surface: Surface<'static>,
+    resource_path: &'static str,
+    /// Cache key of the image with `effects` applied
+    image_key: &'static str,
+    /// Decoded image, shared with other widgets (see `images::load_cached`)
+    image_surface: Rc<Surface<'static>>,
+    effects: ImageEffects,
+    /// Multiplies the image's colors & alpha when drawn
+    tint: Color,
+    /// Multiplies the image's alpha when drawn (0.0 - 1.0)
+    opacity: f32,
+    /// Vector image rasterized at the size it was last drawn
+    raster: RefCell<Option<Raster>>,
+
+    /// How the image is scaled within `rect`
+    fit: ImageFit,
+    /// Placement within `rect` when the image does not fill it
+    alignment: (Alignment, VerticalAlignment),
+    /// Replaces `fit` when assigned
+    nine_slice: Option<NineSlice>,
 
     // Let user decide whether hovering image highlight border it or adjusts image (e.g.: darken)
     // TODO: Add these options to a mod.rs style enum: 'Border', 'Darken' or something like that
@@ -27,6 +156,8 @@ pub struct Image<T> {
     hover_border_width: u32,
     hover_color: Color,
     click_color: Color,
+    /// Displayed border/shade color, tweened between states
+    highlight: Animated<Color>,
     
     // Interact with state when image is clicked
     on_click: Option<Box<dyn Fn(&mut T)>>,
@@ -36,21 +167,44 @@ impl<T> Image<T> {
     /// - `resource_path`: Path to image resource as static string
     /// - `bounds`: (width, height) bounds for image
     pub fn new(id: &'static str, resource_path: &'static str, bounds: (u32, u32)) -> Self {
-        // FIXME: I am doing this here to obtain image dimensions before view init. This is not final.
-        let surface = images::load_image(&Path::new(resource_path)).expect("Failed to load resource");
+        // Image dimensions are needed before view init
+        let surface = images::load_cached(resource_path).expect("Failed to load resource");
+
+        Self::from_surface(id, resource_path, surface, bounds)
+    }
 
-        // TODO: These need to be scaled to image's aspect ration (query dimensions first)
+    /// Image laid out at its natural size
+    /// - `resource_path`: Path to image resource as static string
+    pub fn natural(id: &'static str, resource_path: &'static str) -> Self {
+        let surface = images::load_cached(resource_path).expect("Failed to load resource");
+        let bounds = surface.size();
+
+        Self::from_surface(id, resource_path, surface, bounds)
+    }
+
+    fn from_surface(id: &'static str, resource_path: &'static str, surface: Rc<Surface<'static>>, bounds: (u32, u32)) -> Self {
         let (width, height) = bounds;
 
+        let style = theme::with_current(|theme| theme.styled(&theme.image, "Image", id));
+
         Image {
             id: id,
-            rect: Rect::new(0, 0, width, height),
-            // resource_path: resource_path,
+            rect: Rect::new(0, 0, scale::px(width), scale::px(height)),
+            resource_path,
+            image_key: resource_path,
             image_surface: surface,
+            effects: ImageEffects::default(),
+            tint: Color::RGBA(255, 255, 255, 255),
+            opacity: 1.0,
+            raster: RefCell::new(None),
+            fit: ImageFit::Stretch,
+            alignment: (Alignment::Center, VerticalAlignment::Center),
+            nine_slice: None,
             hover_border: true,
-            hover_border_width: 6,
-            hover_color: colors::DARKER_PURPLE,
-            click_color: colors::BLACK,
+            hover_border_width: scale::px(6),
+            hover_color: style.hover,
+            click_color: style.click,
+            highlight: Animated::new(style.hover),
             on_click: None,
         }
     }
@@ -61,6 +215,62 @@ impl<T> Image<T> {
         self
     }
 
+    /// Send `message` to the state's `update` function when clicked
+    pub fn with_message(self, message: T::Message) -> Self
+    where T: super::Update {
+        self.with_on_click(move |state: &mut T| state.update(message.clone()))
+    }
+
+    /// How the image is scaled within its bounds (see `ImageFit`)
+    pub fn with_fit(mut self, fit: ImageFit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    /// Placement of the image within its bounds when it does not fill them (centered by default)
+    pub fn with_alignment(mut self, horizontal: Alignment, vertical: VerticalAlignment) -> Self {
+        self.alignment = (horizontal, vertical);
+        self
+    }
+
+    /// Stretch the image without scaling its borders (such as a button background)
+    /// - Borders are given in image pixels
+    pub fn with_nine_slice(mut self, left: u32, right: u32, top: u32, bottom: u32) -> Self {
+        self.nine_slice = Some(NineSlice::new(left, right, top, bottom));
+        self
+    }
+
+    /// Make pixels of `color` transparent (such as a bitmap's background)
+    pub fn with_color_key(mut self, color: Color) -> Self {
+        self.effects.color_key = Some(color);
+        self.apply_effects()
+    }
+
+    /// Multiply the image's colors by `color`
+    /// - Applied when drawing, so every tint shares the image's texture
+    pub fn with_tint(mut self, color: Color) -> Self {
+        self.tint = color;
+        self
+    }
+
+    /// Opacity of the image (0.0 - 1.0)
+    /// - Applied when drawing, like `with_tint`
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Reload the image with the current effects (processed images are cached)
+    fn apply_effects(mut self) -> Self {
+        let (key, surface) = images::load_with_effects(self.resource_path, &self.effects)
+            .expect("Failed to load resource");
+
+        self.image_key = key;
+        self.image_surface = surface;
+        self.raster = RefCell::new(None);
+        self
+    }
+
     /// Draw a border around image when hovered
     pub fn with_hover_border(mut self) -> Self {
         self.hover_border = true;
@@ -69,7 +279,7 @@ impl<T> Image<T> {
 
     /// Width of border around image on hover if enabled
     pub fn with_hover_border_width(mut self, width: u32) -> Self {
-        self.hover_border_width = width;
+        self.hover_border_width = scale::px(width);
         self
     }
 
@@ -93,6 +303,139 @@ impl<T> Image<T> {
         self.click_color = color;
         self
     }
+
+    /// Fade the border or shade in & out over `duration` seconds
+    pub fn with_transition(mut self, duration: f32, easing: Easing) -> Self {
+        self.highlight = self.highlight.with_transition(duration, easing);
+        self
+    }
+
+    /// Image size without scaling (image pixels are logical pixels)
+    fn natural_size(&self) -> (u32, u32) {
+        (scale::px(self.image_surface.width()), scale::px(self.image_surface.height()))
+    }
+
+    /// Position a `width` x `height` image within the bounds according to `alignment`
+    fn aligned_rect(&self, width: u32, height: u32) -> Rect {
+        let horizontal = match self.alignment.0 {
+            Alignment::Left => 0.0,
+            Alignment::Center => 0.5,
+            Alignment::Right => 1.0,
+        };
+        let vertical = match self.alignment.1 {
+            VerticalAlignment::Top => 0.0,
+            VerticalAlignment::Center => 0.5,
+            VerticalAlignment::Bottom => 1.0,
+        };
+
+        Rect::new(
+            self.rect.x() + alignment_offset(self.rect.width() as i32 - width as i32, horizontal),
+            self.rect.y() + alignment_offset(self.rect.height() as i32 - height as i32, vertical),
+            width,
+            height,
+        )
+    }
+
+    /// Where the image is drawn (may extend past the bounds)
+    fn fitted_rect(&self) -> Rect {
+        let (image_width, image_height) = (self.image_surface.width().max(1) as f32, self.image_surface.height().max(1) as f32);
+        let (width, height) = (self.rect.width() as f32, self.rect.height() as f32);
+
+        match self.fit {
+            ImageFit::Stretch | ImageFit::Tile => self.rect,
+            ImageFit::Contain | ImageFit::Cover => {
+                let scale = if self.fit == ImageFit::Contain {
+                    (width / image_width).min(height / image_height)
+                } else {
+                    (width / image_width).max(height / image_height)
+                };
+                self.aligned_rect((image_width * scale).round() as u32, (image_height * scale).round() as u32)
+            }
+            ImageFit::Center => {
+                let (natural_width, natural_height) = self.natural_size();
+                self.aligned_rect(natural_width, natural_height)
+            }
+        }
+    }
+
+    /// Cache key & image to draw
+    /// - Vector images are rasterized at their drawn size (in physical pixels),
+    ///   so they are re-rasterized when the size or scale factor changes (releasing the previous size)
+    fn drawn_image(&self, image_cache: &mut ImageCache) -> (&'static str, Rc<Surface<'static>>) {
+        // Nine-slice borders are given in image pixels, so the intrinsic size is used
+        if !images::is_vector(self.resource_path) || self.nine_slice.is_some() {
+            return (self.image_key, self.image_surface.clone());
+        }
+
+        let size = match self.fit {
+            ImageFit::Tile | ImageFit::Center => self.natural_size(),
+            _ => self.fitted_rect().size(),
+        };
+
+        let mut raster = self.raster.borrow_mut();
+        match &*raster {
+            Some((raster_size, key, surface)) if *raster_size == size => (*key, surface.clone()),
+            previous => {
+                let (key, surface) = images::load_sized(self.resource_path, size, &self.effects)
+                    .expect("Failed to rasterize resource");
+                if let Some((_, previous_key, _)) = previous {
+                    if *previous_key != key {
+                        image_cache.release(previous_key);
+                    }
+                }
+                *raster = Some((size, key, surface.clone()));
+                (key, surface)
+            }
+        }
+    }
+
+    /// Draw the image according to its fit, clipped to its bounds
+    fn draw_image(&self, canvas: &mut WindowCanvas, texture: &Texture) {
+        if let Some(slice) = &self.nine_slice {
+            draw_nine_slice(canvas, texture, self.image_surface.size(), slice, self.rect);
+            return;
+        }
+
+        // Stay within any enclosing clip (such as a `SplitView` pane)
+        let previous_clip = canvas.clip_rect();
+        let clip = match previous_clip {
+            Some(previous) => match previous.intersection(self.rect) {
+                Some(clip) => clip,
+                None => return,
+            },
+            None => self.rect,
+        };
+        canvas.set_clip_rect(Some(clip));
+
+        if self.fit == ImageFit::Tile {
+            let (tile_width, tile_height) = self.natural_size();
+            let (tile_width, tile_height) = (tile_width.max(1), tile_height.max(1));
+
+            for y in (self.rect.top()..self.rect.bottom()).step_by(tile_height as usize) {
+                for x in (self.rect.left()..self.rect.right()).step_by(tile_width as usize) {
+                    canvas.copy(texture, None, Some(Rect::new(x, y, tile_width, tile_height))).expect("Failed to copy texture to target");
+                }
+            }
+        } else {
+            canvas.copy(texture, None, Some(self.fitted_rect())).expect("Failed to copy texture to target");
+        }
+
+        canvas.set_clip_rect(previous_clip);
+    }
+
+    /// Border or shade color for the given state, disregarding transitions
+    fn state_color(&self, widget_state: &WidgetState) -> Color {
+        match widget_state {
+            WidgetState::Hovering => self.hover_color,
+            WidgetState::Active => self.click_color,
+            _ => {
+                // Fade out from the hover color
+                let mut transparent = self.hover_color;
+                transparent.a = 0;
+                transparent
+            }
+        }
+    }
 }
 
 impl<T> Widget<T> for Image<T> {
@@ -104,6 +447,10 @@ impl<T> Widget<T> for Image<T> {
         self.id
     }
 
+    fn set_id(&mut self, id: &'static str) {
+        self.id = id;
+    }
+
     fn text_component(&mut self) -> Option<&mut super::Text<T>> {
         None
     }
@@ -127,52 +474,65 @@ impl<T> Widget<T> for Image<T> {
     where T: super::GenerateView<T> {
         // FIXME: There is a lot here that can be refactored and made more efficient
 
-        let mut draw_highlight = false;
-        let mut clicking = false;
+        let highlight = if self.highlight.has_target() {
+            self.highlight.value()
+        } else {
+            self.state_color(&widget_state)
+        };
 
-        match widget_state {
-            WidgetState::Hovering => {
-                window.canvas.set_draw_color(self.hover_color);
-
-                if !self.hover_border {
-                    draw_highlight = true;
-                }
-            }
-            WidgetState::Active => {
-                window.canvas.set_draw_color(self.click_color);
-
-                if !self.hover_border {
-                    draw_highlight = true;
-                    clicking = true;
-                }
-            }
-            _ => {}
-        }
+        window.canvas.set_draw_color(highlight);
+        window.canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
 
-        if !draw_highlight { // border
+        if self.hover_border && highlight.a > 0 {
             let border = Rect::new(self.rect.x() - self.hover_border_width as i32,  self.rect.y() - self.hover_border_width as i32,
                                    self.rect.width() + self.hover_border_width * 2, self.rect.height() + self.hover_border_width * 2);
             window.canvas.fill_rect(border).unwrap();
         }
 
-        // FIXME: Clean up rendering (no memory leaks apparent)
+        let (key, surface) = self.drawn_image(&mut window.image_cache);
+        let texture = window.image_cache.texture_of(key, &surface).expect("Failed to create image texture");
 
-        let texture_creator = window.canvas.texture_creator();
-        let texture = texture_creator.create_texture_from_surface(&self.image_surface)
-                        .expect("Failed to create image texture");
-
-        // let sdl2::render::TextureQuery { width, height, ..} = texture.query();
-
-        // FIXME: These will be scaled according to aspect ration later (done in Self::new())
-        let target = Rect::new(self.rect.x(), self.rect.y(), self.rect.width(), self.rect.height());
-
-        window.canvas.copy(&texture, None, Some(target)).expect("Failed to copy texture to target");
+        // The texture is shared with other widgets, so its modulation is reset after drawing
+        let alpha = (self.tint.a as f32 * self.opacity).round() as u8;
+        texture.set_color_mod(self.tint.r, self.tint.g, self.tint.b);
+        texture.set_alpha_mod(alpha);
+        if alpha < 255 {
+            // Opaque images are uploaded without blending
+            texture.set_blend_mode(sdl2::render::BlendMode::Blend);
+        }
+        self.draw_image(&mut window.canvas, texture);
+        texture.set_color_mod(255, 255, 255);
+        texture.set_alpha_mod(255);
     
-        if draw_highlight {
+        if !self.hover_border && highlight.a > 0 {
             // FIXME: Instead, render colored RGBA rect as surface, then blit with image.
-            window.canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
             window.canvas.fill_rect(self.rect).unwrap();
-            window.canvas.set_blend_mode(sdl2::render::BlendMode::None);
+        }
+
+        window.canvas.set_blend_mode(sdl2::render::BlendMode::None);
+    }
+
+    fn animate(&mut self, delta: f32, widget_state: &WidgetState) {
+        let target = self.state_color(widget_state);
+        self.highlight.set_target(target);
+        self.highlight.advance(delta);
+    }
+
+    fn persistent_state(&self) -> Option<Box<dyn Any>> {
+        let raster = self.raster.borrow().clone();
+        Some(Box::new((self.highlight.clone(), self.resource_path, self.effects, raster)))
+    }
+
+    /// Hover & press transitions continue across view regenerations
+    /// - The last raster is kept too, so that resizing (such as dragging a `SplitView`) releases the previous size
+    fn restore_state(&mut self, state: Box<dyn Any>) {
+        if let Ok(state) = state.downcast::<(Animated<Color>, &'static str, ImageEffects, Option<Raster>)>() {
+            let (highlight, resource_path, effects, raster) = *state;
+            self.highlight.resume(&highlight);
+
+            if resource_path == self.resource_path && effects == self.effects {
+                *self.raster.get_mut() = raster;
+            }
         }
     }
 
@@ -194,4 +554,28 @@ impl<T> IntoViewComponent<T> for Image<T> where T: 'static {
     fn as_component(self) -> ViewComponent<T> {
         ViewComponent::Widget(Box::new(self))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slice_edges_keep_borders() {
+        assert_eq!(slice_edges(0, 100, 10, 20, 1.0), [0, 10, 80, 100]);
+        assert_eq!(slice_edges(5, 100, 10, 20, 1.0), [5, 15, 85, 105]);
+    }
+
+    #[test]
+    fn slice_edges_scale_borders() {
+        assert_eq!(slice_edges(0, 100, 10, 20, 2.0), [0, 20, 60, 100]);
+        assert_eq!(slice_edges(0, 100, 3, 3, 1.5), [0, 5, 95, 100]);
+    }
+
+    #[test]
+    fn slice_edges_shrink_borders_to_fit() {
+        // Borders keep their proportions & fill the length exactly
+        assert_eq!(slice_edges(0, 30, 20, 40, 1.0), [0, 10, 10, 30]);
+        assert_eq!(slice_edges(0, 0, 4, 4, 1.0), [0, 0, 0, 0]);
+    }
+}
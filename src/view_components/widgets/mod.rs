@@ -4,17 +4,23 @@ pub mod text;
 pub mod textbox;
 pub mod scrollbar;
 pub mod image;
+pub mod canvas;
+pub mod rich_text;
+pub mod animated_image;
 
-pub use crate::backend::system::state::GenerateView;
+pub use crate::backend::system::state::{GenerateView, Update};
 
 // Simplified widget imports
 // Allows user to type `::widget::Name` or `::widget::*` rather than full namespaces
 pub use button::Button;
 pub use checkbox::CheckBox;
-pub use text::Text;
+pub use text::{Text, TextAlign};
 pub use textbox::TextBox;
 pub use scrollbar::ScrollBar;
-pub use image::Image;
+pub use image::{Image, ImageFit, NineSlice};
+pub use canvas::{Canvas, Painter};
+pub use rich_text::{RichText, Span};
+pub use animated_image::AnimatedImage;
 
 
 /*
@@ -27,6 +33,8 @@ use sdl2::event::Event;
 
 use crate::backend::system::window::Window;
 
+use std::any::Any;
+
 
 // ========================== WidgetState Enum ========================== //
 
@@ -64,6 +72,15 @@ pub trait Widget<T> {
     fn rect(&self) -> Rect;
     fn id(&self) -> &'static str;
 
+    /// Replace the widget's id (used to assign generated ids)
+    fn set_id(&mut self, _id: &'static str) {
+    }
+
+    /// Name of the widget's type (such as `"Button"`) for diagnostics & matching
+    fn kind(&self) -> &'static str {
+        crate::view_components::ids::short_type_name(std::any::type_name::<Self>())
+    }
+
     /// Whether the widget should grab focus when clicked
     fn can_focus(&self) -> bool {
         false
@@ -72,6 +89,10 @@ pub trait Widget<T> {
     /// Obtain a reference to a widget's text component for sizing/modifying
     fn text_component(&mut self) -> Option<&mut Text<T>>;
 
+    /// Size content which depends on loaded fonts (called when the view is initialized)
+    fn measure(&mut self, _ttf_context: &sdl2::ttf::Sdl2TtfContext) {
+    }
+
     /// Update the widget with known text dimensions  
     /// - Note that this function is called **only when text exists**  
     /// - Improper usage will therefore `panic` at `.expect()` on `None`
@@ -96,6 +117,28 @@ pub trait Widget<T> {
     fn update(&mut self, _state: &mut T, _event: &Event) {
     }
 
+    /// Receives every keyboard event regardless of focus (e.g.: shortcuts)
+    /// - `widget_state` tells whether the widget is focused or hovered, for shortcuts limited to those
+    fn on_key_event(&mut self, _state: &mut T, _event: &Event, _widget_state: &WidgetState) {
+    }
+
+    /// Receives mouse events over the widget, or anywhere while the widget is being clicked
+    fn on_mouse_event(&mut self, _state: &mut T, _event: &Event) {
+    }
+
+    /// Advance animations by `delta` seconds (called each frame before `render`)
+    fn animate(&mut self, _delta: f32, _widget_state: &WidgetState) {
+    }
+
+    /// Internal state to carry over to the matching widget of a regenerated view (see `reconcile`)
+    fn persistent_state(&self) -> Option<Box<dyn Any>> {
+        None
+    }
+
+    /// Receive the `persistent_state` of the matching widget of the previous view
+    fn restore_state(&mut self, _state: Box<dyn Any>) {
+    }
+
     fn cursor(&self) -> sdl2::mouse::Cursor {
         // TODO: Get this working within the backend
         sdl2::mouse::Cursor::from_system(sdl2::mouse::SystemCursor::Hand).expect("Failed to create mouse cursor")
@@ -0,0 +1,483 @@
+extern crate sdl2;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::ttf::{FontStyle, Sdl2TtfContext};
+
+use crate::view_components::{ViewComponent, IntoViewComponent};
+use crate::font::{self, FontParams, StyledMeasure, StyledText};
+use crate::backend::system::window::Window;
+use crate::view_components::theme;
+use crate::scale;
+
+use super::{Widget, WidgetState};
+use super::text::Text;
+
+
+// ========================== Span Struct ========================== //
+
+/// A styled section of a `RichText` paragraph
+/// - Font & color default to those of the `RichText`
+#[derive(Clone, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub color: Option<Color>,
+    pub font_path: Option<&'static str>,
+    pub point_size: Option<u16>,
+    pub style: FontStyle,
+    /// Highlight drawn behind the text
+    pub background: Option<Color>,
+}
+
+impl Span {
+    pub fn new(text: &str) -> Self {
+        Span {
+            text: String::from(text),
+            color: None,
+            font_path: None,
+            point_size: None,
+            style: FontStyle::NORMAL,
+            background: None,
+        }
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.style |= FontStyle::BOLD;
+        self
+    }
+
+    pub fn italic(mut self) -> Self {
+        self.style |= FontStyle::ITALIC;
+        self
+    }
+
+    pub fn underline(mut self) -> Self {
+        self.style |= FontStyle::UNDERLINE;
+        self
+    }
+
+    pub fn strikethrough(mut self) -> Self {
+        self.style |= FontStyle::STRIKETHROUGH;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Path to the span's font (`.ttf`)
+    pub fn with_font(mut self, path: &'static str) -> Self {
+        self.font_path = Some(path);
+        self
+    }
+
+    pub fn with_point_size(mut self, point_size: u16) -> Self {
+        self.point_size = Some(point_size);
+        self
+    }
+
+    /// Highlight the span with a background color
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+}
+
+/// Spans may be created from plain strings
+impl From<&str> for Span {
+    fn from(text: &str) -> Self {
+        Span::new(text)
+    }
+}
+
+
+// ========================== RichText Widget ========================== //
+
+/// Consecutive text of a single span on a single line
+struct Run {
+    span: usize,
+    text: String,
+    /// Position relative to the widget's top-left corner
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    /// Distance from the run's top to its baseline
+    ascent: i32,
+}
+
+/// Vertical metrics of a font
+#[derive(Clone, Copy, Default)]
+struct LineMetrics {
+    height: i32,
+    ascent: i32,
+    spacing: i32,
+}
+
+impl LineMetrics {
+    fn of(font: &StyledMeasure) -> Self {
+        LineMetrics {
+            height: font.height(),
+            ascent: font.ascent(),
+            spacing: font.recommended_line_spacing(),
+        }
+    }
+
+    fn max(self, other: LineMetrics) -> Self {
+        LineMetrics {
+            height: self.height.max(other.height),
+            ascent: self.ascent.max(other.ascent),
+            spacing: self.spacing.max(other.spacing),
+        }
+    }
+}
+
+/// Places runs line by line
+#[derive(Default)]
+struct LineLayout {
+    runs: Vec<Run>,
+    /// Runs of the current line
+    line: Vec<Run>,
+    /// Metrics of the tallest font on the current line
+    metrics: LineMetrics,
+    /// Top of the current line
+    top: i32,
+    /// End of the current line
+    x: i32,
+    /// Width of the widest line
+    width: i32,
+}
+
+impl LineLayout {
+    /// Append text to the current line
+    fn push(&mut self, span: usize, text: &str, width: i32, metrics: LineMetrics) {
+        self.metrics = self.metrics.max(metrics);
+
+        // Extend the previous run if it belongs to the same span
+        match self.line.last_mut() {
+            Some(run) if run.span == span => {
+                run.text.push_str(text);
+                run.width += width as u32;
+            }
+            _ => self.line.push(Run {
+                span,
+                text: text.to_owned(),
+                x: self.x,
+                y: 0,
+                width: width as u32,
+                height: metrics.height as u32,
+                ascent: metrics.ascent,
+            }),
+        }
+
+        self.x += width;
+    }
+
+    /// Position the current line's runs & begin a new line
+    fn finish_line(&mut self) {
+        for mut run in self.line.drain(..) {
+            // Align baselines
+            run.y = self.top + self.metrics.ascent - run.ascent;
+            self.runs.push(run);
+        }
+
+        self.top += self.metrics.spacing.max(self.metrics.height);
+        self.width = self.width.max(self.x);
+        self.metrics = LineMetrics::default();
+        self.x = 0;
+    }
+}
+
+/// RichText Widget
+/// - Displays a paragraph made of differently styled `Span`s
+/// - Lines are broken between words when a wrap width is assigned (and within words too long for a line)
+pub struct RichText<T> {
+    id: &'static str,
+    rect: Rect,
+    spans: Vec<Span>,
+    /// Defaults for spans without their own color or font
+    color: Color,
+    font: FontParams,
+    wrap_width: Option<u32>,
+
+    /// Layout computed by `measure`
+    runs: Vec<Run>,
+    measured: bool,
+
+    phantom: std::marker::PhantomData<T>,
+}
+
+impl<T> RichText<T> {
+    pub fn new<S: Into<Span>>(id: &'static str, spans: Vec<S>) -> Self {
+        let (color, font) = theme::with_current(|theme| {
+            // Styled like `Text` (`[Text]` & `Text#id` sections)
+            let style = theme.styled(&theme.text_style(), "Text", id);
+            (style.color, theme.font)
+        });
+
+        RichText {
+            id,
+            rect: Rect::new(0, 0, 0, scale::px(28)), // FIXME: 28 is only true for default font
+            spans: spans.into_iter().map(|span| span.into()).collect(),
+            color,
+            font,
+            wrap_width: None,
+            runs: Vec::new(),
+            measured: false,
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Wrap text between words so that no line is wider than `width` pixels
+    pub fn with_wrap_width(mut self, width: u32) -> Self {
+        self.wrap_width = Some(scale::px(width));
+        self
+    }
+
+    /// Default color of spans
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Default point size of spans
+    pub fn with_point_size(mut self, point_size: u16) -> Self {
+        self.font.point_size = point_size;
+        self
+    }
+
+    pub fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    fn span_font(&self, span: &Span) -> FontParams {
+        FontParams::new(
+            span.font_path.unwrap_or(self.font.path),
+            span.point_size.unwrap_or(self.font.point_size),
+        )
+    }
+
+    fn span_color(&self, span: &Span) -> Color {
+        span.color.unwrap_or(self.color)
+    }
+
+    /// Break the spans into lines of runs
+    /// - Returns the runs & the paragraph's (width, height)
+    /// - Fonts are measured through `font::with_styled_metrics`, so regenerated views do not reload them
+    fn layout(&self, ttf_context: &Sdl2TtfContext) -> (Vec<Run>, u32, u32) {
+        let mut lines = LineLayout::default();
+        // Whitespace is only placed once a word follows it on the same line: (span, text, width, metrics)
+        let mut pending_space: Option<(usize, &str, i32, LineMetrics)> = None;
+
+        for (index, span) in self.spans.iter().enumerate() {
+            font::with_styled_metrics(ttf_context, &self.span_font(span), span.style, |styled| {
+                let metrics = LineMetrics::of(styled);
+                let mut measure = |text: &str| styled.width(text) as i32;
+
+                for (paragraph_index, paragraph) in span.text.split('\n').enumerate() {
+                    if paragraph_index > 0 {
+                        // Empty lines still take the font's height
+                        if lines.line.is_empty() {
+                            lines.metrics = lines.metrics.max(metrics);
+                        }
+                        lines.finish_line();
+                        pending_space = None;
+                    }
+
+                    for word in split_words(paragraph) {
+                        if word.trim().is_empty() {
+                            if lines.x > 0 {
+                                pending_space = Some((index, word, measure(word), metrics));
+                            }
+                            continue;
+                        }
+
+                        let word_width = measure(word);
+                        let space_width = pending_space.map_or(0, |(_, _, width, _)| width);
+
+                        if let Some(wrap_width) = self.wrap_width {
+                            if lines.x > 0 && lines.x + space_width + word_width > wrap_width as i32 {
+                                lines.finish_line();
+                                pending_space = None;
+                            }
+                        }
+
+                        if let Some((space_index, space, space_width, space_metrics)) = pending_space.take() {
+                            lines.push(space_index, space, space_width, space_metrics);
+                        }
+
+                        // Break words which do not fit on a line of their own (see `font::break_lines`)
+                        match self.wrap_width {
+                            Some(wrap_width) if word_width > wrap_width as i32 => {
+                                let mut pieces = font::break_word(word, wrap_width, |piece| measure(piece).max(0) as u32);
+                                let last = pieces.pop().unwrap_or_default();
+
+                                for piece in &pieces {
+                                    lines.push(index, piece, measure(piece), metrics);
+                                    lines.finish_line();
+                                }
+                                lines.push(index, &last, measure(&last), metrics);
+                            }
+                            _ => lines.push(index, word, word_width, metrics),
+                        }
+                    }
+                }
+            });
+        }
+
+        lines.finish_line();
+        (lines.runs, lines.width.max(0) as u32, lines.top.max(0) as u32)
+    }
+}
+
+/// Split text into words & the whitespace between them
+fn split_words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut in_space = None;
+
+    for (index, character) in text.char_indices() {
+        let is_space = character.is_whitespace();
+        if in_space.is_some() && in_space != Some(is_space) {
+            words.push(&text[start..index]);
+            start = index;
+        }
+        in_space = Some(is_space);
+    }
+
+    if start < text.len() {
+        words.push(&text[start..]);
+    }
+
+    words
+}
+
+impl<T> Widget<T> for RichText<T> {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn set_id(&mut self, id: &'static str) {
+        self.id = id;
+    }
+
+    fn text_component(&mut self) -> Option<&mut Text<T>> {
+        None
+    }
+
+    fn measure(&mut self, ttf_context: &Sdl2TtfContext) {
+        if self.measured {
+            return;
+        }
+
+        let (runs, width, height) = self.layout(ttf_context);
+        self.runs = runs;
+        self.rect.set_width(width.max(1));
+        self.rect.set_height(height.max(1));
+        self.measured = true;
+    }
+
+    fn place(&mut self, x: i32, y: i32) {
+        self.rect.set_x(x);
+        self.rect.set_y(y);
+    }
+
+    fn render(&self, window: &mut Window<T>, _widget_state: WidgetState)
+    where T: super::GenerateView<T> {
+        for run in &self.runs {
+            let span = &self.spans[run.span];
+            let target = Rect::new(self.rect.x() + run.x, self.rect.y() + run.y, run.width, run.height);
+            let color = self.span_color(span);
+
+            if let Some(background) = span.background {
+                window.canvas.set_draw_color(background);
+                window.canvas.fill_rect(target).unwrap();
+            }
+
+            // Glyphs are cached without decorations, which are drawn across the whole run instead
+            let decorations = FontStyle::UNDERLINE | FontStyle::STRIKETHROUGH;
+            let styled = StyledText { text: &run.text, font: self.span_font(span), style: span.style - decorations, color };
+            window.glyph_atlas.draw_styled(&mut window.canvas, &window.ttf_context, &styled, (target.x(), target.y()));
+
+            let thickness = (run.height / 16).max(1);
+            let baseline = target.y() + run.ascent;
+            window.canvas.set_draw_color(color);
+
+            if span.style.contains(FontStyle::UNDERLINE) {
+                let line = Rect::new(target.x(), baseline + thickness as i32, run.width, thickness);
+                window.canvas.fill_rect(line).unwrap();
+            }
+            if span.style.contains(FontStyle::STRIKETHROUGH) {
+                let line = Rect::new(target.x(), baseline - run.ascent / 3, run.width, thickness);
+                window.canvas.fill_rect(line).unwrap();
+            }
+        }
+    }
+
+    fn translate(&mut self, dx: i32, dy: i32) {
+        self.rect.offset(dx, dy);
+    }
+
+    fn draw_width(&self) -> u32 {
+        self.rect.width()
+    }
+
+    fn draw_height(&self) -> u32 {
+        self.rect.height()
+    }
+}
+
+impl<T> IntoViewComponent<T> for RichText<T> where T: 'static {
+    fn as_component(self) -> ViewComponent<T> {
+        ViewComponent::Widget(Box::new(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(height: i32, ascent: i32) -> LineMetrics {
+        LineMetrics { height, ascent, spacing: height + 2 }
+    }
+
+    #[test]
+    fn split_words_keeps_whitespace() {
+        assert_eq!(split_words("one  two three"), vec!["one", "  ", "two", " ", "three"]);
+        assert_eq!(split_words(" a "), vec![" ", "a", " "]);
+        assert!(split_words("").is_empty());
+    }
+
+    #[test]
+    fn line_layout_merges_runs_of_a_span() {
+        let mut lines = LineLayout::default();
+        lines.push(0, "one", 30, metrics(20, 16));
+        lines.push(0, " ", 5, metrics(20, 16));
+        lines.push(1, "two", 30, metrics(20, 16));
+        lines.finish_line();
+
+        let runs: Vec<(usize, &str, i32, u32)> = lines.runs.iter().map(|run| (run.span, run.text.as_str(), run.x, run.width)).collect();
+        assert_eq!(runs, vec![(0, "one ", 0, 35), (1, "two", 35, 30)]);
+        assert_eq!(lines.width, 65);
+    }
+
+    #[test]
+    fn line_layout_aligns_baselines() {
+        let mut lines = LineLayout::default();
+        lines.push(0, "small", 50, metrics(20, 16));
+        lines.push(1, "large", 80, metrics(40, 32));
+        lines.finish_line();
+        lines.push(0, "next", 40, metrics(20, 16));
+        lines.finish_line();
+
+        let positions: Vec<i32> = lines.runs.iter().map(|run| run.y).collect();
+        // The first line is as tall as its largest font (its spacing is 42)
+        assert_eq!(positions, vec![16, 0, 42]);
+        assert_eq!(lines.top, 42 + 22);
+        assert_eq!(lines.width, 130);
+    }
+}
@@ -5,7 +5,8 @@ use sdl2::event::Event;
 
 use crate::view_components::{ViewComponent, IntoViewComponent};
 use crate::backend::system::window::Window;
-use crate::colors;
+use crate::view_components::theme;
+use crate::scale;
 use crate::Orientation;
 
 use super::{Widget, WidgetState};
@@ -38,6 +39,8 @@ pub struct ScrollBar<T> {
 
 impl<T> ScrollBar<T> {
     pub fn new(id: &'static str, from: i32, to: i32, current: i32) -> Self {
+        let style = theme::with_current(|theme| theme.styled(&theme.scrollbar, "ScrollBar", id));
+
         ScrollBar {
             id: id,
 
@@ -45,18 +48,18 @@ impl<T> ScrollBar<T> {
             to: to,
             value: current,
 
-            slider: Rect::new(0, 0, 12, 16),
-            rail: Rect::new(0, 0, 120, 4),
+            slider: Rect::new(0, 0, scale::px(12), scale::px(16)),
+            rail: Rect::new(0, 0, scale::px(120), scale::px(4)),
 
             value_text: None,
 
             on_value_changed: None,
 
-            slider_passive_color: colors::MANILLA,
-            slider_hover_color: Color::RGB(200, 200, 200),
-            slider_active_color: colors::DARK_GRAY,
-            rail_passive_color: colors::LIGHT_GRAY,
-            rail_hover_color: colors::WHITE,
+            slider_passive_color: style.slider,
+            slider_hover_color: style.slider_hover,
+            slider_active_color: style.slider_active,
+            rail_passive_color: style.rail,
+            rail_hover_color: style.rail_hover,
             
             orientation: Orientation::Horizontal,
         }
@@ -68,12 +71,12 @@ impl<T> ScrollBar<T> {
     }
 
     pub fn with_length(mut self, length: u32) -> Self {
-        self.rail.set_width(length);
+        self.rail.set_width(scale::px(length));
         self
     }
 
     pub fn with_thickness(mut self, thickness: u32) -> Self {
-        self.rail.set_height(thickness);
+        self.rail.set_height(scale::px(thickness));
         self
     }
 
@@ -83,6 +86,13 @@ impl<T> ScrollBar<T> {
         self
     }
 
+    /// Send the message created by `to_message` to the state's `update` function when the value changes
+    pub fn with_on_value_changed_message<F: 'static + Fn(i32) -> T::Message>
+    (self, to_message: F) -> Self
+    where T: super::Update {
+        self.with_on_value_changed(move |state: &mut T, value| state.update(to_message(value)))
+    }
+
     /// Maps slider location relative to the rail to slider value
     fn pixel_to_value(&self, pixel: i32) -> i32 {
         match self.orientation {
@@ -127,6 +137,10 @@ impl<T> Widget<T> for ScrollBar<T> {
         self.id
     }
 
+    fn set_id(&mut self, id: &'static str) {
+        self.id = id;
+    }
+
     fn text_component(&mut self) -> Option<&mut Text<T>> {
         self.value_text.as_mut()
     }
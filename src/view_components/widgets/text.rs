@@ -1,11 +1,13 @@
 extern crate sdl2;
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
+use sdl2::ttf::FontStyle;
 
 use crate::view_components::{ViewComponent, IntoViewComponent};
-use crate::font::FontParams;
+use crate::font::{self, FontParams, TextWrap, TextLine};
 use crate::backend::system::window::Window;
-use crate::colors;
+use crate::view_components::theme;
+use crate::scale;
 
 use super::{Widget, WidgetState};
 
@@ -13,12 +15,29 @@ use super::{Widget, WidgetState};
 // TTF is undocumented on sdl2 crate docs.
 
 
+/// Horizontal alignment of each line within multi-line text
+/// ## Alignments
+/// - `Left` - Lines begin at the left edge (default)
+/// - `Center` - Lines are centered
+/// - `Right` - Lines end at the right edge
+/// - `Justify` - Words are spaced to fill the width (except the last line of each paragraph)
+#[derive(Clone, Copy, PartialEq)]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+    Justify,
+}
+
 pub struct Text<T> {
     id: &'static str,
     pub container_rect: Rect,
     primary_color: Color,
     pub text: String,
     pub font: FontParams,
+    /// Line breaking & truncation
+    pub wrap: TextWrap,
+    text_align: TextAlign,
     // How far text must be from its boundary
     internal_padding: u32,
 
@@ -30,6 +49,8 @@ pub struct Text<T> {
     // Text surface parameters    
     pub text_width: u32,
     pub text_height: u32,
+    /// Whether the text surface has been sized (see `assign_text_dimensions`)
+    measured: bool,
 
     // FIXME: This is a hack to fix layout issues
     //  This is caused by layout seeing only the default text_width & text_height
@@ -39,20 +60,27 @@ pub struct Text<T> {
 
 impl<T> Text<T> {
     pub fn new(id: &'static str, text: &str) -> Self {
+        let (style, font) = theme::with_current(|theme| {
+            (theme.styled(&theme.text_style(), "Text", id), theme.font)
+        });
+
         Text {
             id: id,
-            container_rect: Rect::new(0, 0, 0, 28), // FIXME: 28 is only true for default font
-            primary_color: colors::BLACK,
+            container_rect: Rect::new(0, 0, 0, scale::px(28)), // FIXME: 28 is only true for default font
+            primary_color: style.color,
             text: String::from(text),
-            font: FontParams::default_font(),
-            internal_padding: 10,
+            font,
+            wrap: TextWrap::default(),
+            text_align: TextAlign::Left,
+            internal_padding: scale::px(style.padding),
             update_fn: None,
             auto_resize: false,
             center_text: false,
             // FIXME: Defaults are not safe. Should be assigned when building view
-            text_width: 100,
-            text_height: 40,
-            default_width: 100,
+            text_width: scale::px(100),
+            text_height: scale::px(40),
+            measured: false,
+            default_width: scale::px_i32(100),
         }
     }
 
@@ -88,11 +116,79 @@ impl<T> Text<T> {
         self
     }
 
+    /// Wrap text between words so that no line is wider than `width` pixels
+    pub fn with_wrap_width(mut self, width: u32) -> Self {
+        self.wrap.max_width = Some(scale::px(width));
+        self
+    }
+
+    /// Display at most `lines` lines, ending truncated text with an ellipsis
+    pub fn with_max_lines(mut self, lines: usize) -> Self {
+        self.wrap.max_lines = Some(lines);
+        self
+    }
+
+    /// Align lines of multi-line text (see `TextAlign`)
+    pub fn with_text_align(mut self, text_align: TextAlign) -> Self {
+        self.text_align = text_align;
+        self
+    }
+
     pub fn auto_resize(mut self) -> Self {
         self.auto_resize = true;
         self
     }
 
+    /// Whether the text surface has been sized
+    pub fn is_measured(&self) -> bool {
+        self.measured
+    }
+
+    /// Unscaled target of the text within its container
+    fn text_rect(&self) -> Rect {
+        // Text taller than its container (such as wrapped text) extends downwards only
+        let y = self.container_rect.y() + ((self.container_rect.height() as i32 - self.text_height as i32) / 2).max(0);
+
+        if self.center_text {
+            // Center the text, disregarding containers
+            let center_x = self.container_rect.x() + self.default_width / 2;
+            Rect::new(center_x - self.text_width as i32 / 2, y, self.text_width, self.text_height)
+        } else {
+            // Center the text's y position and align left
+            Rect::new(self.container_rect.x(), y, self.text_width, self.text_height)
+        }
+    }
+
+    /// Render a single line of text with its top-left corner at (x, y)
+    fn render_line(&self, window: &mut Window<T>, line: &str, x: i32, y: i32)
+    where T: super::GenerateView<T> {
+        window.glyph_atlas.draw_text(&mut window.canvas, &window.ttf_context, &self.font, line, self.primary_color, (x, y));
+    }
+
+    /// Render a line with its words spaced to fill `width`
+    fn render_justified(&self, window: &mut Window<T>, line: &TextLine, x: i32, y: i32, width: u32)
+    where T: super::GenerateView<T> {
+        let words: Vec<&str> = line.text.split(' ').collect();
+        if words.len() < 2 {
+            self.render_line(window, &line.text, x, y);
+            return;
+        }
+
+        let word_widths: Vec<u32> = words.iter()
+            .map(|word| window.glyph_atlas.size_text(&window.ttf_context, &self.font, FontStyle::NORMAL, word).0)
+            .collect();
+        let free_space = width as i32 - word_widths.iter().sum::<u32>() as i32;
+        let gaps = words.len() as i32 - 1;
+
+        let mut word_x = x;
+        for (index, word) in words.iter().enumerate() {
+            self.render_line(window, word, word_x, y);
+            // Distribute the remainder over the first gaps
+            let gap = free_space / gaps + if (index as i32) < free_space % gaps { 1 } else { 0 };
+            word_x += word_widths[index] as i32 + gap;
+        }
+    }
+
     // TODO: This only rescales the text in one dimension. It should rescale both dimensions by the same factor
     // TODO: Update this with padding_left, padding_right, padding_top, padding_bottom when implemented
     fn fit_and_center_within_container(&self, container_rect: &Rect) -> Rect {
@@ -143,6 +239,7 @@ impl<T> Widget<T> for Text<T> {
     fn assign_text_dimensions(&mut self, dims: (u32, u32)) {
         self.text_width = dims.0;
         self.text_height = dims.1;
+        self.measured = true;
     }
 
     fn translate(&mut self, dx: i32, dy: i32) {
@@ -162,6 +259,10 @@ impl<T> Widget<T> for Text<T> {
         self.id
     }
 
+    fn set_id(&mut self, id: &'static str) {
+        self.id = id;
+    }
+
     // TODO: Resize the text surface on update
     fn update(&mut self, state: &mut T, _event: &sdl2::event::Event) {
         if let Some(ref update_callback) = self.update_fn {
@@ -171,48 +272,44 @@ impl<T> Widget<T> for Text<T> {
 
     fn render(&self, window: &mut Window<T>, _widget_state: WidgetState)
     where T: super::GenerateView<T> {
-        // FIXME: Allocating texture_creator here is probably bad if we use it each render
-        let texture_creator = window.canvas.texture_creator();
-
-        // FIXME: Same here.
-        // TODO: Implement font.rs
-        let font = window.ttf_context.load_font(
-            std::path::Path::new("./res/font/OpenSans-Regular.ttf"), 
-            self.font.point_size
-        ).expect("Failed to load font");
-
-        let surface = font.render(&self.text)
-            .blended(self.primary_color).unwrap();
+        // Multi-line text is laid out line by line
+        if self.wrap != TextWrap::default() || self.text.contains('\n') {
+            let (atlas, ttf_context) = (&mut window.glyph_atlas, &window.ttf_context);
+            let lines = font::break_lines(&self.text, &self.wrap, |line| {
+                atlas.size_text(ttf_context, &self.font, FontStyle::NORMAL, line).0
+            });
+            let line_spacing = atlas.line_spacing(ttf_context, &self.font);
+            let block = self.text_rect();
+
+            for (index, line) in lines.iter().enumerate() {
+                let y = block.y() + line_spacing * index as i32;
+
+                if self.text_align == TextAlign::Justify && !line.ends_paragraph {
+                    self.render_justified(window, line, block.x(), y, block.width());
+                    continue;
+                }
+
+                let x = match self.text_align {
+                    TextAlign::Left | TextAlign::Justify => block.x(),
+                    TextAlign::Center => block.x() + (block.width() as i32 - line.width as i32) / 2,
+                    TextAlign::Right => block.x() + block.width() as i32 - line.width as i32,
+                };
+                self.render_line(window, &line.text, x, y);
+            }
 
-        let texture = texture_creator.create_texture_from_surface(surface).expect("Failed to create texture");
-        // let TextureQuery { width, height, .. } = texture.query();
+            return;
+        }
 
         // println!("Rendering '{}' with containter {}x{} at ({}, {}) and text size {}x{}", self.text, self.container_rect.width(), self.container_rect.height(), self.container_rect.x(), self.container_rect.y(), self.text_width, self.text_height);
 
-        let target = if self.auto_resize {
+        if self.auto_resize {
             // Center text within container & downscale if too large
-            self.fit_and_center_within_container(&self.container_rect)
-        } else if self.center_text {
-            // Center the text, disregarding containers
-            let center_x = self.container_rect.x() + self.default_width / 2;
-            let target_x = center_x - self.text_width as i32 / 2;
-            Rect::new(
-                target_x,
-                self.container_rect.y() + ((self.container_rect.height() as i32 - self.text_height as i32) / 2),
-                self.text_width,
-                self.text_height
-            )
+            let target = self.fit_and_center_within_container(&self.container_rect);
+            window.glyph_atlas.draw_text_within(&mut window.canvas, &window.ttf_context, &self.font, &self.text, self.primary_color, target);
         } else {
-            // Center the text's y position and align left
-            Rect::new(
-                self.container_rect.x(),
-                self.container_rect.y() + ((self.container_rect.height() as i32 - self.text_height as i32) / 2),
-                self.text_width,
-                self.text_height
-            )
-        };
-
-        window.canvas.copy(&texture, None, Some(target)).unwrap();
+            let target = self.text_rect();
+            self.render_line(window, &self.text, target.x(), target.y());
+        }
     }
 
     fn draw_width(&self) -> u32 {
@@ -220,14 +317,17 @@ impl<T> Widget<T> for Text<T> {
     }
 
     fn draw_height(&self) -> u32 {
-        // FIXME: Until widget data can persist between render cycles, text size is lost,
-        //  meaning self.text_height resets to 40
+        // Unmeasured text uses a default height (see `View::init`)
 
         //println!("Text: {} has height {}", self.text, self.text_height);
-        
-        self.text_height
 
-        // 28
+        if self.auto_resize {
+            // Scaled to fit its container
+            self.container_rect.height()
+        } else {
+            // Short text is centered within its container
+            self.text_height.max(self.container_rect.height())
+        }
     }
 }
 
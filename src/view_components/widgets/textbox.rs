@@ -6,7 +6,8 @@ use sdl2::keyboard::Keycode;
 
 use crate::view_components::{ViewComponent, IntoViewComponent};
 use crate::backend::system::window::Window;
-use crate::colors;
+use crate::view_components::theme;
+use crate::scale;
 
 use super::{Widget, WidgetState};
 use super::Text;
@@ -20,6 +21,9 @@ pub struct TextBox<T> {
     background_color: Color,
     // hover_color: Color,
     focus_color: Color,
+    cursor_color: Color,
+    /// Space between the left edge & the text
+    padding: u32,
 
     default_text: Text<T>,
     user_text: Text<T>,
@@ -38,12 +42,16 @@ pub struct TextBox<T> {
 
 impl<T> TextBox<T> {
     pub fn new(id: &'static str, text: &str) -> Self {
+        let style = theme::with_current(|theme| theme.styled(&theme.textbox, "TextBox", id));
+
         TextBox {
             id: id,
-            rect: Rect::new(0, 0, 100, 40),
-            background_color: colors::LIGHT_GRAY,
+            rect: Rect::new(0, 0, scale::px(100), scale::px(40)),
+            background_color: style.background,
             // hover_color: 
-            focus_color: colors::WHITE,
+            focus_color: style.focus,
+            cursor_color: style.cursor,
+            padding: scale::px(style.padding),
 
             default_text: Text::new("", ""),
             user_text: Text::new("", text),
@@ -57,7 +65,7 @@ impl<T> TextBox<T> {
 
     pub fn with_default_text(mut self, text: &str) -> Self {
         let owned_text = Text::new("", text)
-            .with_color(colors::DARK_GRAY);
+            .with_color(theme::with_current(|theme| theme.palette.muted_text));
 
         self.default_text = owned_text;
         self
@@ -79,6 +87,20 @@ impl<T> TextBox<T> {
         self.on_text_submit = Some(Box::new(callback));
         self
     }
+
+    /// Send the message created by `to_message` to the state's `update` function when text input changes
+    pub fn with_on_text_changed_message<F: 'static + Fn(String) -> T::Message>
+    (self, to_message: F) -> Self
+    where T: super::Update {
+        self.with_on_text_changed(move |state: &mut T, text| state.update(to_message(text)))
+    }
+
+    /// Send the message created by `to_message` to the state's `update` function when Enter is pressed
+    pub fn with_on_text_submit_message<F: 'static + Fn(String) -> T::Message>
+    (self, to_message: F) -> Self
+    where T: super::Update {
+        self.with_on_text_submit(move |state: &mut T, text| state.update(to_message(text)))
+    }
 }
 
 impl<T> Widget<T> for TextBox<T> {
@@ -90,6 +112,10 @@ impl<T> Widget<T> for TextBox<T> {
         self.id
     }
 
+    fn set_id(&mut self, id: &'static str) {
+        self.id = id;
+    }
+
     fn text_component(&mut self) -> Option<&mut Text<T>> {
         // FIXME: Clean this up
 
@@ -129,7 +155,7 @@ impl<T> Widget<T> for TextBox<T> {
 
         // Draw cursor
         if draw_cursor {
-            let cursor_height = 20;
+            let cursor_height = scale::px_i32(20);
             
             // FIXME: This is a hack because of default text sizing
             let cursor_x = if self.user_text.text == "" {
@@ -138,12 +164,12 @@ impl<T> Widget<T> for TextBox<T> {
                 self.user_text.rect().x + self.user_text.text_width as i32 + 1
             };
 
-            window.canvas.set_draw_color(colors::BLACK);
+            window.canvas.set_draw_color(self.cursor_color);
             window.canvas.fill_rect(
                 Rect::new(
                     cursor_x,
                     self.user_text.rect().y + self.user_text.rect().height() as i32 / 2 - cursor_height / 2,
-                    3,
+                    scale::px(3),
                     cursor_height as u32
                 )
             ).unwrap();
@@ -170,8 +196,11 @@ impl<T> Widget<T> for TextBox<T> {
         self.rect.set_x(x);
         self.rect.set_y(y);
 
-        self.default_text.container_rect = self.rect;
-        self.user_text.container_rect = self.rect;
+        // The text is inset by the padding on both sides
+        let padding = self.padding.min(self.rect.width() / 2);
+        let text_rect = Rect::new(x + padding as i32, y, self.rect.width() - padding * 2, self.rect.height());
+        self.default_text.container_rect = text_rect;
+        self.user_text.container_rect = text_rect;
     }
 
     fn draw_width(&self) -> u32 {